@@ -0,0 +1,129 @@
+//! Costs of collecting, encoding and recording memory statistics.
+//!
+//! Every group reports throughput in calls per second. On Linux
+//! `collection/os_baseline` only reads /proc/meminfo, so its gap to
+//! `collection/get_memory_stats` is the crate's own overhead. The
+//! fragmentation group also prints how far each pattern moves
+//! `fragmentation_score()`.
+//!
+//! `cargo bench --bench memory_bench --features prometheus`
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use memory_core::memory::cache::CachedMemoryStats;
+use memory_core::memory::history::MemoryHistory;
+use memory_core::memory::{
+    defragment_memory, defragment_with_strategy, fragmentation_score, get_memory_stats, measure_fragmentation,
+    simulate_memory_fragmentation, DefragStrategy, FragmentationPattern,
+};
+
+fn bench_collection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collection");
+    group.throughput(Throughput::Elements(1));
+
+    // The read syscalls behind get_memory_stats, without parsing
+    #[cfg(target_os = "linux")]
+    group.bench_function("os_baseline", |b| b.iter(|| black_box(std::fs::read("/proc/meminfo").unwrap())));
+
+    group.bench_function("get_memory_stats", |b| b.iter(get_memory_stats));
+
+    let cache = CachedMemoryStats::new(Duration::from_secs(1));
+    group.bench_function("cached", |b| b.iter(|| black_box(cache.get())));
+
+    group.finish();
+}
+
+#[cfg(any(feature = "serde", feature = "prometheus"))]
+fn bench_encoding(c: &mut Criterion) {
+    let stats = get_memory_stats();
+    let mut group = c.benchmark_group("encoding");
+    group.throughput(Throughput::Elements(1));
+
+    #[cfg(feature = "serde")]
+    group.bench_function("to_json", |b| b.iter(|| serde_json::to_string(black_box(&stats)).unwrap()));
+
+    #[cfg(feature = "prometheus")]
+    group.bench_function("format_prometheus", |b| {
+        b.iter(|| memory_core::memory::format_prometheus(black_box(&stats)))
+    });
+
+    group.finish();
+}
+
+#[cfg(not(any(feature = "serde", feature = "prometheus")))]
+fn bench_encoding(_: &mut Criterion) {}
+
+fn bench_history_push(c: &mut Criterion) {
+    let stats = get_memory_stats();
+    let mut history = MemoryHistory::new(1024);
+    for _ in 0..history.capacity() {
+        history.push(stats.clone());
+    }
+
+    let mut group = c.benchmark_group("history");
+    group.throughput(Throughput::Elements(1));
+    // The history is full, so every push also evicts the oldest sample
+    group.bench_function("push", |b| b.iter(|| history.push(black_box(stats.clone()))));
+    group.finish();
+}
+
+fn bench_defragmentation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("defragmentation");
+    group.throughput(Throughput::Elements(1));
+    // Each call samples stats twice around a trim, so fewer samples suffice
+    group.sample_size(10);
+
+    group.bench_function("defragment_memory", |b| b.iter(|| defragment_memory(None)));
+    group.bench_function("defragment_with_strategy", |b| {
+        b.iter(|| defragment_with_strategy(DefragStrategy::MallocTrim(0)))
+    });
+
+    group.finish();
+}
+
+fn bench_fragmentation_patterns(c: &mut Criterion) {
+    // 256 KiB blocks are mmap'd one by one, so every hole reaches the kernel
+    const COUNT: i32 = 256;
+    const SIZE_KB: i32 = 256;
+    let patterns = [
+        FragmentationPattern::EveryThird,
+        FragmentationPattern::Random(0.5),
+        FragmentationPattern::Checkerboard,
+        FragmentationPattern::LargeSmallAlternating,
+    ];
+
+    let mut group = c.benchmark_group("fragmentation");
+    group.throughput(Throughput::Elements(1));
+    group.sample_size(10);
+
+    for &pattern in patterns.iter() {
+        if let Some(sample) = measure_fragmentation(COUNT, SIZE_KB, pattern) {
+            println!(
+                "{:?}: fragmentation_score {:.4} before, {:.4} with {} holes open, {:.4} after",
+                pattern,
+                sample.score_before,
+                sample.score_with_holes,
+                sample.holes,
+                fragmentation_score()
+            );
+        }
+
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", pattern)), &pattern, |b, &pattern| {
+            b.iter(|| simulate_memory_fragmentation(COUNT, SIZE_KB, pattern))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_collection,
+    bench_encoding,
+    bench_history_push,
+    bench_defragmentation,
+    bench_fragmentation_patterns
+);
+criterion_main!(benches);
@@ -0,0 +1,100 @@
+"""Tests for the native self_healing_memory module.
+
+Build and install the module with `maturin develop` from bindings/python,
+then run `pytest`.
+"""
+
+import self_healing_memory
+from self_healing_memory import MemoryHistory
+
+
+def sample(used_percent, timestamp=""):
+    """Dict in the shape returned by get_memory_stats, for a 1000-byte system."""
+    used = int(used_percent * 10)
+    return {
+        "total": 1000,
+        "free": 1000 - used,
+        "available": 1000 - used,
+        "used": used,
+        "used_percent": used_percent,
+        "timestamp": timestamp,
+    }
+
+
+def test_get_memory_stats_reports_consistent_totals():
+    stats = self_healing_memory.get_memory_stats()
+
+    for key in ("total", "free", "available", "used", "used_percent", "timestamp"):
+        assert key in stats
+    assert stats["total"] > 0
+    assert stats["used"] <= stats["total"]
+    assert 0.0 <= stats["used_percent"] <= 100.0
+
+
+def test_release_memory_cache_returns_bool():
+    # Usually False without root; only the type is stable across hosts
+    assert isinstance(self_healing_memory.release_memory_cache(), bool)
+
+
+def test_history_keeps_the_newest_samples():
+    history = MemoryHistory(2)
+    for used_percent in (10.0, 20.0, 30.0):
+        history.push(sample(used_percent))
+
+    assert len(history) == 2
+    assert [stats["used_percent"] for stats in history.samples()] == [20.0, 30.0]
+
+
+def test_history_push_without_stats_takes_a_sample():
+    history = MemoryHistory(4)
+    history.push()
+
+    samples = history.samples()
+    assert len(samples) == 1
+    assert samples[0]["total"] > 0
+
+
+def test_history_samples_round_trip_optional_fields():
+    history = MemoryHistory(1)
+    stats = sample(50.0, "2024-01-01T00:00:00.000Z")
+    stats["cached"] = 100
+    history.push(stats)
+
+    pushed = history.samples()[0]
+    assert pushed["cached"] == 100
+    assert pushed["buffers"] is None
+    assert pushed["timestamp"] == "2024-01-01T00:00:00.000Z"
+
+
+def test_history_trend():
+    history = MemoryHistory(8)
+    assert history.trend() is None
+
+    for used_percent in (10.0, 11.0, 12.0, 13.0):
+        history.push(sample(used_percent))
+    direction, slope = history.trend()
+    assert direction == "rising"
+    assert abs(slope - 1.0) < 1e-9
+    assert history.trend(min_samples=5) is None
+
+    flat = MemoryHistory(4)
+    for _ in range(3):
+        flat.push(sample(40.0))
+    assert flat.trend() == ("stable", 0.0)
+
+    falling = MemoryHistory(4)
+    for used_percent in (30.0, 20.0, 10.0):
+        falling.push(sample(used_percent))
+    direction, slope = falling.trend()
+    assert direction == "falling"
+    assert slope < 0.0
+
+
+def test_history_push_rejects_missing_required_fields():
+    history = MemoryHistory(1)
+    try:
+        history.push({"total": 1000})
+    except KeyError as err:
+        assert "free" in str(err)
+    else:
+        raise AssertionError("push accepted a dict without 'free'")
@@ -0,0 +1,85 @@
+//! Generates `self_healing_memory.h` for the C exports when the `cbindgen`
+//! feature is enabled; does nothing otherwise.
+
+fn main() {
+    #[cfg(feature = "cbindgen")]
+    header::generate();
+}
+
+#[cfg(feature = "cbindgen")]
+mod header {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use cbindgen::{Builder, Config, DocumentationStyle, Language};
+
+    const HEADER_NAME: &str = "self_healing_memory.h";
+
+    // Features that gate C exports, with the macro the header guards them by
+    const FEATURE_DEFINES: &[(&str, &str)] = &[
+        ("serde", "SHM_FEATURE_SERDE"),
+        ("prometheus", "SHM_FEATURE_PROMETHEUS"),
+        ("influx", "SHM_FEATURE_INFLUX"),
+        ("msgpack", "SHM_FEATURE_MSGPACK"),
+        ("bincode", "SHM_FEATURE_BINCODE"),
+        ("csv", "SHM_FEATURE_CSV"),
+        ("yaml", "SHM_FEATURE_YAML"),
+    ];
+
+    const TARGET_DEFINES: &[(&str, &str)] = &[
+        ("linux", "SHM_TARGET_LINUX"),
+        ("macos", "SHM_TARGET_MACOS"),
+        ("windows", "SHM_TARGET_WINDOWS"),
+    ];
+
+    pub fn generate() {
+        let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set"));
+        let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR not set"));
+        let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+
+        let mut config = Config {
+            language: Language::C,
+            cpp_compat: true,
+            include_guard: Some(String::from("SELF_HEALING_MEMORY_H")),
+            sys_includes: vec![String::from("stdint.h")],
+            documentation: true,
+            documentation_style: DocumentationStyle::Doxy,
+            ..Config::default()
+        };
+
+        // Define the macros for this build up front, so the header declares
+        // exactly the functions the library it ships with exports
+        let mut enabled = String::new();
+        for (feature, define) in FEATURE_DEFINES {
+            config.defines.insert(format!("feature = {}", feature), define.to_string());
+            let variable = format!("CARGO_FEATURE_{}", feature.to_uppercase());
+            if env::var_os(variable).is_some() {
+                enabled.push_str(&format!("#define {}\n", define));
+            }
+        }
+        for (os, define) in TARGET_DEFINES {
+            config.defines.insert(format!("target_os = {}", os), define.to_string());
+            if target_os == *os {
+                enabled.push_str(&format!("#define {}\n", define));
+            }
+        }
+        config.after_includes = Some(enabled);
+
+        let header = out_dir.join(HEADER_NAME);
+        Builder::new()
+            .with_crate(&crate_dir)
+            .with_config(config)
+            .generate()
+            .expect("cbindgen failed to generate the C header")
+            .write_to_file(&header);
+
+        // OUT_DIR is target/<profile>/build/<package>-<hash>/out; put a copy
+        // in target/<profile> beside the cdylib so the two ship together
+        if let Some(profile_dir) = out_dir.ancestors().nth(3) {
+            let _ = fs::copy(&header, profile_dir.join(HEADER_NAME));
+        }
+
+        println!("cargo:rerun-if-changed=src");
+    }
+}
@@ -0,0 +1,33 @@
+//! Print how much each worker thread allocated, using `AccountingAllocator`.
+
+use std::alloc::System;
+use std::thread;
+
+use memory_core::memory::accounting::{self, AccountingAllocator};
+
+#[global_allocator]
+static ALLOC: AccountingAllocator = AccountingAllocator::new(System);
+
+fn main() {
+    let workers: Vec<_> = (1..=4)
+        .map(|index| {
+            thread::spawn(move || {
+                // Each worker keeps a buffer proportional to its index
+                let buffer = vec![0u8; index * 1024 * 1024];
+                let usage = accounting::thread_usage();
+                drop(buffer);
+                (index, usage, accounting::thread_usage())
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let (index, holding, after_drop) = worker.join().expect("worker panicked");
+        println!(
+            "worker {}: {} bytes while holding its buffer, {} bytes after dropping it",
+            index, holding, after_drop
+        );
+    }
+    println!("main thread: {} bytes", accounting::thread_usage());
+    println!("process: {:?}", ALLOC.stats());
+}
@@ -0,0 +1,13 @@
+//! Arbitrary bytes as /proc/buddyinfo: `cargo +nightly fuzz run buddyinfo`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use memory_core::memory::parse_buddyinfo;
+
+fuzz_target!(|data: &[u8]| {
+    for zone in parse_buddyinfo(data) {
+        let score = zone.fragmentation_score();
+        assert!((0.0..=1.0).contains(&score), "score {} out of range", score);
+    }
+});
@@ -0,0 +1,10 @@
+//! Arbitrary bytes as /proc/meminfo: `cargo +nightly fuzz run meminfo`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use memory_core::memory::parse_meminfo;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_meminfo(data);
+});
@@ -0,0 +1,10 @@
+//! Arbitrary bytes as /proc/vmstat: `cargo +nightly fuzz run vmstat`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use memory_core::memory::parse_vmstat;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_vmstat(data);
+});
@@ -0,0 +1,180 @@
+//! `self-healing-memory-cli`: ad-hoc memory diagnostics from the shell.
+
+#[macro_use]
+extern crate serde_derive;
+
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use memory_core::memory::{self, history::MemoryHistory, MemoryStats};
+
+#[derive(Parser)]
+#[command(name = "self-healing-memory-cli", version, about = "Inspect and heal system memory")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print current memory statistics
+    Stats {
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+    /// Release the OS memory cache
+    Release,
+    /// Return unused allocator pages to the OS
+    Defrag,
+    /// Print statistics periodically until interrupted
+    Monitor {
+        #[arg(long, value_parser = parse_duration, default_value = "5s")]
+        interval: Duration,
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+    /// Collect snapshots over a period and print the usage trend
+    History {
+        #[arg(long, value_parser = parse_duration, default_value = "60s")]
+        duration: Duration,
+        #[arg(long, default_value_t = 12)]
+        samples: usize,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Csv,
+    Prometheus,
+}
+
+/// Summary printed by the `history` subcommand.
+#[derive(Serialize)]
+struct HistoryReport {
+    samples: usize,
+    average_used_percent: f64,
+    used_percent_std_dev: f64,
+    peak_used: Option<u64>,
+    trend: Option<memory::history::MemoryTrend>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Stats { format } => render(&memory::get_memory_stats(), format, true).map(|output| print!("{}", output)),
+        Command::Release => {
+            if memory::release_memory_cache() {
+                println!("memory cache released");
+                Ok(())
+            } else {
+                Err(String::from("failed to release the memory cache"))
+            }
+        }
+        Command::Defrag => to_json(&memory::defragment_memory(None)).map(|json| println!("{}", json)),
+        Command::Monitor { interval, format } => monitor(interval, format),
+        Command::History { duration, samples } => history(duration, samples),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Poll until the process is interrupted; Ctrl-C ends it through the default handler.
+fn monitor(interval: Duration, format: Format) -> Result<(), String> {
+    let mut first = true;
+    loop {
+        print!("{}", render(&memory::get_memory_stats(), format, first)?);
+        first = false;
+        thread::sleep(interval);
+    }
+}
+
+fn history(duration: Duration, samples: usize) -> Result<(), String> {
+    if samples < 2 {
+        return Err(String::from("--samples must be at least 2"));
+    }
+
+    // Samples at both ends of the period, so there are samples - 1 gaps
+    let interval = duration / (samples as u32 - 1);
+    let mut history = MemoryHistory::new(samples);
+    for index in 0..samples {
+        if index > 0 {
+            thread::sleep(interval);
+        }
+        history.push(memory::get_memory_stats());
+    }
+
+    let report = HistoryReport {
+        samples: history.len(),
+        average_used_percent: history.average_used_percent(),
+        used_percent_std_dev: history.used_percent_std_dev(),
+        peak_used: history.peak_used(),
+        trend: history.trend(2),
+    };
+    println!("{}", to_json(&report)?);
+    Ok(())
+}
+
+/// Render `stats` in `format`; `header` adds the CSV header line.
+fn render(stats: &MemoryStats, format: Format, header: bool) -> Result<String, String> {
+    match format {
+        Format::Json => to_json(stats).map(|json| json + "\n"),
+        Format::Csv => render_csv(stats, header),
+        Format::Prometheus => render_prometheus(stats),
+    }
+}
+
+#[cfg(feature = "csv")]
+fn render_csv(stats: &MemoryStats, header: bool) -> Result<String, String> {
+    let row = memory::to_csv_row(stats);
+    if header {
+        Ok(format!("{}\n{}\n", memory::csv_header(), row))
+    } else {
+        Ok(format!("{}\n", row))
+    }
+}
+
+#[cfg(not(feature = "csv"))]
+fn render_csv(_stats: &MemoryStats, _header: bool) -> Result<String, String> {
+    Err(String::from("CSV output needs the csv feature"))
+}
+
+#[cfg(feature = "prometheus")]
+fn render_prometheus(stats: &MemoryStats) -> Result<String, String> {
+    Ok(memory::format_prometheus(stats))
+}
+
+#[cfg(not(feature = "prometheus"))]
+fn render_prometheus(_stats: &MemoryStats) -> Result<String, String> {
+    Err(String::from("Prometheus output needs the prometheus feature"))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|err| err.to_string())
+}
+
+/// Parse durations like `500ms`, `5s`, `2m` or `1h`; a bare number is seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: u64 = number.parse().map_err(|_| format!("invalid duration '{}'", value))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        "h" => Ok(Duration::from_secs(number * 3600)),
+        _ => Err(format!("unknown duration unit '{}', expected ms, s, m or h", unit)),
+    }
+}
@@ -0,0 +1,983 @@
+//! C API used by the Python bindings; every export is `#[no_mangle] extern "C"`.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::memory;
+
+/// Serialize a value to a C string; shared by the JSON exports below.
+#[cfg(feature = "serde")]
+fn to_json_c_string<T: serde::Serialize>(value: &T, what: &str) -> *const c_char {
+    let json = match serde_json::to_string(value) {
+        Ok(json_str) => json_str,
+        Err(_) => format!("{{\"error\": \"Failed to serialize {}\"}}", what),
+    };
+
+    let c_str = match CString::new(json) {
+        Ok(s) => s,
+        Err(_) => CString::new("{\"error\": \"Failed to create C string\"}").unwrap(),
+    };
+
+    // The caller is responsible for freeing this memory with free_string
+    c_str.into_raw()
+}
+
+/// Get memory statistics as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing memory statistics in JSON format.
+/// The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_memory_stats_json() -> *const c_char {
+    let stats = memory::get_memory_stats();
+    
+    // Convert to JSON
+    let json = match serde_json::to_string(&stats) {
+        Ok(json_str) => json_str,
+        Err(_) => String::from("{\"error\": \"Failed to serialize memory statistics\"}"),
+    };
+    
+    // Convert to C string
+    let c_str = match CString::new(json) {
+        Ok(s) => s,
+        Err(_) => CString::new("{\"error\": \"Failed to create C string\"}").unwrap(),
+    };
+    
+    // Return the pointer - the caller is responsible for freeing this memory
+    c_str.into_raw()
+}
+
+/// Get memory statistics in the Prometheus text exposition format.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string suitable for serving from a `/metrics` endpoint.
+/// The caller is responsible for freeing this memory.
+#[cfg(feature = "prometheus")]
+#[no_mangle]
+pub extern "C" fn get_memory_stats_prometheus() -> *const c_char {
+    let text = memory::format_prometheus(&memory::get_memory_stats());
+    
+    match CString::new(text) {
+        Ok(s) => s.into_raw(),
+        Err(_) => CString::new("").unwrap().into_raw(),
+    }
+}
+
+/// Get memory statistics as an InfluxDB line protocol row.
+/// 
+/// # Arguments
+/// 
+/// * `measurement` - Measurement name, or null for "memory".
+/// 
+/// # Returns
+/// 
+/// A C-compatible string holding a single line protocol row.
+/// The caller is responsible for freeing this memory.
+/// 
+/// # Safety
+/// 
+/// `measurement` must be null or point to a NUL-terminated string.
+#[cfg(feature = "influx")]
+#[no_mangle]
+pub unsafe extern "C" fn get_memory_stats_influx(measurement: *const c_char) -> *const c_char {
+    let measurement = if measurement.is_null() {
+        "memory"
+    } else {
+        CStr::from_ptr(measurement).to_str().unwrap_or("memory")
+    };
+    let line = memory::format_influx(&memory::get_memory_stats(), measurement, &[]);
+    
+    match CString::new(line) {
+        Ok(s) => s.into_raw(),
+        Err(_) => CString::new("").unwrap().into_raw(),
+    }
+}
+
+/// Get memory statistics as a MessagePack blob.
+/// 
+/// # Arguments
+/// 
+/// * `out_len` - Receives the length of the returned buffer.
+/// 
+/// # Returns
+/// 
+/// A pointer to the encoded bytes, or null on failure. The caller is
+/// responsible for freeing this memory with `free_bytes`.
+/// 
+/// # Safety
+/// 
+/// `out_len` must be null or valid for writes.
+#[cfg(feature = "msgpack")]
+#[no_mangle]
+pub unsafe extern "C" fn get_memory_stats_msgpack(out_len: *mut usize) -> *mut u8 {
+    match memory::to_msgpack(&memory::get_memory_stats()) {
+        Ok(bytes) => into_raw_bytes(bytes, out_len),
+        Err(_) => into_raw_bytes(Vec::new(), out_len),
+    }
+}
+
+/// Get memory statistics as a bincode blob.
+/// 
+/// # Arguments
+/// 
+/// * `out_len` - Receives the length of the returned buffer.
+/// 
+/// # Returns
+/// 
+/// A pointer to the encoded bytes, or null on failure. The caller is
+/// responsible for freeing this memory with `free_bytes`.
+/// 
+/// # Safety
+/// 
+/// `out_len` must be null or valid for writes.
+#[cfg(feature = "bincode")]
+#[no_mangle]
+pub unsafe extern "C" fn get_memory_stats_bincode(out_len: *mut usize) -> *mut u8 {
+    match memory::to_bincode(&memory::get_memory_stats()) {
+        Ok(bytes) => into_raw_bytes(bytes, out_len),
+        Err(_) => into_raw_bytes(Vec::new(), out_len),
+    }
+}
+
+/// Hand `bytes` to C, storing its length in `out_len`.
+/// 
+/// Returns null, with a length of 0, if `out_len` is null or `bytes` is empty.
+#[cfg(any(feature = "msgpack", feature = "bincode"))]
+unsafe fn into_raw_bytes(bytes: Vec<u8>, out_len: *mut usize) -> *mut u8 {
+    if out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    *out_len = 0;
+    if bytes.is_empty() {
+        return std::ptr::null_mut();
+    }
+    
+    // A boxed slice has no spare capacity, so `len` alone is enough to free it
+    let bytes = bytes.into_boxed_slice();
+    *out_len = bytes.len();
+    Box::into_raw(bytes) as *mut u8
+}
+
+/// Free a buffer previously returned by this library.
+/// 
+/// # Arguments
+/// 
+/// * `ptr` - Pointer to the buffer to free.
+/// * `len` - Length reported when the buffer was returned.
+/// 
+/// # Safety
+/// 
+/// `ptr` must be null or come from this library with the matching `len`,
+/// and must not be freed twice.
+#[cfg(any(feature = "msgpack", feature = "bincode"))]
+#[no_mangle]
+pub unsafe extern "C" fn free_bytes(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    let _ = Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len));
+}
+
+/// Get memory statistics for the calling process as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing process statistics in JSON format.
+/// The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_current_process_stats_json() -> *const c_char {
+    match memory::process::get_current_process_stats() {
+        Ok(stats) => to_json_c_string(&stats, "process statistics"),
+        Err(_) => CString::new("{\"error\": \"Failed to read process statistics\"}").unwrap().into_raw(),
+    }
+}
+
+/// Get the smaps rollup of the calling process as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing RSS, PSS, clean/dirty and swap totals in
+/// JSON format. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_current_process_smaps_json() -> *const c_char {
+    match memory::get_process_smaps(std::process::id()) {
+        Ok(smaps) => to_json_c_string(&smaps, "smaps rollup"),
+        Err(_) => CString::new("{\"error\": \"Failed to read smaps_rollup\"}").unwrap().into_raw(),
+    }
+}
+
+/// Get the page fault counters of the calling process as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing minor and major fault counts, for the
+/// process and its children, in JSON format. The caller is responsible for
+/// freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_current_process_page_fault_stats_json() -> *const c_char {
+    match memory::get_page_fault_stats(std::process::id()) {
+        Ok(faults) => to_json_c_string(&faults, "page fault statistics"),
+        Err(_) => CString::new("{\"error\": \"Failed to read page fault statistics\"}").unwrap().into_raw(),
+    }
+}
+
+/// Lock all current and future pages of the calling process into RAM.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise, e.g. when `RLIMIT_MEMLOCK` is too low.
+#[no_mangle]
+pub extern "C" fn lock_current_process_memory() -> i32 {
+    match memory::mlock::lock_all_memory() {
+        Ok(()) => 1,
+        Err(_) => 0,
+    }
+}
+
+/// Unlock every page locked by the calling process.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise.
+#[no_mangle]
+pub extern "C" fn unlock_current_process_memory() -> i32 {
+    match memory::mlock::unlock_all_memory() {
+        Ok(()) => 1,
+        Err(_) => 0,
+    }
+}
+
+/// Get the OOM killer's badness score for the calling process.
+/// 
+/// # Returns
+/// 
+/// The score, or -1 if it could not be read.
+#[no_mangle]
+pub extern "C" fn get_current_oom_score() -> i32 {
+    memory::get_oom_score(std::process::id()).unwrap_or(-1)
+}
+
+/// Set the OOM score adjustment of the calling process.
+/// 
+/// # Arguments
+/// 
+/// * `adj` - Adjustment, clamped to [-1000, 1000].
+/// 
+/// # Returns
+/// 
+/// 0 if successful, the errno value otherwise, or -1 on platforms without
+/// OOM scores.
+#[no_mangle]
+pub extern "C" fn set_current_oom_score_adj(adj: i32) -> i32 {
+    let adj = adj.clamp(memory::process::OOM_SCORE_ADJ_MIN as i32, memory::process::OOM_SCORE_ADJ_MAX as i32) as i16;
+    
+    match memory::set_oom_score_adj(std::process::id(), adj) {
+        Ok(()) => 0,
+        Err(err) => err.raw_os_error().unwrap_or(-1),
+    }
+}
+
+/// Get memory statistics as CSV.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string holding the header row and one data row, each
+/// newline-terminated. The caller is responsible for freeing this memory.
+#[cfg(feature = "csv")]
+#[no_mangle]
+pub extern "C" fn get_memory_stats_csv() -> *const c_char {
+    let csv = format!("{}\n{}\n", memory::csv_header(), memory::to_csv_row(&memory::get_memory_stats()));
+    
+    match CString::new(csv) {
+        Ok(s) => s.into_raw(),
+        Err(_) => CString::new("").unwrap().into_raw(),
+    }
+}
+
+/// Get memory statistics as a YAML document.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing memory statistics in YAML format.
+/// The caller is responsible for freeing this memory.
+#[cfg(feature = "yaml")]
+#[no_mangle]
+pub extern "C" fn get_memory_stats_yaml() -> *const c_char {
+    let yaml = match memory::to_yaml(&memory::get_memory_stats()) {
+        Ok(yaml) => yaml,
+        Err(_) => String::from("error: Failed to serialize memory statistics\n"),
+    };
+    
+    match CString::new(yaml) {
+        Ok(s) => s.into_raw(),
+        Err(_) => CString::new("").unwrap().into_raw(),
+    }
+}
+
+/// Get swap statistics as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing swap statistics in JSON format.
+/// The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_swap_stats_json() -> *const c_char {
+    to_json_c_string(&memory::get_swap_stats(), "swap statistics")
+}
+
+/// Get page and swap activity counters from /proc/vmstat as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing VM statistics in JSON format.
+/// The caller is responsible for freeing this memory.
+#[cfg(all(target_os = "linux", feature = "serde"))]
+#[no_mangle]
+pub extern "C" fn get_vm_stats_json() -> *const c_char {
+    to_json_c_string(&memory::get_vm_stats(), "VM statistics")
+}
+
+/// Get commit, kernel pool and object counts from `GetPerformanceInfo` as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing the `WindowsSystemInfo` in JSON format.
+/// The caller is responsible for freeing this memory.
+#[cfg(all(target_os = "windows", feature = "serde"))]
+#[no_mangle]
+pub extern "C" fn get_windows_system_info_json() -> *const c_char {
+    match memory::get_windows_system_info() {
+        Ok(info) => to_json_c_string(&info, "Windows system information"),
+        Err(_) => CString::new("{\"error\": \"Failed to read performance information\"}").unwrap().into_raw(),
+    }
+}
+
+/// Get transparent huge page statistics as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing the THP mode and huge page counters in
+/// JSON format. The caller is responsible for freeing this memory.
+#[cfg(all(target_os = "linux", feature = "serde"))]
+#[no_mangle]
+pub extern "C" fn get_thp_stats_json() -> *const c_char {
+    to_json_c_string(&memory::get_thp_stats(), "THP statistics")
+}
+
+/// Get kernel same-page merging counters as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing KSM counters plus `savings_bytes` in JSON
+/// format, or `null` where KSM is unavailable. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_ksm_stats_json() -> *const c_char {
+    #[derive(Serialize)]
+    struct KsmReport {
+        #[serde(flatten)]
+        stats: memory::KsmStats,
+        savings_bytes: u64,
+    }
+    
+    let page_size = memory::util::page_size() as u64;
+    let report = memory::get_ksm_stats().map(|stats| KsmReport {
+        stats,
+        savings_bytes: stats.savings_bytes(page_size),
+    });
+    to_json_c_string(&report, "KSM statistics")
+}
+
+/// Get per-node NUMA memory statistics as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing a JSON array of nodes, empty on systems
+/// without NUMA support. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_numa_stats_json() -> *const c_char {
+    to_json_c_string(&memory::get_numa_stats(), "NUMA statistics")
+}
+
+/// Get per-zone free block counts from /proc/buddyinfo as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing a JSON array of zones, empty on
+/// platforms without buddyinfo. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_buddyinfo_json() -> *const c_char {
+    to_json_c_string(&memory::get_buddyinfo(), "buddyinfo")
+}
+
+/// Get memory pressure stall information as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing the JSON representation of memory pressure,
+/// all zeros where PSI is unavailable. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_memory_pressure_json() -> *const c_char {
+    to_json_c_string(&memory::get_memory_pressure(), "memory pressure")
+}
+
+/// Get a combined memory health score with the default weights.
+/// 
+/// # Returns
+/// 
+/// A value from 0.0 (healthy) to 100.0 (critical).
+#[no_mangle]
+pub extern "C" fn get_memory_health_score() -> f64 {
+    memory::MemoryStatsConfig::new()
+        .include_timestamp(false)
+        .include_fragmentation(true)
+        .fetch()
+        .health_score()
+}
+
+/// Get the change in memory since an earlier snapshot as a JSON string.
+/// 
+/// # Arguments
+/// 
+/// * `before_json` - A snapshot previously returned by `get_memory_stats_json`.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing the `MemoryDelta` in JSON format.
+/// The caller is responsible for freeing this memory.
+/// 
+/// # Safety
+/// 
+/// `before_json` must be null or point to a NUL-terminated string.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub unsafe extern "C" fn get_memory_delta_json(before_json: *const c_char) -> *const c_char {
+    let before = if before_json.is_null() {
+        None
+    } else {
+        CStr::from_ptr(before_json)
+            .to_str()
+            .ok()
+            .and_then(|json| serde_json::from_str::<memory::MemoryStats>(json).ok())
+    };
+    
+    match before {
+        Some(before) => to_json_c_string(&memory::compute_delta(&before, &memory::get_memory_stats()), "memory delta"),
+        None => CString::new("{\"error\": \"Failed to parse memory snapshot\"}").unwrap().into_raw(),
+    }
+}
+
+/// Get the fields that changed since an earlier snapshot as a JSON Patch.
+/// 
+/// # Arguments
+/// 
+/// * `before_json` - A snapshot previously returned by `get_memory_stats_json`.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing an RFC 6902 JSON Patch array that turns
+/// `before_json` into the current statistics. The caller is responsible for
+/// freeing this memory.
+/// 
+/// # Safety
+/// 
+/// `before_json` must be null or point to a NUL-terminated string.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub unsafe extern "C" fn get_memory_stats_diff_json(before_json: *const c_char) -> *const c_char {
+    let before = if before_json.is_null() {
+        None
+    } else {
+        CStr::from_ptr(before_json)
+            .to_str()
+            .ok()
+            .and_then(|json| serde_json::from_str::<memory::MemoryStats>(json).ok())
+    };
+    
+    let patch = match before {
+        Some(before) => memory::json_patch(&before, &memory::get_memory_stats()),
+        None => String::from("{\"error\": \"Failed to parse memory snapshot\"}"),
+    };
+    
+    match CString::new(patch) {
+        Ok(s) => s.into_raw(),
+        Err(_) => CString::new("{\"error\": \"Failed to create C string\"}").unwrap().into_raw(),
+    }
+}
+
+/// Send current memory statistics to a StatsD server over UDP.
+/// 
+/// # Arguments
+/// 
+/// * `host` - StatsD host name or address.
+/// * `port` - StatsD UDP port.
+/// * `prefix` - Metric name prefix, or null for none.
+/// 
+/// # Returns
+/// 
+/// 0 if the datagram was sent, -1 otherwise.
+/// 
+/// # Safety
+/// 
+/// `host` and `prefix` must be null or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn emit_memory_stats_statsd(host: *const c_char, port: u16, prefix: *const c_char) -> i32 {
+    if host.is_null() {
+        return -1;
+    }
+    let host = match CStr::from_ptr(host).to_str() {
+        Ok(host) => host,
+        Err(_) => return -1,
+    };
+    let prefix = if prefix.is_null() {
+        ""
+    } else {
+        CStr::from_ptr(prefix).to_str().unwrap_or("")
+    };
+    
+    match memory::emit_statsd(&memory::get_memory_stats(), host, port, prefix) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Release memory cache.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise.
+#[no_mangle]
+pub extern "C" fn release_memory_cache() -> i32 {
+    match memory::release_memory_cache() {
+        true => 1,
+        false => 0,
+    }
+}
+
+/// Free a C string previously returned by this library.
+/// 
+/// # Arguments
+/// 
+/// * `s` - Pointer to the C string to free.
+#[no_mangle]
+pub extern "C" fn free_string(s: *mut c_char) {
+    unsafe {
+        if s.is_null() {
+            return;
+        }
+        let _ = CString::from_raw(s);
+    }
+}
+
+/// Simulate memory fragmentation for testing purposes.
+/// 
+/// # Arguments
+/// 
+/// * `count` - Number of memory blocks to allocate and free.
+/// * `size_kb` - Size of each memory block in kilobytes.
+/// * `pattern` - Which blocks to free first: 0 every third, 1 random with
+///   probability 0.5, 2 checkerboard, 3 alternating large and small blocks.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise or if `pattern` is unknown.
+#[no_mangle]
+pub extern "C" fn simulate_memory_fragmentation_v2(count: i32, size_kb: i32, pattern: i32) -> i32 {
+    let pattern = match pattern {
+        0 => memory::FragmentationPattern::EveryThird,
+        1 => memory::FragmentationPattern::Random(0.5),
+        2 => memory::FragmentationPattern::Checkerboard,
+        3 => memory::FragmentationPattern::LargeSmallAlternating,
+        _ => return 0,
+    };
+    
+    match memory::simulate_memory_fragmentation(count, size_kb, pattern) {
+        true => 1,
+        false => 0,
+    }
+}
+
+/// Hold memory at a target usage for testing purposes.
+/// 
+/// # Arguments
+/// 
+/// * `target_percent` - System memory usage to reach, from 0 to 100.
+/// * `duration_ms` - How long to hold the memory in milliseconds.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise.
+#[no_mangle]
+pub extern "C" fn simulate_high_usage(target_percent: f64, duration_ms: u64) -> i32 {
+    match memory::simulate_high_usage(target_percent, duration_ms) {
+        true => 1,
+        false => 0,
+    }
+}
+
+/// Perform memory defragmentation.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise.
+#[no_mangle]
+pub extern "C" fn defragment_memory() -> i32 {
+    match memory::defragment_memory(None).success {
+        true => 1,
+        false => 0,
+    }
+}
+
+/// Perform memory defragmentation and report the result as a JSON string.
+/// 
+/// # Arguments
+/// 
+/// * `arena` - jemalloc arena to purge, or a negative value for all arenas.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing the `DefragResult` in JSON format.
+/// The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn defragment_memory_json(arena: i32) -> *const c_char {
+    let arena = if arena < 0 { None } else { Some(arena as u32) };
+    to_json_c_string(&memory::defragment_memory(arena), "defragmentation result")
+}
+
+/// Run one defragmentation strategy and report the result as a JSON string.
+/// 
+/// # Arguments
+/// 
+/// * `strategy` - 0 purges jemalloc arenas, 1 runs `malloc_trim`, 2 drops the
+///   page cache and 3 asks the kernel to compact memory.
+/// * `pad` - Bytes of heap top padding kept by `malloc_trim`; ignored otherwise.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing the `DefragResult` in JSON format, or an
+/// error object for an unknown strategy. The caller is responsible for
+/// freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn defragment_with_strategy_json(strategy: i32, pad: i32) -> *const c_char {
+    let strategy = match strategy {
+        0 => memory::DefragStrategy::JemallocPurge,
+        1 => memory::DefragStrategy::MallocTrim(pad),
+        2 => memory::DefragStrategy::DropPageCache,
+        3 => memory::DefragStrategy::Compact,
+        _ => return CString::new("{\"error\": \"Unknown defragmentation strategy\"}").unwrap().into_raw(),
+    };
+    to_json_c_string(&memory::defragment_with_strategy(strategy), "defragmentation result")
+}
+
+/// Defragment within a time budget and report progress as a JSON string.
+/// 
+/// # Arguments
+/// 
+/// * `budget_ms` - Time budget for this call in milliseconds.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing the `DefragProgress` in JSON format.
+/// Call again while `work_remaining` is true. The caller is responsible for
+/// freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn incremental_defragment_json(budget_ms: u64) -> *const c_char {
+    to_json_c_string(&memory::incremental_defragment(budget_ms), "defragmentation progress")
+}
+
+/// Enable or disable dry-run mode for healing.
+/// 
+/// While enabled, healing engines only simulate their actions.
+/// 
+/// # Arguments
+/// 
+/// * `enabled` - Non-zero to enable dry-run mode, 0 to disable it.
+#[no_mangle]
+pub extern "C" fn set_healing_dry_run(enabled: i32) {
+    memory::healing::set_dry_run(enabled != 0);
+}
+
+/// Get platform details as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing OS, kernel, CPU and memory details in
+/// JSON format. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_platform_info_json() -> *const c_char {
+    to_json_c_string(&memory::platform::get_platform_info(), "platform information")
+}
+
+/// Get the operations and data sources available on this system as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing one boolean per capability in JSON
+/// format. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_memory_capabilities_json() -> *const c_char {
+    to_json_c_string(&memory::capabilities(), "memory capabilities")
+}
+
+// Monitor driven through the C API; Rust callers own their own MemoryMonitor
+const MONITOR_HISTORY_CAPACITY: usize = 360;
+static MONITOR: Mutex<Option<memory::monitor::MemoryMonitor>> = Mutex::new(None);
+
+/// Start the background memory monitor, replacing one already running.
+/// 
+/// # Arguments
+/// 
+/// * `interval_ms` - Polling interval in milliseconds.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise.
+#[no_mangle]
+pub extern "C" fn memory_monitor_start(interval_ms: u64) -> i32 {
+    if interval_ms == 0 {
+        return 0;
+    }
+    
+    let mut monitor = MONITOR.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // Dropping the previous monitor stops its thread
+    *monitor = Some(
+        memory::monitor::MemoryMonitor::start(Duration::from_millis(interval_ms))
+            .with_history(MONITOR_HISTORY_CAPACITY),
+    );
+    1
+}
+
+/// Stop the background memory monitor.
+/// 
+/// # Returns
+/// 
+/// 1 if a monitor was running, 0 otherwise.
+#[no_mangle]
+pub extern "C" fn memory_monitor_stop() -> i32 {
+    let monitor = MONITOR.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+    match monitor {
+        Some(mut monitor) => {
+            monitor.stop();
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Get the most recent sample of the background memory monitor as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing memory statistics in JSON format, or
+/// `null` if the monitor is not running. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn memory_monitor_latest_json() -> *const c_char {
+    let latest = MONITOR
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .as_ref()
+        .map(|monitor| monitor.latest());
+    to_json_c_string(&latest, "monitor statistics")
+}
+
+/// Get the samples recorded by the background memory monitor as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing a JSON array of memory statistics, oldest
+/// first, empty if the monitor is not running. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_memory_history_json() -> *const c_char {
+    let history = MONITOR
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .as_ref()
+        .and_then(|monitor| monitor.history());
+    let samples = history.as_ref().map(|history| history.samples()).unwrap_or(&[]);
+    to_json_c_string(&samples, "memory history")
+}
+
+/// Get memory compressor statistics as a JSON string (macOS only).
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing the `MacosCompressionStats` in JSON format.
+/// The caller is responsible for freeing this memory.
+#[cfg(all(target_os = "macos", feature = "serde"))]
+#[no_mangle]
+pub extern "C" fn get_macos_compression_stats_json() -> *const c_char {
+    to_json_c_string(&memory::get_macos_compression_stats(), "compression statistics")
+}
+
+/// Get the installed memory modules as a JSON string (macOS only).
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing a JSON array of modules with slot, size,
+/// speed and type, empty on Apple silicon. The caller is responsible for
+/// freeing this memory.
+#[cfg(all(target_os = "macos", feature = "serde"))]
+#[no_mangle]
+pub extern "C" fn get_physical_memory_info_json() -> *const c_char {
+    match memory::get_physical_memory_info() {
+        Ok(dimms) => to_json_c_string(&dimms, "physical memory information"),
+        Err(_) => CString::new("{\"error\": \"Failed to read memory modules from IOKit\"}").unwrap().into_raw(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+static MACOS_PRESSURE_SOURCE: Mutex<Option<memory::macos_pressure::MacosPressureSource>> = Mutex::new(None);
+
+/// Register a callback for macOS memory pressure transitions.
+/// 
+/// # Arguments
+/// 
+/// * `callback` - Called with 1 (normal), 2 (warning) or 4 (critical) from a
+///   dispatch queue thread. Replaces any callback registered earlier.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise.
+#[cfg(target_os = "macos")]
+#[no_mangle]
+pub extern "C" fn register_macos_pressure_callback(callback: extern "C" fn(i32)) -> i32 {
+    let source = memory::macos_pressure::MacosPressureSource::register(move |level| callback(level as i32));
+    let registered = source.is_some();
+    
+    // Dropping a previous source cancels it
+    *MACOS_PRESSURE_SOURCE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = source;
+    registered as i32
+}
+
+/// Unregister the macOS memory pressure callback.
+/// 
+/// # Returns
+/// 
+/// 1 if a callback was registered, 0 otherwise.
+#[cfg(target_os = "macos")]
+#[no_mangle]
+pub extern "C" fn unregister_macos_pressure_callback() -> i32 {
+    let source = MACOS_PRESSURE_SOURCE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+    match source {
+        Some(source) => {
+            source.unregister();
+            1
+        }
+        None => 0,
+    }
+}
+
+static STATS_CACHE: Mutex<Option<memory::cache::CachedMemoryStats>> = Mutex::new(None);
+
+/// Start caching memory statistics, replacing a cache already running.
+/// 
+/// # Arguments
+/// 
+/// * `ttl_ms` - Refresh interval in milliseconds.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise.
+#[no_mangle]
+pub extern "C" fn init_memory_stats_cache(ttl_ms: u64) -> i32 {
+    if ttl_ms == 0 {
+        return 0;
+    }
+    
+    let cache = memory::cache::CachedMemoryStats::new(Duration::from_millis(ttl_ms));
+    *STATS_CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(cache);
+    1
+}
+
+/// Get cached memory statistics as a JSON string.
+/// 
+/// # Returns
+/// 
+/// A C-compatible string containing memory statistics in JSON format, read
+/// fresh if `init_memory_stats_cache` was not called. The caller is responsible for freeing this memory.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn get_cached_memory_stats_json() -> *const c_char {
+    let cached = STATS_CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .as_ref()
+        .map(|cache| cache.get());
+    
+    match cached {
+        Some(stats) => to_json_c_string(&*stats, "memory statistics"),
+        None => to_json_c_string(&memory::get_memory_stats(), "memory statistics"),
+    }
+}
+
+// Log driven through the C API; Rust callers own their own TimeSeriesLog
+#[cfg(feature = "serde")]
+static TIMESERIES_LOG: Mutex<Option<memory::TimeSeriesLog>> = Mutex::new(None);
+
+/// Open a newline-delimited JSON log of memory statistics, replacing a log
+/// opened earlier.
+/// 
+/// # Arguments
+/// 
+/// * `path` - File to append to; created if it does not exist.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 otherwise.
+/// 
+/// # Safety
+/// 
+/// `path` must be null or point to a NUL-terminated string.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub unsafe extern "C" fn open_timeseries_log(path: *const c_char) -> i32 {
+    if path.is_null() {
+        return 0;
+    }
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(_) => return 0,
+    };
+    
+    match memory::TimeSeriesLog::open(std::path::Path::new(path)) {
+        Ok(log) => {
+            *TIMESERIES_LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(log);
+            1
+        }
+        Err(_) => 0,
+    }
+}
+
+/// Append current memory statistics to the log opened with `open_timeseries_log`.
+/// 
+/// # Returns
+/// 
+/// 1 if successful, 0 if no log is open or the write failed.
+#[cfg(feature = "serde")]
+#[no_mangle]
+pub extern "C" fn record_memory_stats_to_log() -> i32 {
+    let mut log = TIMESERIES_LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match log.as_mut().map(|log| log.record(&memory::get_memory_stats())) {
+        Some(Ok(())) => 1,
+        _ => 0,
+    }
+}
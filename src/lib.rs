@@ -1,96 +1,50 @@
+#![cfg_attr(feature = "no_std", no_std)]
+
+#[cfg(feature = "serde")]
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "serde")]
 extern crate serde;
+extern crate alloc;
+#[cfg(all(feature = "serde", not(feature = "no_std")))]
 extern crate serde_json;
-
-use std::slice;
-use std::ffi::CString;
-use std::os::raw::c_char;
+#[cfg(not(feature = "no_std"))]
+extern crate arc_swap;
+// The encoders behind these live in the std-only format module
+#[cfg(all(feature = "msgpack", not(feature = "no_std")))]
+extern crate rmp_serde;
+#[cfg(all(feature = "yaml", not(feature = "no_std")))]
+extern crate serde_yaml;
+#[cfg(all(feature = "bincode", not(feature = "no_std")))]
+extern crate bincode;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+extern crate js_sys;
+#[cfg(feature = "http")]
+extern crate tiny_http;
+#[cfg(feature = "opentelemetry")]
+extern crate opentelemetry;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "logging")]
+extern crate log;
+#[cfg(feature = "python")]
+extern crate pyo3;
 
 // Include the memory module
+#[cfg(not(feature = "no_std"))]
 pub mod memory;
 
-/// Get memory statistics as a JSON string.
-/// 
-/// # Returns
-/// 
-/// A C-compatible string containing memory statistics in JSON format.
-/// The caller is responsible for freeing this memory.
-#[no_mangle]
-pub extern "C" fn get_memory_stats_json() -> *const c_char {
-    let stats = memory::get_memory_stats();
-    
-    // Convert to JSON
-    let json = match serde_json::to_string(&stats) {
-        Ok(json_str) => json_str,
-        Err(_) => String::from("{\"error\": \"Failed to serialize memory statistics\"}"),
-    };
-    
-    // Convert to C string
-    let c_str = match CString::new(json) {
-        Ok(s) => s,
-        Err(_) => CString::new("{\"error\": \"Failed to create C string\"}").unwrap(),
-    };
-    
-    // Return the pointer - the caller is responsible for freeing this memory
-    c_str.into_raw()
-}
-
-/// Release memory cache.
-/// 
-/// # Returns
-/// 
-/// 1 if successful, 0 otherwise.
-#[no_mangle]
-pub extern "C" fn release_memory_cache() -> i32 {
-    match memory::release_memory_cache() {
-        true => 1,
-        false => 0,
-    }
-}
-
-/// Free a C string previously returned by this library.
-/// 
-/// # Arguments
-/// 
-/// * `s` - Pointer to the C string to free.
-#[no_mangle]
-pub extern "C" fn free_string(s: *mut c_char) {
-    unsafe {
-        if s.is_null() {
-            return;
-        }
-        let _ = CString::from_raw(s);
-    }
-}
+// Without std only the allocation helpers are available
+#[cfg(feature = "no_std")]
+#[path = "memory/bare.rs"]
+pub mod memory;
 
-/// Simulate memory fragmentation for testing purposes.
-/// 
-/// # Arguments
-/// 
-/// * `count` - Number of memory blocks to allocate and free.
-/// * `size_kb` - Size of each memory block in kilobytes.
-/// 
-/// # Returns
-/// 
-/// 1 if successful, 0 otherwise.
-#[no_mangle]
-pub extern "C" fn simulate_memory_fragmentation(count: i32, size_kb: i32) -> i32 {
-    match memory::simulate_memory_fragmentation(count, size_kb) {
-        true => 1,
-        false => 0,
-    }
-}
+// C exports, re-exported so they keep their crate-root paths
+#[cfg(not(feature = "no_std"))]
+mod ffi;
+#[cfg(not(feature = "no_std"))]
+pub use ffi::*;
 
-/// Perform memory defragmentation.
-/// 
-/// # Returns
-/// 
-/// 1 if successful, 0 otherwise.
-#[no_mangle]
-pub extern "C" fn defragment_memory() -> i32 {
-    match memory::defragment_memory() {
-        true => 1,
-        false => 0,
-    }
-}
+// Native Python module, built with maturin from bindings/python
+#[cfg(all(feature = "python", not(feature = "no_std")))]
+mod python;
@@ -1,34 +1,272 @@
-use std::time::{SystemTime, UNIX_EPOCH};
 use std::collections::HashMap;
 use std::thread;
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use std::alloc::{alloc, dealloc, Layout};
+use std::fmt;
+use std::ops::Sub;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct MemoryStats {
-    pub total: u64,       // Total physical memory in bytes
-    pub free: u64,        // Free physical memory in bytes
-    pub available: u64,   // Available memory in bytes
-    pub used: u64,        // Used physical memory in bytes
-    pub used_percent: f64, // Used memory as a percentage
-    pub buffers: Option<u64>, // Memory used for buffers (Linux specific)
-    pub cached: Option<u64>,  // Memory used for cache (Linux specific)
-    pub timestamp: String,    // ISO8601 timestamp
+use self::memory_stats::parse_timestamp_ms;
+use self::util::HumanBytes;
+
+#[macro_use]
+mod instrument;
+
+mod memory_stats;
+mod text;
+
+pub mod error;
+pub mod debug;
+pub mod alloc;
+pub mod healing;
+pub mod diagnostics;
+pub mod util;
+pub mod trace;
+pub mod stats;
+pub mod proc;
+pub mod policy;
+pub mod format;
+pub mod rate_limiter;
+pub mod retry;
+pub mod platform;
+pub mod cgroup;
+pub mod monitor;
+pub mod history;
+pub mod process;
+pub mod pool;
+pub mod guard;
+pub mod secure;
+pub mod ksm;
+pub mod cache;
+pub mod gate;
+#[cfg(feature = "serde")]
+pub mod timeseries;
+#[cfg(feature = "bincode")]
+pub mod snapshot;
+pub mod scheduler;
+pub mod accounting;
+pub mod mlock;
+pub mod reserve;
+pub mod budget;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "macos")]
+pub mod macos_pressure;
+#[cfg(target_os = "macos")]
+pub mod macos_dimm;
+
+#[cfg(all(feature = "bincode", any(target_os = "linux", target_os = "macos", target_os = "freebsd", target_os = "openbsd")))]
+pub mod shm;
+
+#[cfg(feature = "tokio")]
+mod asynchronous;
+
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+
+pub use self::memory_stats::MemoryStats;
+pub use self::error::MemoryError;
+pub use self::cgroup::{get_cgroup_memory_stats, CgroupMemoryStats};
+pub use self::format::emit_statsd;
+#[cfg(feature = "serde")]
+pub use self::format::json_patch;
+pub use self::process::{
+    get_oom_score, get_page_fault_stats, get_process_smaps, get_process_stats, get_smaps_detail, set_oom_score_adj,
+    PageFaultStats, ProcessStats, SmapRegion, SmapsRollup, SmapsSummary,
+};
+pub use self::ksm::{get_ksm_stats, KsmStats};
+#[cfg(feature = "serde")]
+pub use self::timeseries::TimeSeriesLog;
+pub use self::scheduler::{HealingScheduler, SchedulerStatus};
+pub use self::platform::{capabilities, MemoryCapabilities};
+pub use self::mlock::{lock_region, locked_memory_bytes, unlock_region, LockedRegion};
+pub use self::gate::MemoryGate;
+pub use self::reserve::{reserve, total_reserved_bytes, EmergencyReserve, MemoryReservation};
+pub use self::budget::{BudgetError, BudgetGuard, BudgetRegistry, MemoryBudget};
+
+#[cfg(target_os = "linux")]
+pub use self::linux::transparent_hugepage::{get_thp_stats, ThpStats};
+
+#[cfg(target_os = "macos")]
+pub use self::macos_dimm::{get_physical_memory_info, DimmInfo};
+
+#[cfg(feature = "tokio")]
+pub use self::asynchronous::{async_get_memory_stats, async_release_memory_cache};
+
+#[cfg(feature = "prometheus")]
+pub use self::format::format_prometheus;
+
+#[cfg(feature = "influx")]
+pub use self::format::format_influx;
+
+#[cfg(feature = "dogstatsd")]
+pub use self::format::emit_dogstatsd;
+
+#[cfg(feature = "msgpack")]
+pub use self::format::{from_msgpack, to_msgpack};
+
+#[cfg(feature = "csv")]
+pub use self::format::{csv_header, to_csv_row, MemoryStatsCsvWriter};
+
+#[cfg(feature = "yaml")]
+pub use self::format::{from_yaml, to_yaml};
+
+#[cfg(feature = "bincode")]
+pub use self::format::{from_bincode, to_bincode};
+#[cfg(feature = "bincode")]
+pub use self::snapshot::Snapshot;
+
+#[cfg(all(feature = "bincode", any(target_os = "linux", target_os = "macos", target_os = "freebsd", target_os = "openbsd")))]
+pub use self::shm::SharedStatsBuffer;
+
+#[cfg(feature = "http")]
+pub use self::http::{serve_metrics, HttpServer};
+#[cfg(feature = "opentelemetry")]
+pub use self::otel::{register_otel_instruments, OtelMemoryInstruments};
+
+impl MemoryStats {
+    /// Combined score from 0.0 (healthy) to 100.0 (critical), with the default
+    /// weights.
+    /// 
+    /// Fills in what the snapshot lacks: PSI is read where
+    /// /proc/pressure/memory exists, and `fragmentation_score()` is sampled
+    /// when `fragmentation_score` is `None`. Use `health_score_with` to score
+    /// the snapshot alone.
+    pub fn health_score(&self) -> f64 {
+        let pressure = if std::path::Path::new("/proc/pressure/memory").exists() {
+            Some(get_memory_pressure())
+        } else {
+            None
+        };
+        let fragmentation = self.fragmentation_score.unwrap_or_else(fragmentation_score);
+        
+        self.weighted_health_score(&HealthScoreWeights::default(), Some(fragmentation), pressure.as_ref())
+    }
+    
+    /// Combined score from 0.0 (healthy) to 100.0 (critical).
+    /// 
+    /// Each input is scaled to 0-100: used_percent as is, the fragmentation
+    /// score times 100, the PSI `some` avg10 stall percentage from `pressure`,
+    /// and dirty pages relative to the kernel's default 20% dirty_ratio. The
+    /// result is the weighted mean of the inputs that are available. Nothing
+    /// is sampled here, so the score only reflects the snapshot: a missing
+    /// `fragmentation_score`, `pressure` or dirty page count is left out.
+    pub fn health_score_with(&self, weights: &HealthScoreWeights, pressure: Option<&MemoryPressure>) -> f64 {
+        self.weighted_health_score(weights, self.fragmentation_score, pressure)
+    }
+    
+    fn weighted_health_score(
+        &self,
+        weights: &HealthScoreWeights,
+        fragmentation: Option<f64>,
+        pressure: Option<&MemoryPressure>,
+    ) -> f64 {
+        let mut components = vec![(self.used_percent, weights.used_percent_weight)];
+        
+        if let Some(fragmentation) = fragmentation {
+            components.push((fragmentation * 100.0, weights.fragmentation_weight));
+        }
+        if let Some(pressure) = pressure {
+            components.push((pressure.some.avg10, weights.psi_weight));
+        }
+        if let Some(dirty_percent) = self.dirty_percent() {
+            components.push((dirty_percent * 5.0, weights.dirty_weight));
+        }
+        
+        let total_weight: f64 = components.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        
+        let weighted: f64 = components
+            .iter()
+            .map(|(value, weight)| value.clamp(0.0, 100.0) * weight.max(0.0))
+            .sum();
+        weighted / total_weight
+    }
+}
+
+/// Relative weights of the inputs to `MemoryStats::health_score_with`.
+/// 
+/// Weights need not sum to 1; they are normalised over the inputs present
+/// in the snapshot. Negative weights count as zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HealthScoreWeights {
+    pub used_percent_weight: f64,  // Weight of used_percent
+    pub fragmentation_weight: f64, // Weight of the fragmentation score
+    pub psi_weight: f64,           // Weight of the PSI some avg10 stall percentage
+    pub dirty_weight: f64,         // Weight of dirty and writeback pages
+}
+
+impl Default for HealthScoreWeights {
+    /// Usage dominates, with stalls next since they show the system is
+    /// already struggling; fragmentation and dirty pages refine the score.
+    fn default() -> Self {
+        HealthScoreWeights {
+            used_percent_weight: 0.5,
+            fragmentation_weight: 0.15,
+            psi_weight: 0.25,
+            dirty_weight: 0.1,
+        }
+    }
+}
+
+impl fmt::Display for MemoryStats {
+    /// One-line summary, e.g. `Memory: 8.2 GiB used / 16.0 GiB total (51.3%) |
+    /// free=6.1 GiB available=7.8 GiB @ 2024-01-01T00:00:00.000Z`; buffers and
+    /// cached follow available when the platform reports them.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Memory: {} used / {} total ({:.1}%) | free={} available={}",
+            HumanBytes(self.used),
+            HumanBytes(self.total),
+            self.used_percent,
+            HumanBytes(self.free),
+            HumanBytes(self.available)
+        )?;
+        if let Some(buffers) = self.buffers {
+            write!(f, " buffers={}", HumanBytes(buffers))?;
+        }
+        if let Some(cached) = self.cached {
+            write!(f, " cached={}", HumanBytes(cached))?;
+        }
+        write!(f, " @ {}", self.timestamp)
+    }
 }
 
 /// Get current memory statistics.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", ret))]
 pub fn get_memory_stats() -> MemoryStats {
     #[cfg(target_os = "linux")]
     return get_memory_stats_linux();
     
+    #[cfg(target_os = "android")]
+    return get_memory_stats_android();
+    
     #[cfg(target_os = "macos")]
     return get_memory_stats_macos();
     
     #[cfg(target_os = "windows")]
     return get_memory_stats_windows();
     
+    #[cfg(target_os = "freebsd")]
+    return get_memory_stats_freebsd();
+    
+    #[cfg(target_os = "openbsd")]
+    return get_memory_stats_openbsd();
+    
+    #[cfg(target_arch = "wasm32")]
+    return get_memory_stats_wasm();
+    
     // Default implementation for unsupported platforms
-    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "windows",
+                  target_os = "freebsd", target_os = "openbsd", target_arch = "wasm32")))]
     return MemoryStats {
         total: 0,
         free: 0,
@@ -37,13 +275,111 @@ pub fn get_memory_stats() -> MemoryStats {
         used_percent: 0.0,
         buffers: None,
         cached: None,
+        speculative: None,
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: None,
+        writeback: None,
         timestamp: format_timestamp(),
     };
 }
 
+/// Selects which optional `MemoryStats` fields to collect.
+/// 
+/// Collection skips the work behind fields that are turned off, such as the
+/// `/proc/buddyinfo` read for the fragmentation score. Fields that are off
+/// are `None`, or an empty string for the timestamp. Everything except swap
+/// and fragmentation is on by default, matching `get_memory_stats()`.
+#[derive(Debug, Clone)]
+pub struct MemoryStatsConfig {
+    buffers: bool,
+    cached: bool,
+    timestamp: bool,
+    swap: bool,
+    fragmentation: bool,
+}
+
+impl MemoryStatsConfig {
+    pub fn new() -> Self {
+        MemoryStatsConfig {
+            buffers: true,
+            cached: true,
+            timestamp: true,
+            swap: false,
+            fragmentation: false,
+        }
+    }
+    
+    pub fn include_buffers(mut self, include: bool) -> Self {
+        self.buffers = include;
+        self
+    }
+    
+    pub fn include_cached(mut self, include: bool) -> Self {
+        self.cached = include;
+        self
+    }
+    
+    pub fn include_timestamp(mut self, include: bool) -> Self {
+        self.timestamp = include;
+        self
+    }
+    
+    /// Fill `swap_total` and `swap_free`.
+    pub fn include_swap(mut self, include: bool) -> Self {
+        self.swap = include;
+        self
+    }
+    
+    /// Fill `fragmentation_score` where the platform supports it.
+    pub fn include_fragmentation(mut self, include: bool) -> Self {
+        self.fragmentation = include;
+        self
+    }
+    
+    /// Collect memory statistics with the selected fields.
+    pub fn fetch(&self) -> MemoryStats {
+        #[cfg(target_os = "linux")]
+        let mut stats = get_memory_stats_linux_with(self);
+        
+        #[cfg(not(target_os = "linux"))]
+        let mut stats = {
+            let mut stats = get_memory_stats();
+            if self.swap {
+                let swap = get_swap_stats();
+                stats.swap_total = Some(swap.total);
+                stats.swap_free = Some(swap.free);
+            }
+            stats
+        };
+        
+        if !self.buffers {
+            stats.buffers = None;
+        }
+        if !self.cached {
+            stats.cached = None;
+        }
+        if !self.fragmentation {
+            stats.fragmentation_score = None;
+        }
+        if !self.timestamp {
+            stats.timestamp = String::new();
+        }
+        stats
+    }
+}
+
+impl Default for MemoryStatsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Format current time as ISO8601 timestamp.
+#[cfg(feature = "timestamps")]
 fn format_timestamp() -> String {
-    match SystemTime::now().duration_since(UNIX_EPOCH) {
+    match now_since_epoch() {
         Ok(duration) => {
             let secs = duration.as_secs();
             let millis = duration.subsec_millis();
@@ -56,41 +392,296 @@ fn format_timestamp() -> String {
     }
 }
 
+/// Format current time as nanoseconds since the Unix epoch.
+#[cfg(not(feature = "timestamps"))]
+fn format_timestamp() -> String {
+    now_since_epoch()
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0)
+        .to_string()
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn now_since_epoch() -> Result<Duration, ()> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    SystemTime::now().duration_since(UNIX_EPOCH).map_err(|_| ())
+}
+
+/// SystemTime::now() panics on wasm32-unknown-unknown; ask the JS host instead.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "wasm-bindgen"))]
+fn now_since_epoch() -> Result<Duration, ()> {
+    Ok(Duration::from_millis(js_sys::Date::now() as u64))
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", not(feature = "wasm-bindgen")))]
+fn now_since_epoch() -> Result<Duration, ()> {
+    Err(())
+}
+
+/// Change between two `MemoryStats` snapshots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryDelta {
+    pub used_delta: i64,         // Change in used memory in bytes
+    pub free_delta: i64,         // Change in free memory in bytes
+    pub available_delta: i64,    // Change in available memory in bytes
+    pub used_percent_delta: f64, // Change in used percentage points
+    pub elapsed_ms: u64,         // Time between the snapshots, 0 if out of order
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub fragmentation_score_changed: Option<f64>, // Change in fragmentation score, negative as compaction progresses
+}
+
+/// Compute how memory changed from `before` to `after`.
+pub fn compute_delta(before: &MemoryStats, after: &MemoryStats) -> MemoryDelta {
+    let elapsed_ms = match (parse_timestamp_ms(&before.timestamp), parse_timestamp_ms(&after.timestamp)) {
+        (Some(start), Some(end)) if end > start => (end - start) as u64,
+        _ => 0,
+    };
+    
+    MemoryDelta {
+        used_delta: after.used as i64 - before.used as i64,
+        free_delta: after.free as i64 - before.free as i64,
+        available_delta: after.available as i64 - before.available as i64,
+        used_percent_delta: after.used_percent - before.used_percent,
+        elapsed_ms,
+        fragmentation_score_changed: match (before.fragmentation_score, after.fragmentation_score) {
+            (Some(before), Some(after)) => Some(after - before),
+            _ => None,
+        },
+    }
+}
+
+/// `after - before` yields the change between two snapshots.
+impl Sub for MemoryStats {
+    type Output = MemoryDelta;
+    
+    fn sub(self, before: MemoryStats) -> MemoryDelta {
+        compute_delta(&before, &self)
+    }
+}
+
+impl<'a> Sub for &'a MemoryStats {
+    type Output = MemoryDelta;
+    
+    fn sub(self, before: &'a MemoryStats) -> MemoryDelta {
+        compute_delta(before, self)
+    }
+}
+
+/// Get memory statistics on WebAssembly.
+/// 
+/// Best effort: `total` is the 4 GiB wasm32 address space, `used` the linear
+/// memory grown so far. Pages freed by the allocator are never returned to
+/// the host, so `used` only grows.
+#[cfg(target_arch = "wasm32")]
+fn get_memory_stats_wasm() -> MemoryStats {
+    const WASM_PAGE_SIZE: u64 = 64 * 1024;
+    const WASM32_MAX_PAGES: u64 = 65536;
+    
+    let total = WASM32_MAX_PAGES * WASM_PAGE_SIZE;
+    let used = (core::arch::wasm32::memory_size(0) as u64 * WASM_PAGE_SIZE).min(total);
+    let free = total - used;
+    
+    MemoryStats {
+        total,
+        free,
+        available: free,
+        used,
+        used_percent: (used as f64 / total as f64) * 100.0,
+        buffers: None,
+        cached: None,
+        speculative: None,
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: None,
+        writeback: None,
+        timestamp: format_timestamp(),
+    }
+}
+
+/// Read /proc/meminfo into a map of field name to value in bytes.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn read_meminfo() -> HashMap<String, u64> {
+    // Read /proc/meminfo for memory information
+    match std::fs::read("/proc/meminfo") {
+        Ok(contents) => parse_meminfo(&contents),
+        Err(err) => {
+            warn_event!("failed to read /proc/meminfo: {}", err);
+            HashMap::new()
+        }
+    }
+}
+
+/// Parse the raw contents of /proc/meminfo; lines that do not parse are skipped.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn parse_meminfo(contents: &[u8]) -> HashMap<String, u64> {
+    let mut mem_info = HashMap::new();
+    
+    for line in String::from_utf8_lossy(contents).lines() {
+        parse_meminfo_line(line, &mut mem_info);
+    }
+    
+    mem_info
+}
+
+/// Like `parse_meminfo`, but only parse the lines for `keys`.
+#[cfg(target_os = "linux")]
+fn parse_meminfo_keys(contents: &[u8], keys: &[&str]) -> HashMap<String, u64> {
+    let mut mem_info = HashMap::new();
+    
+    for line in String::from_utf8_lossy(contents).lines() {
+        let key = line.split(':').next().unwrap_or("").trim();
+        if keys.contains(&key) {
+            parse_meminfo_line(line, &mut mem_info);
+        }
+    }
+    
+    mem_info
+}
+
+/// Parse one "Key:   value kB" line of /proc/meminfo into `mem_info`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn parse_meminfo_line(line: &str, mem_info: &mut HashMap<String, u64>) {
+    let parts: Vec<&str> = line.split(':').collect();
+    if parts.len() == 2 {
+        let key = parts[0].trim();
+        let value_parts: Vec<&str> = parts[1].split_whitespace().collect();
+        
+        if let Some(Ok(value)) = value_parts.first().map(|value| value.parse::<u64>()) {
+            let value_in_bytes = match value_parts.get(1) {
+                // Saturate rather than overflow on absurd values
+                Some(unit) if unit.eq_ignore_ascii_case("kb") => value.saturating_mul(1024),
+                _ => value,
+            };
+            
+            mem_info.insert(key.to_string(), value_in_bytes);
+        }
+    }
+}
+
 /// Get memory statistics on Linux.
 #[cfg(target_os = "linux")]
 fn get_memory_stats_linux() -> MemoryStats {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
+    get_memory_stats_linux_with(&MemoryStatsConfig::new())
+}
+
+/// Get memory statistics on Linux, reading only what `config` asks for.
+#[cfg(target_os = "linux")]
+fn get_memory_stats_linux_with(config: &MemoryStatsConfig) -> MemoryStats {
+    // used is derived from Buffers and Cached, so they are always needed
+    let mut keys = vec!["MemTotal", "MemFree", "MemAvailable", "Buffers", "Cached", "Dirty", "Writeback"];
+    if config.swap {
+        keys.extend(["SwapTotal", "SwapFree"]);
+    }
+    let mem_info = match std::fs::read("/proc/meminfo") {
+        Ok(contents) => parse_meminfo_keys(&contents, &keys),
+        Err(err) => {
+            warn_event!("failed to read /proc/meminfo: {}", err);
+            HashMap::new()
+        }
+    };
     
-    let mut mem_info = HashMap::new();
+    let mut stats = memory_stats_from_meminfo(&mem_info);
+    if config.fragmentation {
+        stats.fragmentation_score = buddyinfo_fragmentation_score(&get_buddyinfo());
+    }
+    if config.swap {
+        stats.swap_total = mem_info.get("SwapTotal").cloned();
+        stats.swap_free = mem_info.get("SwapFree").cloned();
+    }
     
-    // Read /proc/meminfo for memory information
-    if let Ok(file) = File::open("/proc/meminfo") {
-        let reader = BufReader::new(file);
-        
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                let parts: Vec<&str> = line.split(':').collect();
-                if parts.len() == 2 {
-                    let key = parts[0].trim();
-                    let value_parts: Vec<&str> = parts[1].trim().split_whitespace().collect();
-                    
-                    if !value_parts.is_empty() {
-                        if let Ok(value) = value_parts[0].parse::<u64>() {
-                            let value_in_bytes = if value_parts.len() > 1 && value_parts[1].to_lowercase() == "kb" {
-                                value * 1024 // Convert KB to bytes
-                            } else {
-                                value
-                            };
-                            
-                            mem_info.insert(key.to_string(), value_in_bytes);
-                        }
-                    }
-                }
-            }
-        }
+    // Inside a container the cgroup limit, not physical memory, is the ceiling
+    if let Some(cgroup) = get_cgroup_memory_stats() {
+        clamp_to_cgroup_limit(&mut stats, &cgroup);
+    }
+    
+    stats
+}
+
+/// Clamp system-wide stats to a cgroup memory limit below physical memory.
+#[cfg(target_os = "linux")]
+fn clamp_to_cgroup_limit(stats: &mut MemoryStats, cgroup: &CgroupMemoryStats) {
+    let limit = match cgroup.limit {
+        Some(limit) if limit < stats.total => limit,
+        _ => return,
+    };
+    
+    let used = cgroup.current.min(limit);
+    let headroom = limit - used;
+    
+    stats.total = limit;
+    stats.used = used;
+    stats.free = stats.free.min(headroom);
+    stats.available = stats.available.min(headroom);
+    stats.used_percent = (used as f64 / limit as f64) * 100.0;
+}
+
+/// Get memory statistics on Android.
+#[cfg(target_os = "android")]
+fn get_memory_stats_android() -> MemoryStats {
+    let mut mem_info = read_meminfo();
+    
+    // SELinux policy denies /proc/meminfo to apps on some devices
+    if !mem_info.contains_key("MemTotal") {
+        return get_memory_stats_android_fallback();
     }
     
+    // Kernels before 3.14, common on older Android releases, lack MemAvailable;
+    // free plus page cache is the usual estimate
+    if !mem_info.contains_key("MemAvailable") {
+        let estimate = ["MemFree", "Buffers", "Cached"]
+            .iter()
+            .filter_map(|key| mem_info.get(*key))
+            .fold(0u64, |total, value| total.saturating_add(*value));
+        mem_info.insert("MemAvailable".to_string(), estimate);
+    }
+    
+    memory_stats_from_meminfo(&mem_info)
+}
+
+/// Approximate stats from sysconf and this process's RSS when /proc/meminfo is unreadable.
+#[cfg(target_os = "android")]
+fn get_memory_stats_android_fallback() -> MemoryStats {
+    let page_size = util::page_size() as u64;
+    let (phys_pages, avail_pages) = unsafe { (libc::sysconf(libc::_SC_PHYS_PAGES), libc::sysconf(libc::_SC_AVPHYS_PAGES)) };
+    
+    let total = (phys_pages.max(0) as u64).saturating_mul(page_size);
+    let free = (avail_pages.max(0) as u64).saturating_mul(page_size).min(total);
+    // Only our own footprint is visible, so "used" is this process's RSS
+    let used = process::get_current_process_stats()
+        .map(|stats| stats.rss)
+        .unwrap_or(total - free)
+        .min(total);
+    
+    let used_percent = if total > 0 {
+        (used as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    };
+    
+    MemoryStats {
+        total,
+        free,
+        available: free,
+        used,
+        used_percent,
+        buffers: None,
+        cached: None,
+        speculative: None,
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: None,
+        writeback: None,
+        timestamp: format_timestamp(),
+    }
+}
+
+/// Build `MemoryStats` from parsed /proc/meminfo fields.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn memory_stats_from_meminfo(mem_info: &HashMap<String, u64>) -> MemoryStats {
     // Extract values from the map
     let total = mem_info.get("MemTotal").cloned().unwrap_or(0);
     let free = mem_info.get("MemFree").cloned().unwrap_or(0);
@@ -98,11 +689,11 @@ fn get_memory_stats_linux() -> MemoryStats {
     let buffers = mem_info.get("Buffers").cloned();
     let cached = mem_info.get("Cached").cloned();
     
-    // Calculate used memory
+    // Calculate used memory; inconsistent fields must not underflow
     let used = if let (Some(buffers_val), Some(cached_val)) = (buffers, cached) {
-        total - free - buffers_val - cached_val
+        total.saturating_sub(free).saturating_sub(buffers_val).saturating_sub(cached_val)
     } else {
-        total - free
+        total.saturating_sub(free)
     };
     
     // Calculate percentage
@@ -120,6 +711,12 @@ fn get_memory_stats_linux() -> MemoryStats {
         used_percent,
         buffers,
         cached,
+        speculative: None,
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: mem_info.get("Dirty").cloned(),
+        writeback: mem_info.get("Writeback").cloned(),
         timestamp: format_timestamp(),
     }
 }
@@ -129,41 +726,46 @@ fn get_memory_stats_linux() -> MemoryStats {
 fn get_memory_stats_macos() -> MemoryStats {
     use std::process::Command;
     
-    let mut total: u64 = 0;
+    // Get total memory using sysctl
+    let memsize = Command::new("sysctl").args(&["-n", "hw.memsize"]).output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    
+    // Get memory statistics using vm_stat
+    let vm_stat = Command::new("vm_stat").output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    
+    memory_stats_from_macos(&memsize, &vm_stat)
+}
+
+/// Build `MemoryStats` from `sysctl -n hw.memsize` and `vm_stat` output.
+#[cfg(target_os = "macos")]
+pub(crate) fn memory_stats_from_macos(memsize: &str, vm_stat: &str) -> MemoryStats {
     let mut free: u64 = 0;
     let mut active: u64 = 0;
     let mut inactive: u64 = 0;
     let mut speculative: u64 = 0;
     
-    // Get total memory using sysctl
-    if let Ok(output) = Command::new("sysctl").args(&["-n", "hw.memsize"]).output() {
-        if let Ok(output_str) = String::from_utf8(output.stdout) {
-            if let Ok(value) = output_str.trim().parse::<u64>() {
-                total = value;
-            }
-        }
-    }
+    let total = memsize.trim().parse::<u64>().unwrap_or(0);
     
-    // Get memory statistics using vm_stat
-    if let Ok(output) = Command::new("vm_stat").output() {
-        if let Ok(output_str) = String::from_utf8(output.stdout) {
-            let page_size: u64 = 4096; // Default page size in bytes
+    let page_size: u64 = 4096; // Default page size in bytes
+    
+    for line in vm_stat.lines() {
+        let parts: Vec<&str> = line.split(':').collect();
+        if parts.len() == 2 {
+            let key = parts[0].trim();
+            let value_str = parts[1].trim().trim_end_matches('.');
             
-            for line in output_str.lines() {
-                let parts: Vec<&str> = line.split(':').collect();
-                if parts.len() == 2 {
-                    let key = parts[0].trim();
-                    let value_str = parts[1].trim().trim_end_matches('.');
-                    
-                    if let Ok(value) = value_str.parse::<u64>() {
-                        match key {
-                            "Pages free" => free = value * page_size,
-                            "Pages active" => active = value * page_size,
-                            "Pages inactive" => inactive = value * page_size,
-                            "Pages speculative" => speculative = value * page_size,
-                            _ => {}
-                        }
-                    }
+            if let Ok(value) = value_str.parse::<u64>() {
+                match key {
+                    "Pages free" => free = value * page_size,
+                    "Pages active" => active = value * page_size,
+                    "Pages inactive" => inactive = value * page_size,
+                    "Pages speculative" => speculative = value * page_size,
+                    _ => {}
                 }
             }
         }
@@ -190,47 +792,254 @@ fn get_memory_stats_macos() -> MemoryStats {
         used_percent,
         buffers: None,
         cached: None,
+        speculative: Some(speculative),
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: None,
+        writeback: None,
         timestamp: format_timestamp(),
     }
 }
 
-/// Get memory statistics on Windows.
-#[cfg(target_os = "windows")]
-fn get_memory_stats_windows() -> MemoryStats {
-    use winapi::um::sysinfoapi::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
-    use winapi::um::winnt::DWORD;
+/// State of the macOS memory compressor (macOS specific).
+#[cfg(target_os = "macos")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MacosCompressionStats {
+    pub pages_compressed: u64,   // Pages stored in the compressor, before compression
+    pub compression_ratio: f64,  // Pages stored per page the compressor occupies, 0.0 when empty
+    pub memorystatus_level: u32, // kern.memorystatus_level: percentage of memory considered free
+}
+
+/// Get memory compressor statistics from `vm_stat` and `kern.memorystatus_level`.
+#[cfg(target_os = "macos")]
+pub fn get_macos_compression_stats() -> MacosCompressionStats {
+    use std::process::Command;
     
-    let mut memory_status = MEMORYSTATUSEX {
-        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as DWORD,
-        dwMemoryLoad: 0,
-        ullTotalPhys: 0,
-        ullAvailPhys: 0,
-        ullTotalPageFile: 0,
-        ullAvailPageFile: 0,
-        ullTotalVirtual: 0,
-        ullAvailVirtual: 0,
-        ullAvailExtendedVirtual: 0,
-    };
+    let vm_stat = Command::new("vm_stat").output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
     
-    unsafe {
-        if GlobalMemoryStatusEx(&mut memory_status) == 0 {
-            // Error getting memory status
-            return MemoryStats {
-                total: 0,
-                free: 0,
-                available: 0,
-                used: 0,
-                used_percent: 0.0,
-                buffers: None,
-                cached: None,
-                timestamp: format_timestamp(),
-            };
+    let level = Command::new("sysctl").args(["-n", "kern.memorystatus_level"]).output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    
+    macos_compression_from_vm_stat(&vm_stat, &level)
+}
+
+/// Build `MacosCompressionStats` from `vm_stat` and `sysctl -n kern.memorystatus_level` output.
+#[cfg(target_os = "macos")]
+pub(crate) fn macos_compression_from_vm_stat(vm_stat: &str, memorystatus_level: &str) -> MacosCompressionStats {
+    let mut stored: u64 = 0;
+    let mut occupied: u64 = 0;
+    
+    for line in vm_stat.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let value = match value.trim().trim_end_matches('.').parse::<u64>() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        
+        match key.trim() {
+            "Pages stored in compressor" => stored = value,
+            "Pages occupied by compressor" => occupied = value,
+            _ => {}
         }
     }
     
-    let total = memory_status.ullTotalPhys;
-    let available = memory_status.ullAvailPhys;
-    let free = available; // On Windows, free is the same as available
+    MacosCompressionStats {
+        pages_compressed: stored,
+        compression_ratio: if occupied > 0 { stored as f64 / occupied as f64 } else { 0.0 },
+        memorystatus_level: memorystatus_level.trim().parse().unwrap_or(0),
+    }
+}
+
+/// Read a numeric sysctl by name on FreeBSD.
+#[cfg(target_os = "freebsd")]
+fn sysctl_u64(name: &str) -> Option<u64> {
+    use std::ffi::CString;
+    
+    let c_name = CString::new(name).ok()?;
+    
+    // Counters are u_int, sizes are u_long; read into the wider type and
+    // interpret according to the length the kernel reports back
+    let mut buf = [0u8; 8];
+    let mut len = buf.len();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            c_name.as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            &mut len,
+            std::ptr::null(),
+            0,
+        )
+    };
+    
+    if ret != 0 {
+        return None;
+    }
+    
+    match len {
+        4 => Some(u64::from(u32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]))),
+        8 => Some(u64::from_ne_bytes(buf)),
+        _ => None,
+    }
+}
+
+/// Get memory statistics on FreeBSD.
+#[cfg(target_os = "freebsd")]
+fn get_memory_stats_freebsd() -> MemoryStats {
+    let total = sysctl_u64("hw.physmem").unwrap_or(0);
+    let page_size = sysctl_u64("vm.stats.vm.v_page_size").unwrap_or(4096);
+    let free = sysctl_u64("vm.stats.vm.v_free_count").unwrap_or(0) * page_size;
+    let inactive = sysctl_u64("vm.stats.vm.v_inactive_count").unwrap_or(0) * page_size;
+    let buffers = sysctl_u64("vfs.bufspace");
+    
+    // Inactive pages can be reclaimed without paging anything out
+    let available = (free + inactive).min(total);
+    let used = total - available;
+    
+    // Calculate percentage
+    let used_percent = if total > 0 {
+        (used as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    };
+    
+    MemoryStats {
+        total,
+        free,
+        available,
+        used,
+        used_percent,
+        buffers,
+        cached: None,
+        speculative: None,
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: None,
+        writeback: None,
+        timestamp: format_timestamp(),
+    }
+}
+
+/// Get memory statistics on OpenBSD.
+#[cfg(target_os = "openbsd")]
+fn get_memory_stats_openbsd() -> MemoryStats {
+    // Not exported by libc for OpenBSD
+    const HW_PHYSMEM64: libc::c_int = 19;
+    const VM_UVMEXP: libc::c_int = 4;
+    
+    // Leading int fields of struct uvmexp (sys/uvm/uvmexp.h)
+    const UVMEXP_PAGESIZE: usize = 0;
+    const UVMEXP_FREE: usize = 4;
+    const UVMEXP_INACTIVE: usize = 6;
+    
+    let mut total: u64 = 0;
+    let mut len = std::mem::size_of::<u64>();
+    let mut mib = [libc::CTL_HW, HW_PHYSMEM64];
+    unsafe {
+        if libc::sysctl(mib.as_mut_ptr(), 2, &mut total as *mut u64 as *mut libc::c_void, &mut len, std::ptr::null_mut(), 0) != 0 {
+            total = 0;
+        }
+    }
+    
+    // Oversized buffer so we never depend on the exact struct size
+    let mut uvmexp = [0 as libc::c_int; 256];
+    let mut len = std::mem::size_of_val(&uvmexp);
+    let mut mib = [libc::CTL_VM, VM_UVMEXP];
+    let have_uvmexp = unsafe {
+        libc::sysctl(mib.as_mut_ptr(), 2, uvmexp.as_mut_ptr() as *mut libc::c_void, &mut len, std::ptr::null_mut(), 0) == 0
+    };
+    
+    let (free, inactive) = if have_uvmexp {
+        let page_size = uvmexp[UVMEXP_PAGESIZE].max(0) as u64;
+        (
+            uvmexp[UVMEXP_FREE].max(0) as u64 * page_size,
+            uvmexp[UVMEXP_INACTIVE].max(0) as u64 * page_size,
+        )
+    } else {
+        (0, 0)
+    };
+    
+    let available = (free + inactive).min(total);
+    let used = total - available;
+    
+    // Calculate percentage
+    let used_percent = if total > 0 {
+        (used as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    };
+    
+    MemoryStats {
+        total,
+        free,
+        available,
+        used,
+        used_percent,
+        buffers: None,
+        cached: None,
+        speculative: None,
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: None,
+        writeback: None,
+        timestamp: format_timestamp(),
+    }
+}
+
+/// Get memory statistics on Windows.
+#[cfg(target_os = "windows")]
+fn get_memory_stats_windows() -> MemoryStats {
+    use winapi::um::sysinfoapi::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+    use winapi::shared::minwindef::DWORD;
+    
+    let mut memory_status = MEMORYSTATUSEX {
+        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as DWORD,
+        dwMemoryLoad: 0,
+        ullTotalPhys: 0,
+        ullAvailPhys: 0,
+        ullTotalPageFile: 0,
+        ullAvailPageFile: 0,
+        ullTotalVirtual: 0,
+        ullAvailVirtual: 0,
+        ullAvailExtendedVirtual: 0,
+    };
+    
+    unsafe {
+        if GlobalMemoryStatusEx(&mut memory_status) == 0 {
+            // Error getting memory status
+            return MemoryStats {
+                total: 0,
+                free: 0,
+                available: 0,
+                used: 0,
+                used_percent: 0.0,
+                buffers: None,
+                cached: None,
+                speculative: None,
+                fragmentation_score: None,
+                swap_total: None,
+                swap_free: None,
+                dirty: None,
+                writeback: None,
+                timestamp: format_timestamp(),
+            };
+        }
+    }
+    
+    let total = memory_status.ullTotalPhys;
+    let available = memory_status.ullAvailPhys;
+    let free = available; // On Windows, free is the same as available
     let used = total - available;
     let used_percent = memory_status.dwMemoryLoad as f64;
     
@@ -242,12 +1051,567 @@ fn get_memory_stats_windows() -> MemoryStats {
         used_percent,
         buffers: None,
         cached: None,
+        speculative: None,
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: None,
+        writeback: None,
+        timestamp: format_timestamp(),
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SwapStats {
+    pub total: u64,        // Total swap space in bytes
+    pub used: u64,         // Used swap space in bytes
+    pub free: u64,         // Free swap space in bytes
+    pub used_percent: f64, // Used swap as a percentage
+    pub timestamp: String, // Same format as `MemoryStats::timestamp`
+}
+
+/// Get current swap statistics.
+pub fn get_swap_stats() -> SwapStats {
+    #[cfg(target_os = "linux")]
+    let (total, free) = {
+        let mem_info = read_meminfo();
+        (
+            mem_info.get("SwapTotal").cloned().unwrap_or(0),
+            mem_info.get("SwapFree").cloned().unwrap_or(0),
+        )
+    };
+    
+    #[cfg(target_os = "macos")]
+    let (total, free) = get_swap_totals_macos();
+    
+    #[cfg(target_os = "windows")]
+    let (total, free) = get_swap_totals_windows();
+    
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    let (total, free) = (0u64, 0u64);
+    
+    let used = total.saturating_sub(free);
+    
+    // Calculate percentage
+    let used_percent = if total > 0 {
+        (used as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    };
+    
+    SwapStats {
+        total,
+        used,
+        free,
+        used_percent,
+        timestamp: format_timestamp(),
+    }
+}
+
+/// Get (total, free) swap in bytes on macOS from `sysctl vm.swapusage`.
+#[cfg(target_os = "macos")]
+fn get_swap_totals_macos() -> (u64, u64) {
+    use std::process::Command;
+    
+    // Output looks like "total = 2048.00M  used = 1024.25M  free = 1023.75M  (encrypted)"
+    let output = match Command::new("sysctl").args(["-n", "vm.swapusage"]).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(_) => return (0, 0),
+    };
+    
+    let mut total = 0;
+    let mut free = 0;
+    let tokens: Vec<&str> = output.split_whitespace().collect();
+    for window in tokens.windows(3) {
+        if window[1] != "=" {
+            continue;
+        }
+        
+        let value = parse_size_with_suffix(window[2]);
+        match window[0] {
+            "total" => total = value,
+            "free" => free = value,
+            _ => {}
+        }
+    }
+    
+    (total, free)
+}
+
+/// Parse a size like "1023.75M" into bytes.
+#[cfg(target_os = "macos")]
+fn parse_size_with_suffix(value: &str) -> u64 {
+    let (number, multiplier) = match value.chars().last() {
+        Some('K') => (&value[..value.len() - 1], 1024.0),
+        Some('M') => (&value[..value.len() - 1], 1024.0 * 1024.0),
+        Some('G') => (&value[..value.len() - 1], 1024.0 * 1024.0 * 1024.0),
+        _ => (value, 1.0),
+    };
+    
+    number.parse::<f64>().map(|n| (n * multiplier) as u64).unwrap_or(0)
+}
+
+/// Get (total, free) swap in bytes on Windows from the page file fields.
+#[cfg(target_os = "windows")]
+fn get_swap_totals_windows() -> (u64, u64) {
+    use winapi::um::sysinfoapi::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+    
+    let mut memory_status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+    memory_status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+    
+    unsafe {
+        if GlobalMemoryStatusEx(&mut memory_status) == 0 {
+            return (0, 0);
+        }
+    }
+    
+    // The page file figures include physical memory; subtract it to get swap
+    let total = memory_status.ullTotalPageFile.saturating_sub(memory_status.ullTotalPhys);
+    let free = memory_status.ullAvailPageFile.saturating_sub(memory_status.ullAvailPhys).min(total);
+    
+    (total, free)
+}
+
+/// System-wide commit, kernel pool and object counts from `GetPerformanceInfo` (Windows specific).
+#[cfg(target_os = "windows")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindowsSystemInfo {
+    pub commit_total: u64,    // Bytes currently committed by the system
+    pub commit_limit: u64,    // Bytes that can be committed without growing the page file
+    pub kernel_total: u64,    // Bytes in the paged and nonpaged kernel pools
+    pub kernel_paged: u64,    // Bytes in the paged kernel pool
+    pub kernel_nonpaged: u64, // Bytes in the nonpaged kernel pool
+    pub page_size: u64,       // Page size in bytes
+    pub process_count: u32,   // Running processes
+    pub handle_count: u32,    // Open handles
+    pub thread_count: u32,    // Running threads
+    pub timestamp: String,    // Same format as `MemoryStats::timestamp`
+}
+
+/// Get commit, kernel pool and object counts.
+/// 
+/// Supplements `get_memory_stats`, which only uses `GlobalMemoryStatusEx`.
+#[cfg(target_os = "windows")]
+pub fn get_windows_system_info() -> std::io::Result<WindowsSystemInfo> {
+    use winapi::um::psapi::{GetPerformanceInfo, PERFORMANCE_INFORMATION};
+    
+    let mut info: PERFORMANCE_INFORMATION = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<PERFORMANCE_INFORMATION>() as u32;
+    info.cb = size;
+    
+    if unsafe { GetPerformanceInfo(&mut info, size) } == 0 {
+        let err = std::io::Error::last_os_error();
+        warn_event!("GetPerformanceInfo failed: {}", err);
+        return Err(err);
+    }
+    
+    // Commit and kernel figures are reported in pages
+    let page_size = info.PageSize as u64;
+    Ok(WindowsSystemInfo {
+        commit_total: (info.CommitTotal as u64).saturating_mul(page_size),
+        commit_limit: (info.CommitLimit as u64).saturating_mul(page_size),
+        kernel_total: (info.KernelTotal as u64).saturating_mul(page_size),
+        kernel_paged: (info.KernelPaged as u64).saturating_mul(page_size),
+        kernel_nonpaged: (info.KernelNonpaged as u64).saturating_mul(page_size),
+        page_size,
+        process_count: info.ProcessCount,
+        handle_count: info.HandleCount,
+        thread_count: info.ThreadCount,
+        timestamp: format_timestamp(),
+    })
+}
+
+/// Page and swap activity counters from /proc/vmstat (Linux specific).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VmStats {
+    pub pgfault: u64,      // Page faults, minor and major
+    pub pgmajfault: u64,   // Major page faults that required I/O
+    pub pgpgin: u64,       // KB paged in from disk
+    pub pgpgout: u64,      // KB paged out to disk
+    pub pswpin: u64,       // Pages swapped in
+    pub pswpout: u64,      // Pages swapped out
+    pub pgsteal: u64,      // Pages reclaimed by kswapd and direct reclaim
+    pub pgscan: u64,       // Pages scanned by kswapd and direct reclaim
+    pub nr_dirty: u64,     // Pages waiting to be written back
+    pub nr_writeback: u64, // Pages currently being written back
+    pub oom_kill: u64,     // OOM killer invocations
+    pub timestamp: String, // Same format as `MemoryStats::timestamp`
+}
+
+/// Get page and swap activity counters.
+#[cfg(target_os = "linux")]
+pub fn get_vm_stats() -> VmStats {
+    // Read /proc/vmstat for paging activity
+    let mut vm_stats = match std::fs::read("/proc/vmstat") {
+        Ok(contents) => parse_vmstat(&contents),
+        Err(_) => VmStats::default(),
+    };
+    
+    vm_stats.timestamp = format_timestamp();
+    vm_stats
+}
+
+/// Parse the raw contents of /proc/vmstat; lines that do not parse are skipped.
+/// 
+/// `timestamp` is left empty.
+#[cfg(target_os = "linux")]
+pub fn parse_vmstat(contents: &[u8]) -> VmStats {
+    let mut vm_stats = VmStats::default();
+    
+    for line in String::from_utf8_lossy(contents).lines() {
+        parse_vmstat_line(line, &mut vm_stats);
+    }
+    
+    vm_stats
+}
+
+/// Parse one "name value" line of /proc/vmstat into `vm_stats`.
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_vmstat_line(line: &str, vm_stats: &mut VmStats) {
+    let mut parts = line.split_whitespace();
+    let (key, value) = match (parts.next(), parts.next()) {
+        (Some(key), Some(value)) => (key, value),
+        _ => return,
+    };
+    
+    let value = match value.parse::<u64>() {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    
+    match key {
+        "pgfault" => vm_stats.pgfault = value,
+        "pgmajfault" => vm_stats.pgmajfault = value,
+        "pgpgin" => vm_stats.pgpgin = value,
+        "pgpgout" => vm_stats.pgpgout = value,
+        "pswpin" => vm_stats.pswpin = value,
+        "pswpout" => vm_stats.pswpout = value,
+        "nr_dirty" => vm_stats.nr_dirty = value,
+        "nr_writeback" => vm_stats.nr_writeback = value,
+        "oom_kill" => vm_stats.oom_kill = value,
+        // Reclaim is split by reclaimer (kswapd, direct, khugepaged)
+        _ if key.starts_with("pgsteal_") => vm_stats.pgsteal = vm_stats.pgsteal.saturating_add(value),
+        _ if key.starts_with("pgscan_") => vm_stats.pgscan = vm_stats.pgscan.saturating_add(value),
+        _ => {}
+    }
+}
+
+/// Rates of change of the `VmStats` counters between two polls.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VmStatDelta {
+    pub pgfault_per_sec: f64,    // Page faults, minor and major
+    pub pgmajfault_per_sec: f64, // Major page faults that required I/O
+    pub pgpgin_per_sec: f64,     // KB paged in from disk
+    pub pgpgout_per_sec: f64,    // KB paged out to disk
+    pub pswpin_per_sec: f64,     // Pages swapped in
+    pub pswpout_per_sec: f64,    // Pages swapped out
+    pub pgsteal_per_sec: f64,    // Pages reclaimed
+    pub pgscan_per_sec: f64,     // Pages scanned for reclaim
+    pub oom_kill_per_sec: f64,   // OOM killer invocations
+    pub elapsed_secs: f64,       // Time between the two samples
+    pub timestamp: String,       // Timestamp of the later sample
+}
+
+/// Turns successive `get_vm_stats()` samples into per-second rates.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+pub struct VmStatPoller {
+    last: VmStats,
+    last_at: Instant,
+}
+
+#[cfg(target_os = "linux")]
+impl VmStatPoller {
+    /// Take the first sample; rates are measured from here.
+    pub fn new() -> VmStatPoller {
+        VmStatPoller {
+            last: get_vm_stats(),
+            last_at: Instant::now(),
+        }
+    }
+    
+    /// Take a new sample and return the rates since the previous one.
+    /// 
+    /// Counters that went backwards, as after a container restart, count as
+    /// zero. All rates are zero if no time has passed.
+    pub fn poll(&mut self) -> VmStatDelta {
+        let current = get_vm_stats();
+        let now = Instant::now();
+        let elapsed_secs = now.duration_since(self.last_at).as_secs_f64();
+        
+        let rate = |after: u64, before: u64| {
+            if elapsed_secs > 0.0 {
+                after.saturating_sub(before) as f64 / elapsed_secs
+            } else {
+                0.0
+            }
+        };
+        let last = &self.last;
+        let delta = VmStatDelta {
+            pgfault_per_sec: rate(current.pgfault, last.pgfault),
+            pgmajfault_per_sec: rate(current.pgmajfault, last.pgmajfault),
+            pgpgin_per_sec: rate(current.pgpgin, last.pgpgin),
+            pgpgout_per_sec: rate(current.pgpgout, last.pgpgout),
+            pswpin_per_sec: rate(current.pswpin, last.pswpin),
+            pswpout_per_sec: rate(current.pswpout, last.pswpout),
+            pgsteal_per_sec: rate(current.pgsteal, last.pgsteal),
+            pgscan_per_sec: rate(current.pgscan, last.pgscan),
+            oom_kill_per_sec: rate(current.oom_kill, last.oom_kill),
+            elapsed_secs,
+            timestamp: current.timestamp.clone(),
+        };
+        
+        self.last = current;
+        self.last_at = now;
+        delta
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Default for VmStatPoller {
+    fn default() -> Self {
+        VmStatPoller::new()
+    }
+}
+
+/// Memory totals for one NUMA node.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NumaNodeStats {
+    pub node_id: u32,
+    pub total: u64,             // Total memory on the node in bytes
+    pub free: u64,              // Free memory on the node in bytes
+    pub used: u64,              // Used memory on the node in bytes
+    pub distance_map: Vec<u32>, // Access cost to every node, indexed by node order; 10 is local
+}
+
+/// Get per-node memory totals, sorted by node id.
+/// 
+/// Returns an empty list on non-Linux platforms and kernels without NUMA support.
+pub fn get_numa_stats() -> Vec<NumaNodeStats> {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(topology) = linux::nid_to_cpus::build_numa_topology() {
+            return topology.nodes.into_iter().map(|node| node.memory_stats).collect();
+        }
+    }
+    
+    Vec::new()
+}
+
+/// Free block counts for one memory zone from /proc/buddyinfo.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BuddyZone {
+    pub node: u32,              // NUMA node
+    pub zone: String,           // Zone name, e.g. "DMA32" or "Normal"
+    pub free_blocks: [u64; 11], // Free blocks of 2^order pages, for orders 0-10
+}
+
+impl BuddyZone {
+    /// Total free pages across all orders.
+    pub fn free_pages(&self) -> u64 {
+        self.free_blocks
+            .iter()
+            .enumerate()
+            .map(|(order, count)| count.saturating_mul(1 << order))
+            .fold(0u64, |total, pages| total.saturating_add(pages))
+    }
+    
+    /// Fraction of free pages sitting in order-0 blocks.
+    /// 
+    /// 0.0 means all free memory is in larger contiguous blocks; 1.0 means
+    /// every free page is isolated, the worst possible fragmentation.
+    pub fn fragmentation_score(&self) -> f64 {
+        let free_pages = self.free_pages();
+        if free_pages == 0 {
+            return 0.0;
+        }
+        
+        self.free_blocks[0] as f64 / free_pages as f64
+    }
+}
+
+/// Get per-zone free block counts.
+/// 
+/// Returns an empty list on platforms without /proc/buddyinfo.
+pub fn get_buddyinfo() -> Vec<BuddyZone> {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(contents) = std::fs::read("/proc/buddyinfo") {
+            return parse_buddyinfo(&contents);
+        }
+    }
+    
+    Vec::new()
+}
+
+/// Parse the raw contents of /proc/buddyinfo; lines that do not parse are skipped.
+#[cfg(target_os = "linux")]
+pub fn parse_buddyinfo(contents: &[u8]) -> Vec<BuddyZone> {
+    String::from_utf8_lossy(contents)
+        .lines()
+        .filter_map(parse_buddyinfo_line)
+        .collect()
+}
+
+/// Parse one "Node 0, zone   Normal   12   8 ..." line of /proc/buddyinfo.
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_buddyinfo_line(line: &str) -> Option<BuddyZone> {
+    let mut parts = line.split_whitespace();
+    
+    if parts.next()? != "Node" {
+        return None;
+    }
+    let node = parts.next()?.trim_end_matches(',').parse::<u32>().ok()?;
+    if parts.next()? != "zone" {
+        return None;
+    }
+    let zone = parts.next()?.to_string();
+    
+    let mut free_blocks = [0u64; 11];
+    for slot in free_blocks.iter_mut() {
+        // Kernels built with a smaller MAX_ORDER print fewer columns
+        match parts.next() {
+            Some(count) => *slot = count.parse::<u64>().ok()?,
+            None => break,
+        }
+    }
+    
+    Some(BuddyZone {
+        node,
+        zone,
+        free_blocks,
+    })
+}
+
+/// Stall averages for one line of a PSI file.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PressureAverages {
+    pub avg10: f64,  // Percentage of time stalled over the last 10 seconds
+    pub avg60: f64,  // Percentage of time stalled over the last 60 seconds
+    pub avg300: f64, // Percentage of time stalled over the last 300 seconds
+    pub total: u64,  // Total stall time in microseconds
+}
+
+/// Memory Pressure Stall Information.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryPressure {
+    pub some: PressureAverages, // At least one task stalled on memory
+    pub full: PressureAverages, // All non-idle tasks stalled on memory
+    pub timestamp: String,      // Same format as `MemoryStats::timestamp`
+}
+
+/// Get memory pressure stall information.
+/// 
+/// Reads /proc/pressure/memory on Linux 4.20+. Older kernels, kernels
+/// booted without PSI and other platforms report all zeros.
+pub fn get_memory_pressure() -> MemoryPressure {
+    #[allow(unused_mut)]
+    let mut pressure = MemoryPressure {
         timestamp: format_timestamp(),
+        ..MemoryPressure::default()
+    };
+    
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(contents) = std::fs::read_to_string("/proc/pressure/memory") {
+            for line in contents.lines() {
+                parse_pressure_line(line, &mut pressure);
+            }
+        }
+    }
+    
+    pressure
+}
+
+/// Parse one "some avg10=0.00 avg60=0.00 avg300=0.00 total=0" line of a PSI file.
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_pressure_line(line: &str, pressure: &mut MemoryPressure) {
+    let mut parts = line.split_whitespace();
+    let averages = match parts.next() {
+        Some("some") => &mut pressure.some,
+        Some("full") => &mut pressure.full,
+        _ => return,
+    };
+    
+    for field in parts {
+        let (key, value) = match field.split_once('=') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        
+        match key {
+            "avg10" => averages.avg10 = value.parse().unwrap_or(0.0),
+            "avg60" => averages.avg60 = value.parse().unwrap_or(0.0),
+            "avg300" => averages.avg300 = value.parse().unwrap_or(0.0),
+            "total" => averages.total = value.parse().unwrap_or(0),
+            _ => {}
+        }
     }
 }
 
+/// Score memory fragmentation from 0.0 (none) to 1.0 (fully fragmented).
+/// 
+/// On Linux this is the share of free pages sitting in isolated order-0
+/// blocks across all zones. Elsewhere it is a rough heuristic from
+/// `get_memory_stats`: the mean of the used ratio and the share of memory
+/// that is not available for allocation.
+pub fn fragmentation_score() -> f64 {
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(score) = buddyinfo_fragmentation_score(&get_buddyinfo()) {
+            return score;
+        }
+    }
+    
+    let stats = get_memory_stats();
+    if stats.total == 0 {
+        return 0.0;
+    }
+    
+    let unavailable = 1.0 - (stats.available as f64 / stats.total as f64);
+    ((stats.used_percent / 100.0 + unavailable) / 2.0).clamp(0.0, 1.0)
+}
+
+/// Order-0 share of free pages summed over `zones`, `None` without free pages.
+#[cfg(target_os = "linux")]
+fn buddyinfo_fragmentation_score(zones: &[BuddyZone]) -> Option<f64> {
+    let order0 = zones.iter().fold(0u64, |total, zone| total.saturating_add(zone.free_blocks[0]));
+    let free_pages = zones.iter().fold(0u64, |total, zone| total.saturating_add(zone.free_pages()));
+    
+    if free_pages == 0 {
+        return None;
+    }
+    Some(order0 as f64 / free_pages as f64)
+}
+
 /// Release memory cache to free up memory.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", ret))]
 pub fn release_memory_cache() -> bool {
+    // Only worth a second sample when someone will see the result
+    #[cfg(any(feature = "logging", feature = "tracing"))]
+    let free_before = get_memory_stats().free;
+    
+    let released = release_platform_memory_cache();
+    
+    #[cfg(any(feature = "logging", feature = "tracing"))]
+    if released {
+        let bytes_freed = get_memory_stats().free as i64 - free_before as i64;
+        info_event!("memory cache released, {} bytes freed", bytes_freed);
+    }
+    
+    released
+}
+
+fn release_platform_memory_cache() -> bool {
     #[cfg(target_os = "linux")]
     return release_memory_cache_linux();
     
@@ -271,13 +1635,28 @@ fn release_memory_cache_linux() -> bool {
     
     // First, sync to disk to ensure data is safe
     let sync_result = Command::new("sync").status();
+    if let Err(err) = &sync_result {
+        warn_event!("sync failed: {}", err);
+    }
     
-    // Try to drop caches
-    let drop_caches_result = File::create("/proc/sys/vm/drop_caches")
-        .and_then(|mut file| file.write_all(b"3"))
-        .is_ok();
-    
-    sync_result.is_ok() || drop_caches_result
+    // Try to drop caches; the write can be refused transiently, so retry it
+    let drop_caches_result = retry::retry_healing_io(
+        || File::create("/proc/sys/vm/drop_caches").and_then(|mut file| file.write_all(b"3")),
+        retry::DEFAULT_MAX_ATTEMPTS,
+        retry::DEFAULT_BASE_DELAY_MS,
+    );
+    match drop_caches_result {
+        Ok(()) => true,
+        // Not running as root; no other attempt or fallback will help
+        Err(ref err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+            warn_event!("not permitted to write /proc/sys/vm/drop_caches: {}", err);
+            false
+        }
+        Err(err) => {
+            warn_event!("failed to write /proc/sys/vm/drop_caches: {}", err);
+            sync_result.is_ok()
+        }
+    }
 }
 
 /// Release memory cache on macOS.
@@ -286,7 +1665,15 @@ fn release_memory_cache_macos() -> bool {
     use std::process::Command;
     
     // On macOS, the purge command can clear inactive memory
-    Command::new("purge").status().is_ok()
+    let purged = retry::retry_healing(
+        || Command::new("purge").status().is_ok(),
+        retry::DEFAULT_MAX_ATTEMPTS,
+        retry::DEFAULT_BASE_DELAY_MS,
+    );
+    if !purged {
+        warn_event!("purge could not be run");
+    }
+    purged
 }
 
 /// Release memory cache on Windows.
@@ -296,39 +1683,109 @@ fn release_memory_cache_windows() -> bool {
     use winapi::um::psapi::EmptyWorkingSet;
     
     // On Windows, we can empty the working set of the current process
-    unsafe {
-        let handle = GetCurrentProcess();
-        EmptyWorkingSet(handle) != 0
+    let emptied = retry::retry_healing(
+        || unsafe { EmptyWorkingSet(GetCurrentProcess()) != 0 },
+        retry::DEFAULT_MAX_ATTEMPTS,
+        retry::DEFAULT_BASE_DELAY_MS,
+    );
+    if !emptied {
+        warn_event!("EmptyWorkingSet failed: {}", std::io::Error::last_os_error());
+    }
+    emptied
+}
+
+/// Which allocations `simulate_memory_fragmentation` frees to leave holes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FragmentationPattern {
+    EveryThird,            // Free every third block
+    Random(f64),           // Free each block with the given probability
+    Checkerboard,          // Free every other block
+    LargeSmallAlternating, // Alternate full-size and 1/16-size blocks, free the large ones
+}
+
+impl FragmentationPattern {
+    /// Size of block `index` for a requested block `size`.
+    fn block_size(&self, index: usize, size: usize) -> usize {
+        match self {
+            FragmentationPattern::LargeSmallAlternating if index % 2 == 1 => (size / 16).max(1),
+            _ => size,
+        }
+    }
+    
+    /// Whether block `index` is freed while its neighbours are still held.
+    fn frees(&self, index: usize, random: &std::collections::hash_map::RandomState) -> bool {
+        use std::hash::{BuildHasher, Hasher};
+        
+        match self {
+            FragmentationPattern::EveryThird => index.is_multiple_of(3),
+            FragmentationPattern::Random(free_probability) => {
+                let mut hasher = random.build_hasher();
+                hasher.write_usize(index);
+                (hasher.finish() as f64 / u64::MAX as f64) < *free_probability
+            }
+            FragmentationPattern::Checkerboard | FragmentationPattern::LargeSmallAlternating => index.is_multiple_of(2),
+        }
     }
 }
 
+/// Fragmentation observed by `measure_fragmentation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FragmentationSample {
+    pub pattern: FragmentationPattern,
+    pub blocks_held: usize,      // Blocks still allocated when the score was taken
+    pub holes: usize,            // Blocks freed between them
+    pub score_before: f64,       // fragmentation_score() before allocating
+    pub score_with_holes: f64,   // fragmentation_score() while the holes were open
+}
+
 /// Simulate memory fragmentation for testing purposes.
-pub fn simulate_memory_fragmentation(count: i32, size_kb: i32) -> bool {
-    // Vector to hold allocations
-    let mut allocations = Vec::new();
-    let size = (size_kb as usize) * 1024;
+/// 
+/// Runs `measure_fragmentation` and discards the scores. Returns false if
+/// `count` or `size_kb` is not positive.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", ret))]
+pub fn simulate_memory_fragmentation(count: i32, size_kb: i32, pattern: FragmentationPattern) -> bool {
+    measure_fragmentation(count, size_kb, pattern).is_some()
+}
+
+/// Fragment memory with `pattern` and score the result.
+/// 
+/// Allocates `count` blocks of `size_kb` KiB and touches every page, frees
+/// the blocks `pattern` picks while the rest are still held, samples
+/// `fragmentation_score()`, and only then frees the remaining blocks.
+/// 
+/// With glibc, blocks of 128 KiB or more are mmap'd individually, so each
+/// hole hands its pages back to the kernel and shows up in
+/// /proc/buddyinfo. Smaller blocks come from the heap; their holes fragment
+/// only the process heap and barely move the score. The score counts free
+/// order-0 pages, so holes of many contiguous pages can coalesce in the
+/// buddy allocator and lower it. Returns `None` if `count` or `size_kb` is
+/// not positive.
+pub fn measure_fragmentation(count: i32, size_kb: i32, pattern: FragmentationPattern) -> Option<FragmentationSample> {
+    if count <= 0 || size_kb <= 0 {
+        return None;
+    }
     
-    // Perform allocations in a pattern that tends to cause fragmentation
-    for i in 0..count {
-        // Create a layout for our allocation
-        let layout = Layout::from_size_align(size, 64).unwrap();
+    let size = (size_kb as usize).checked_mul(1024)?;
+    let random = std::collections::hash_map::RandomState::new();
+    let page_size = util::page_size();
+    let score_before = fragmentation_score();
+    
+    let mut allocations = Vec::with_capacity(count as usize);
+    for i in 0..count as usize {
+        let layout = Layout::from_size_align(pattern.block_size(i, size), 64).ok()?;
         
         unsafe {
-            // Allocate memory
             let ptr = alloc(layout);
             if !ptr.is_null() {
-                // Write some data to ensure it's actually allocated
-                for j in 0..size.min(1024) {
-                    *ptr.add(j) = (i % 255) as u8;
-                }
-                
-                // Store the allocation if we'll keep some
-                if i % 3 != 0 {
-                    allocations.push((ptr, layout));
-                } else {
-                    // Free immediately to create fragmentation
-                    dealloc(ptr, layout);
+                // Write every page so the block is backed by physical memory
+                for offset in (0..layout.size()).step_by(page_size) {
+                    ptr.add(offset).write_volatile((i % 255) as u8);
                 }
+                allocations.push((i, ptr, layout));
+            } else {
+                warn_event!("allocation of {} bytes failed", layout.size());
             }
         }
         
@@ -338,29 +1795,702 @@ pub fn simulate_memory_fragmentation(count: i32, size_kb: i32) -> bool {
         }
     }
     
-    // Free remaining allocations
-    for (ptr, layout) in allocations {
+    // Punch the holes while their neighbours are still held
+    let (freed, kept): (Vec<_>, Vec<_>) = allocations.into_iter().partition(|(i, _, _)| pattern.frees(*i, &random));
+    for &(_, ptr, layout) in &freed {
         unsafe {
             dealloc(ptr, layout);
         }
     }
     
-    true
+    let score_with_holes = fragmentation_score();
+    debug_event!(
+        "holding {} blocks around {} holes: fragmentation score {:.3} -> {:.3}",
+        kept.len(),
+        freed.len(),
+        score_before,
+        score_with_holes
+    );
+    
+    let sample = FragmentationSample {
+        pattern,
+        blocks_held: kept.len(),
+        holes: freed.len(),
+        score_before,
+        score_with_holes,
+    };
+    
+    for (_, ptr, layout) in kept {
+        unsafe {
+            dealloc(ptr, layout);
+        }
+    }
+    
+    Some(sample)
+}
+
+/// Block size used by `simulate_high_usage`.
+const PRESSURE_BLOCK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Apply sustained memory pressure for testing purposes.
+/// 
+/// Allocates enough 4 MB blocks to bring system usage up to `target_percent`,
+/// touching every page so the memory is actually resident, holds them for
+/// `duration_ms` and frees them. The allocation never exceeds the memory
+/// currently available, to stay clear of the OOM killer. Returns `false` if
+/// `target_percent` is outside 0..=100 or an allocation fails.
+pub fn simulate_high_usage(target_percent: f64, duration_ms: u64) -> bool {
+    if !(0.0..=100.0).contains(&target_percent) {
+        return false;
+    }
+    
+    let stats = get_memory_stats();
+    let target_used = (stats.total as f64 * target_percent / 100.0) as u64;
+    let needed = target_used.saturating_sub(stats.used).min(stats.available) as usize;
+    
+    let layout = Layout::from_size_align(PRESSURE_BLOCK_SIZE, 4096).unwrap();
+    let mut blocks = Vec::with_capacity(needed.div_ceil(PRESSURE_BLOCK_SIZE));
+    let mut success = true;
+    
+    for _ in 0..needed.div_ceil(PRESSURE_BLOCK_SIZE) {
+        let ptr = unsafe { alloc(layout) };
+        if ptr.is_null() {
+            success = false;
+            break;
+        }
+        
+        // Untouched pages are never backed, so write to each of them
+        for offset in (0..PRESSURE_BLOCK_SIZE).step_by(4096) {
+            unsafe {
+                ptr.add(offset).write_volatile(1);
+            }
+        }
+        blocks.push(ptr);
+    }
+    
+    if success {
+        thread::sleep(Duration::from_millis(duration_ms));
+    }
+    
+    for ptr in blocks {
+        unsafe {
+            dealloc(ptr, layout);
+        }
+    }
+    
+    success
+}
+
+/// Outcome of a defragmentation pass.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DefragResult {
+    pub success: bool,        // Whether the strategy reported success
+    pub bytes_reclaimed: i64, // Change in free memory, negative if it shrank
+    #[cfg_attr(feature = "serde", serde(alias = "elapsed_ms"))]
+    pub duration_ms: u64,     // Wall time spent compacting
+}
+
+/// How `defragment_with_strategy` tries to give memory back.
+pub enum DefragStrategy {
+    JemallocPurge,                        // Purge all jemalloc arenas (needs the `jemalloc` feature)
+    MallocTrim(i32),                      // glibc `malloc_trim` keeping this many bytes of heap top padding
+    DropPageCache,                        // Drop the OS page cache, see `release_memory_cache`
+    Compact,                              // Ask the kernel to compact physical memory (Linux)
+    Custom(Box<dyn Fn() -> bool + Send>), // Caller-supplied routine returning whether it succeeded
 }
 
 /// Perform memory defragmentation.
 /// 
-/// Note: This is a simulated function since true memory defragmentation
-/// is typically handled by the operating system or memory allocator.
-pub fn defragment_memory() -> bool {
-    // In a real implementation, this might:
-    // 1. Compact memory if the allocator supports it
-    // 2. Call OS-specific memory compaction functions
-    // 3. Perform application-specific optimizations
-    
-    // For now, we'll simulate the operation with a small delay
-    thread::sleep(Duration::from_millis(500));
-    
-    // Report success
+/// Asks the allocator to return unused pages to the OS: jemalloc arenas are
+/// purged when the `jemalloc` feature is enabled (only `arena` if given),
+/// otherwise glibc's `malloc_trim` is used. Free memory is measured before
+/// and after to report how much was reclaimed.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", ret))]
+pub fn defragment_memory(arena: Option<u32>) -> DefragResult {
+    #[cfg(feature = "jemalloc")]
+    {
+        if let Some(index) = arena {
+            return measure_defrag(|| alloc::jemalloc_stats::jemalloc_arena_purge_index(index).is_ok());
+        }
+        defragment_with_strategy(DefragStrategy::JemallocPurge)
+    }
+    
+    #[cfg(not(feature = "jemalloc"))]
+    {
+        // glibc has no per-arena trim; malloc_trim covers all of them
+        let _ = arena;
+        defragment_with_strategy(DefragStrategy::MallocTrim(0))
+    }
+}
+
+/// Run one defragmentation `strategy`, measuring free memory around it.
+/// 
+/// Strategies the platform cannot perform fail without touching memory.
+pub fn defragment_with_strategy(strategy: DefragStrategy) -> DefragResult {
+    match strategy {
+        DefragStrategy::JemallocPurge => measure_defrag(purge_jemalloc),
+        DefragStrategy::MallocTrim(pad) => measure_defrag(|| malloc_trim(pad)),
+        DefragStrategy::DropPageCache => measure_defrag(release_memory_cache),
+        DefragStrategy::Compact => measure_defrag(compact_memory),
+        DefragStrategy::Custom(routine) => measure_defrag(routine),
+    }
+}
+
+/// Progress of one `incremental_defragment` call.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DefragProgress {
+    pub bytes_reclaimed: i64, // Change in free memory during this call
+    pub work_remaining: bool, // Whether another call has steps left to run
+    pub steps_completed: u32, // Steps run during this call
+    pub duration_ms: u64,     // Wall time spent in this call
+}
+
+/// One unit of work in an incremental defragmentation pass.
+enum DefragStep {
+    #[cfg(feature = "jemalloc")]
+    PurgeArena(u32),
+    #[cfg(all(not(feature = "jemalloc"), target_os = "linux", target_env = "gnu"))]
+    MallocTrim,
+    #[cfg(target_os = "linux")]
+    CompactNode(u32),
+    #[cfg(target_os = "linux")]
+    CompactAll,
+}
+
+/// Steps of the pass in progress and the index of the next one to run.
+struct IncrementalDefrag {
+    steps: Vec<DefragStep>,
+    next: usize,
+}
+
+static INCREMENTAL_DEFRAG: Mutex<Option<IncrementalDefrag>> = Mutex::new(None);
+
+/// Defragment for at most about `budget_ms`, resuming where the last call stopped.
+/// 
+/// A pass is split into small steps: one jemalloc arena purge per arena (or
+/// a single `malloc_trim` on glibc), then kernel compaction one NUMA node at
+/// a time on Linux. Steps run until the budget is spent; a step that has
+/// started always finishes, and every call runs at least one step so a tiny
+/// budget still makes progress. Call again while `work_remaining` is set;
+/// the call after that starts a fresh pass.
+pub fn incremental_defragment(budget_ms: u64) -> DefragProgress {
+    let budget = Duration::from_millis(budget_ms);
+    let before = get_memory_stats();
+    let started = Instant::now();
+    
+    let mut state = INCREMENTAL_DEFRAG.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let pass = state.get_or_insert_with(|| IncrementalDefrag {
+        steps: incremental_defrag_steps(),
+        next: 0,
+    });
+    
+    let mut steps_completed = 0;
+    while pass.next < pass.steps.len() {
+        if steps_completed > 0 && started.elapsed() >= budget {
+            break;
+        }
+        run_defrag_step(&pass.steps[pass.next]);
+        pass.next += 1;
+        steps_completed += 1;
+    }
+    
+    let work_remaining = pass.next < pass.steps.len();
+    if !work_remaining {
+        *state = None;
+    }
+    drop(state);
+    
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let after = get_memory_stats();
+    
+    DefragProgress {
+        bytes_reclaimed: after.free as i64 - before.free as i64,
+        work_remaining,
+        steps_completed,
+        duration_ms,
+    }
+}
+
+/// Plan a fresh incremental pass for this platform.
+fn incremental_defrag_steps() -> Vec<DefragStep> {
+    #[allow(unused_mut)]
+    let mut steps = Vec::new();
+    
+    #[cfg(feature = "jemalloc")]
+    {
+        let arenas = alloc::jemalloc_stats::jemalloc_arena_count().unwrap_or(0);
+        steps.extend((0..arenas).map(DefragStep::PurgeArena));
+    }
+    
+    #[cfg(all(not(feature = "jemalloc"), target_os = "linux", target_env = "gnu"))]
+    steps.push(DefragStep::MallocTrim);
+    
+    #[cfg(target_os = "linux")]
+    {
+        let nodes = compactable_nodes();
+        if nodes.is_empty() {
+            steps.push(DefragStep::CompactAll);
+        } else {
+            steps.extend(nodes.into_iter().map(DefragStep::CompactNode));
+        }
+    }
+    
+    steps
+}
+
+/// Run a single step, ignoring failures so the rest of the pass still runs.
+fn run_defrag_step(step: &DefragStep) {
+    match *step {
+        #[cfg(feature = "jemalloc")]
+        DefragStep::PurgeArena(index) => {
+            // Uninitialized arenas fail to purge, which is fine
+            let _ = alloc::jemalloc_stats::jemalloc_arena_purge_index(index);
+        }
+        #[cfg(all(not(feature = "jemalloc"), target_os = "linux", target_env = "gnu"))]
+        DefragStep::MallocTrim => {
+            malloc_trim(0);
+        }
+        #[cfg(target_os = "linux")]
+        DefragStep::CompactNode(node) => {
+            let path = format!("/sys/devices/system/node/node{}/compact", node);
+            let _ = std::fs::write(path, b"1");
+        }
+        #[cfg(target_os = "linux")]
+        DefragStep::CompactAll => {
+            compact_memory();
+        }
+    }
+}
+
+/// NUMA nodes that accept per-node compaction requests.
+#[cfg(target_os = "linux")]
+fn compactable_nodes() -> Vec<u32> {
+    let entries = match std::fs::read_dir("/sys/devices/system/node") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    
+    let mut nodes: Vec<u32> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("compact").exists())
+        .filter_map(|entry| entry.file_name().to_str()?.strip_prefix("node")?.parse().ok())
+        .collect();
+    nodes.sort_unstable();
+    nodes
+}
+
+/// Move the objects in `regions` into one new mapping, packed in order.
+/// 
+/// Each `(ptr, len)` must be a whole anonymous mapping created with `mmap`,
+/// holding one live object of `len` bytes. Objects are copied to 16-byte
+/// aligned offsets of a fresh mapping, every pointer in `regions` is updated
+/// to the object's new address, and the original mappings are unmapped. The
+/// new mapping runs from the first non-empty region's new pointer to the end
+/// of the last one; free it with a single `munmap` over that range.
+/// 
+/// Zero-length entries hold no object and are left as they are, so when
+/// every `len` is zero nothing is mapped and the call returns true with
+/// `regions` unchanged.
+/// 
+/// Returns false, with nothing moved, if the new mapping cannot be created
+/// or the platform has no `mmap`.
+/// 
+/// # Safety
+/// 
+/// The caller must guarantee that nothing else references these objects:
+/// every other pointer into an original region, including copies of the
+/// pointers in `regions`, dangles once this returns true. Regions must not
+/// overlap, and objects must not contain pointers to themselves or to each
+/// other, since those are copied unchanged.
+pub unsafe fn mmap_compact(regions: &mut [(*mut u8, usize)]) -> bool {
+    #[cfg(unix)]
+    {
+        const OBJECT_ALIGN: usize = 16;
+        
+        let mut offsets = Vec::with_capacity(regions.len());
+        let mut total: usize = 0;
+        for &(_, len) in regions.iter() {
+            if len == 0 {
+                offsets.push(0);
+                continue;
+            }
+            let offset = match total.checked_add(OBJECT_ALIGN - 1) {
+                Some(end) => end & !(OBJECT_ALIGN - 1),
+                None => return false,
+            };
+            total = match offset.checked_add(len) {
+                Some(end) => end,
+                None => return false,
+            };
+            offsets.push(offset);
+        }
+        if total == 0 {
+            return true;
+        }
+        
+        let base = libc::mmap(
+            std::ptr::null_mut(),
+            total,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if base == libc::MAP_FAILED {
+            warn_event!("mmap of {} bytes for compaction failed: {}", total, std::io::Error::last_os_error());
+            return false;
+        }
+        let base = base as *mut u8;
+        
+        for (region, offset) in regions.iter_mut().zip(offsets) {
+            let (ptr, len) = *region;
+            if len == 0 {
+                continue;
+            }
+            let target = base.add(offset);
+            std::ptr::copy_nonoverlapping(ptr, target, len);
+            // The copy is already in place, so a failed unmap only leaks the original
+            if libc::munmap(ptr as *mut libc::c_void, len) != 0 {
+                warn_event!("munmap of compacted region failed: {}", std::io::Error::last_os_error());
+            }
+            *region = (target, len);
+        }
+        
+        true
+    }
+    
+    #[cfg(not(unix))]
+    {
+        let _ = regions;
+        false
+    }
+}
+
+/// Time `defrag` and report the change in free memory it caused.
+fn measure_defrag<F: FnOnce() -> bool>(defrag: F) -> DefragResult {
+    let before = get_memory_stats();
+    let started = Instant::now();
+    
+    let success = defrag();
+    
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let after = get_memory_stats();
+    
+    let bytes_reclaimed = after.free as i64 - before.free as i64;
+    debug_event!("defragmentation finished: success {}, {} bytes reclaimed in {} ms", success, bytes_reclaimed, duration_ms);
+    
+    DefragResult {
+        success,
+        bytes_reclaimed,
+        duration_ms,
+    }
+}
+
+/// Purge every jemalloc arena.
+#[cfg(feature = "jemalloc")]
+fn purge_jemalloc() -> bool {
+    match alloc::jemalloc_stats::jemalloc_arena_purge() {
+        Ok(()) => true,
+        Err(err) => {
+            warn_event!("jemalloc purge failed: {}", err);
+            false
+        }
+    }
+}
+
+/// Purge every jemalloc arena.
+#[cfg(not(feature = "jemalloc"))]
+fn purge_jemalloc() -> bool {
+    // jemalloc is not linked in
+    false
+}
+
+/// Trim the glibc heap, keeping `pad` bytes free at its top.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn malloc_trim(pad: i32) -> bool {
+    unsafe {
+        libc::malloc_trim(pad.max(0) as usize);
+    }
+    
+    // malloc_trim only reports whether memory was released, not errors
     true
 }
+
+/// Trim the glibc heap, keeping `pad` bytes free at its top.
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+fn malloc_trim(_pad: i32) -> bool {
+    // No portable way to ask the system allocator to compact
+    false
+}
+
+/// Ask the kernel to compact all zones.
+#[cfg(target_os = "linux")]
+fn compact_memory() -> bool {
+    match std::fs::write("/proc/sys/vm/compact_memory", b"1") {
+        Ok(()) => true,
+        Err(err) => {
+            warn_event!("failed to write /proc/sys/vm/compact_memory: {}", err);
+            false
+        }
+    }
+}
+
+/// Ask the kernel to compact all zones.
+#[cfg(not(target_os = "linux"))]
+fn compact_memory() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    
+    use super::*;
+    
+    fn stats(used_percent: f64, timestamp: &str) -> MemoryStats {
+        MemoryStats {
+            total: 1000,
+            free: 1000 - (used_percent * 10.0) as u64,
+            available: 1000 - (used_percent * 10.0) as u64,
+            used: (used_percent * 10.0) as u64,
+            used_percent,
+            buffers: None,
+            cached: None,
+            speculative: None,
+            fragmentation_score: None,
+            swap_total: None,
+            swap_free: None,
+            dirty: None,
+            writeback: None,
+            timestamp: timestamp.to_string(),
+        }
+    }
+    
+    #[test]
+    fn partial_eq_compares_every_field() {
+        let a = stats(50.0, "2024-01-01T00:00:00.000Z");
+        assert_eq!(a, a.clone());
+        
+        let mut b = a.clone();
+        b.free += 1;
+        assert_ne!(a, b);
+        
+        let mut c = a.clone();
+        c.cached = Some(0);
+        assert_ne!(a, c);
+    }
+    
+    #[test]
+    fn eq_treats_nan_as_equal_to_itself() {
+        let a = stats(f64::NAN, "2024-01-01T00:00:00.000Z");
+        assert_eq!(a, a.clone());
+        assert_ne!(a, stats(50.0, "2024-01-01T00:00:00.000Z"));
+    }
+    
+    #[test]
+    fn partial_ord_orders_by_used_percent_then_time() {
+        let low = stats(40.0, "2024-01-01T00:00:09.000Z");
+        let early = stats(60.0, "2024-01-01T00:00:00.000Z");
+        let late = stats(60.0, "2024-01-01T00:00:05.000Z");
+        
+        assert!(low < early);
+        assert!(early < late);
+        assert_eq!(late.partial_cmp(&late.clone()), Some(Ordering::Equal));
+    }
+    
+    #[test]
+    fn ord_sorts_and_handles_nan() {
+        let mut snapshots = [
+            stats(70.0, "b"),
+            stats(f64::NAN, "a"),
+            stats(10.0, "c"),
+            stats(70.0, "a"),
+        ];
+        snapshots.sort();
+        
+        let order: Vec<(f64, &str)> = snapshots.iter().map(|s| (s.used_percent, s.timestamp.as_str())).collect();
+        assert_eq!(&order[..3], &[(10.0, "c"), (70.0, "a"), (70.0, "b")]);
+        assert!(order[3].0.is_nan());
+        assert!(snapshots.iter().max().unwrap().used_percent.is_nan());
+    }
+    
+    #[test]
+    fn ord_breaks_remaining_ties_on_other_fields() {
+        let a = stats(50.0, "2024-01-01T00:00:00.000Z");
+        let mut b = a.clone();
+        b.free += 1;
+        
+        assert_ne!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+    }
+    
+    #[test]
+    fn display_with_zero_total() {
+        let mut empty = stats(0.0, "2024-01-01T00:00:00.000Z");
+        empty.total = 0;
+        empty.free = 0;
+        empty.available = 0;
+        empty.used = 0;
+        
+        assert_eq!(
+            empty.to_string(),
+            "Memory: 0 B used / 0 B total (0.0%) | free=0 B available=0 B @ 2024-01-01T00:00:00.000Z"
+        );
+    }
+    
+    #[test]
+    fn display_at_full_usage_with_buffers_and_cached() {
+        let full = MemoryStats {
+            total: 16 << 30,
+            free: 0,
+            available: 0,
+            used: 16 << 30,
+            used_percent: 100.0,
+            buffers: Some(512 << 20),
+            cached: Some(1536),
+            ..stats(100.0, "2024-01-01T00:00:00.000Z")
+        };
+        
+        assert_eq!(
+            full.to_string(),
+            "Memory: 16.0 GiB used / 16.0 GiB total (100.0%) | free=0 B available=0 B \
+             buffers=512.0 MiB cached=1.5 KiB @ 2024-01-01T00:00:00.000Z"
+        );
+    }
+    
+    #[test]
+    fn display_omits_missing_buffers() {
+        let mut partial = stats(51.3, "t");
+        partial.total = 16 << 30;
+        partial.used = 8 << 30;
+        partial.free = 6 << 30;
+        partial.available = 7 << 30;
+        partial.cached = Some(1 << 30);
+        
+        assert_eq!(
+            partial.to_string(),
+            "Memory: 8.0 GiB used / 16.0 GiB total (51.3%) | free=6.0 GiB available=7.0 GiB cached=1.0 GiB @ t"
+        );
+    }
+    
+    #[test]
+    fn measure_fragmentation_holds_blocks_around_holes() {
+        let patterns = [
+            FragmentationPattern::EveryThird,
+            FragmentationPattern::Random(0.5),
+            FragmentationPattern::Checkerboard,
+            FragmentationPattern::LargeSmallAlternating,
+        ];
+        
+        for pattern in patterns {
+            let sample = measure_fragmentation(12, 128, pattern).unwrap();
+            assert_eq!(sample.pattern, pattern);
+            assert_eq!(sample.blocks_held + sample.holes, 12);
+            assert!((0.0..=1.0).contains(&sample.score_before));
+            assert!((0.0..=1.0).contains(&sample.score_with_holes));
+            
+            match pattern {
+                FragmentationPattern::EveryThird => assert_eq!(sample.holes, 4),
+                FragmentationPattern::Checkerboard | FragmentationPattern::LargeSmallAlternating => {
+                    assert_eq!(sample.holes, 6)
+                }
+                FragmentationPattern::Random(_) => {}
+            }
+        }
+        
+        assert!(measure_fragmentation(0, 128, FragmentationPattern::Checkerboard).is_none());
+        assert!(!simulate_memory_fragmentation(4, 0, FragmentationPattern::Checkerboard));
+    }
+    
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const MEMINFO: &str = "MemTotal:       16384000 kB
+MemFree:         4096000 kB
+MemAvailable:    8192000 kB
+Buffers:          512000 kB
+Cached:          2048000 kB
+SwapCached:            0 kB
+Dirty:              1536 kB
+Writeback:            64 kB
+HugePages_Total:       0
+";
+    
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn meminfo_dirty_and_writeback() {
+        let stats = memory_stats_from_meminfo(&parse_meminfo(MEMINFO.as_bytes()));
+        
+        assert_eq!(stats.total, 16384000 * 1024);
+        assert_eq!(stats.used, (16384000 - 4096000 - 512000 - 2048000) * 1024);
+        assert_eq!(stats.dirty, Some(1536 * 1024));
+        assert_eq!(stats.writeback, Some(64 * 1024));
+        
+        let without = memory_stats_from_meminfo(&parse_meminfo(b"MemTotal: 1024 kB\nMemFree: 512 kB\n"));
+        assert_eq!(without.dirty, None);
+        assert_eq!(without.writeback, None);
+    }
+    
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_omits_missing_dirty_and_writeback() {
+        let mut snapshot = stats(50.0, "t");
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert!(json.get("dirty").is_none());
+        assert!(json.get("writeback").is_none());
+        
+        snapshot.dirty = Some(4096);
+        snapshot.writeback = Some(0);
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["dirty"], 4096);
+        assert_eq!(json["writeback"], 0);
+        
+        let decoded: MemoryStats = serde_json::from_str(r#"{"total":1,"free":1,"available":1,"used":0,"used_percent":0.0,"buffers":null,"cached":null,"timestamp":"t"}"#).unwrap();
+        assert_eq!(decoded.dirty, None);
+        assert_eq!(decoded.writeback, None);
+    }
+    
+    #[test]
+    fn health_score_leaves_out_missing_inputs() {
+        let weights = HealthScoreWeights::default();
+        let snapshot = stats(60.0, "");
+        assert!((snapshot.health_score_with(&weights, None) - 60.0).abs() < 1e-9);
+        
+        let fragmented = MemoryStats {
+            fragmentation_score: Some(0.2),
+            ..snapshot.clone()
+        };
+        // (60 * 0.5 + 20 * 0.15) / 0.65
+        assert!((fragmented.health_score_with(&weights, None) - 33.0 / 0.65).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn health_score_uses_given_pressure() {
+        let snapshot = stats(60.0, "");
+        let mut pressure = MemoryPressure::default();
+        pressure.some.avg10 = 40.0;
+        
+        let score = snapshot.health_score_with(&HealthScoreWeights::default(), Some(&pressure));
+        // (60 * 0.5 + 40 * 0.25) / 0.75
+        assert!((score - 40.0 / 0.75).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn health_score_samples_what_the_snapshot_lacks() {
+        let idle = stats(0.0, "");
+        let score = idle.health_score();
+        assert!((0.0..=100.0).contains(&score));
+        
+        // With a stored fragmentation score, only PSI can add to the score
+        let fragmented = MemoryStats {
+            fragmentation_score: Some(0.2),
+            ..idle
+        };
+        if !std::path::Path::new("/proc/pressure/memory").exists() {
+            let expected = fragmented.health_score_with(&HealthScoreWeights::default(), None);
+            assert!((fragmented.health_score() - expected).abs() < 1e-9);
+        } else {
+            assert!(fragmented.health_score() > 0.0);
+        }
+    }
+}
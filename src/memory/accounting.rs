@@ -0,0 +1,198 @@
+//! Process-wide and per-thread allocation accounting.
+//!
+//! Install `AccountingAllocator` as the global allocator, then read
+//! `ALLOC.stats()` for the whole process or call `thread_usage()` on any
+//! thread to see how much it has allocated.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+thread_local! {
+    // Const-initialised so the allocator can touch it without allocating
+    static THREAD_ALLOC_BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Count `bytes` as allocated by the calling thread.
+pub fn track_alloc(bytes: usize) {
+    // Fails only while the thread is being torn down; nothing left to report
+    let _ = THREAD_ALLOC_BYTES.try_with(|counter| counter.set(counter.get().saturating_add(bytes as u64)));
+}
+
+/// Count `bytes` as freed by the calling thread.
+pub fn track_dealloc(bytes: usize) {
+    let _ = THREAD_ALLOC_BYTES.try_with(|counter| counter.set(counter.get().saturating_sub(bytes as u64)));
+}
+
+/// Net bytes allocated by the calling thread since it started.
+///
+/// Memory freed on a different thread than the one that allocated it can
+/// bring a thread's count down early; the count never goes below zero.
+pub fn thread_usage() -> u64 {
+    THREAD_ALLOC_BYTES.try_with(Cell::get).unwrap_or(0)
+}
+
+/// Counters kept by an `AccountingAllocator`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AllocatorStats {
+    pub total_allocated: u64,   // Bytes handed out since start
+    pub total_deallocated: u64, // Bytes returned since start
+    pub current_usage: i64,     // Bytes currently live
+}
+
+/// Allocator that forwards to `inner` and counts every allocation, both in
+/// process-wide totals and through `track_alloc` and `track_dealloc`.
+///
+/// Install it with `#[global_allocator]`, wrapping `std::alloc::System` or
+/// any other allocator. A realloc counts as freeing the old block and
+/// allocating the new one.
+pub struct AccountingAllocator<A: GlobalAlloc = System> {
+    inner: A,
+    total_allocated: AtomicU64,
+    total_deallocated: AtomicU64,
+    current_usage: AtomicI64,
+}
+
+impl<A: GlobalAlloc> AccountingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        AccountingAllocator {
+            inner,
+            total_allocated: AtomicU64::new(0),
+            total_deallocated: AtomicU64::new(0),
+            current_usage: AtomicI64::new(0),
+        }
+    }
+
+    /// Current counters.
+    ///
+    /// Each counter is read separately, so under concurrent allocation they
+    /// can be a few operations apart from each other.
+    pub fn stats(&self) -> AllocatorStats {
+        AllocatorStats {
+            total_allocated: self.total_allocated.load(Ordering::Relaxed),
+            total_deallocated: self.total_deallocated.load(Ordering::Relaxed),
+            current_usage: self.current_usage.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_alloc(&self, bytes: usize) {
+        self.total_allocated.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.current_usage.fetch_add(bytes as i64, Ordering::Relaxed);
+        track_alloc(bytes);
+    }
+
+    fn record_dealloc(&self, bytes: usize) {
+        self.total_deallocated.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.current_usage.fetch_sub(bytes as i64, Ordering::Relaxed);
+        track_dealloc(bytes);
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for AccountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            self.record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.record_dealloc(layout.size());
+            self.record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const THREADS: usize = 8;
+    const BLOCKS_PER_THREAD: usize = 100;
+
+    fn block_layout(thread_index: usize) -> Layout {
+        Layout::from_size_align((thread_index + 1) * 64, 8).unwrap()
+    }
+
+    #[test]
+    fn counts_concurrent_allocations() {
+        let allocator = AccountingAllocator::new(System);
+
+        thread::scope(|scope| {
+            for thread_index in 0..THREADS {
+                let allocator = &allocator;
+                scope.spawn(move || {
+                    let layout = block_layout(thread_index);
+                    let blocks: Vec<usize> = (0..BLOCKS_PER_THREAD)
+                        .map(|_| unsafe { allocator.alloc(layout) } as usize)
+                        .collect();
+                    assert!(blocks.iter().all(|&ptr| ptr != 0));
+                    assert_eq!(thread_usage(), (BLOCKS_PER_THREAD * layout.size()) as u64);
+
+                    for ptr in blocks {
+                        unsafe { allocator.dealloc(ptr as *mut u8, layout) };
+                    }
+                    assert_eq!(thread_usage(), 0);
+                });
+            }
+        });
+
+        let expected: u64 = (0..THREADS)
+            .map(|thread_index| (BLOCKS_PER_THREAD * block_layout(thread_index).size()) as u64)
+            .sum();
+        assert_eq!(
+            allocator.stats(),
+            AllocatorStats {
+                total_allocated: expected,
+                total_deallocated: expected,
+                current_usage: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn live_blocks_and_realloc() {
+        let allocator = AccountingAllocator::new(System);
+        let layout = Layout::from_size_align(100, 8).unwrap();
+
+        unsafe {
+            let zeroed = allocator.alloc_zeroed(layout);
+            let grown = allocator.realloc(allocator.alloc(layout), layout, 300);
+            assert!(!zeroed.is_null() && !grown.is_null());
+
+            assert_eq!(
+                allocator.stats(),
+                AllocatorStats {
+                    total_allocated: 500,
+                    total_deallocated: 100,
+                    current_usage: 400,
+                }
+            );
+
+            allocator.dealloc(zeroed, layout);
+            allocator.dealloc(grown, Layout::from_size_align(300, 8).unwrap());
+        }
+
+        assert_eq!(allocator.stats().current_usage, 0);
+        assert_eq!(allocator.stats().total_deallocated, 500);
+    }
+}
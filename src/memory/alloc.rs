@@ -0,0 +1,4 @@
+//! Allocator-specific statistics and maintenance hooks.
+
+#[cfg(feature = "jemalloc")]
+pub mod jemalloc_stats;
@@ -0,0 +1,148 @@
+//! jemalloc statistics via `mallctl`.
+//!
+//! Only meaningful when the process actually runs on jemalloc, e.g. through
+//! `tikv-jemallocator` or `jemallocator` as the global allocator.
+
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::{c_uint, c_void};
+use std::ptr;
+
+use jemalloc_sys::mallctl;
+
+use super::super::MemoryError;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JemallocStats {
+    pub allocated: u64,          // Bytes allocated by the application
+    pub active: u64,             // Bytes in active pages
+    pub metadata: u64,           // Bytes dedicated to allocator metadata
+    pub resident: u64,           // Bytes in physically resident data pages
+    pub mapped: u64,             // Bytes in active extents mapped by the allocator
+    pub retained: u64,           // Bytes retained rather than returned to the OS
+    pub fragmentation_ratio: f64, // resident / allocated, 1.0 means no overhead
+}
+
+/// Get jemalloc statistics.
+///
+/// Returns `None` if the statistics could not be read, for example when
+/// jemalloc was built without stats support.
+pub fn get_jemalloc_stats() -> Option<JemallocStats> {
+    // Statistics are cached by jemalloc until the epoch is advanced
+    if !advance_epoch() {
+        return None;
+    }
+
+    let allocated = read_size("stats.allocated")?;
+    let active = read_size("stats.active")?;
+    let metadata = read_size("stats.metadata")?;
+    let resident = read_size("stats.resident")?;
+    let mapped = read_size("stats.mapped")?;
+    let retained = read_size("stats.retained")?;
+
+    let fragmentation_ratio = if allocated > 0 {
+        resident as f64 / allocated as f64
+    } else {
+        0.0
+    };
+
+    Some(JemallocStats {
+        allocated,
+        active,
+        metadata,
+        resident,
+        mapped,
+        retained,
+        fragmentation_ratio,
+    })
+}
+
+/// Purge unused dirty pages from every jemalloc arena back to the OS.
+pub fn jemalloc_arena_purge() -> Result<(), MemoryError> {
+    for i in 0..jemalloc_arena_count()? {
+        match jemalloc_arena_purge_index(i) {
+            // Uninitialized arenas report EFAULT; skip them
+            Err(MemoryError::Io(ref err)) if err.raw_os_error() == Some(libc::EFAULT) => {}
+            result => result?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of arenas, including ones that are not initialized yet.
+pub fn jemalloc_arena_count() -> Result<c_uint, MemoryError> {
+    let mut narenas: c_uint = 0;
+    let mut len = mem::size_of::<c_uint>();
+    let name = CString::new("arenas.narenas").unwrap();
+
+    let ret = unsafe {
+        mallctl(
+            name.as_ptr(),
+            &mut narenas as *mut c_uint as *mut c_void,
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 {
+        return Err(MemoryError::Io(std::io::Error::from_raw_os_error(ret)));
+    }
+
+    Ok(narenas)
+}
+
+/// Purge unused dirty pages from a single jemalloc arena.
+pub fn jemalloc_arena_purge_index(index: c_uint) -> Result<(), MemoryError> {
+    let name = CString::new(format!("arena.{}.purge", index)).unwrap();
+    let ret = unsafe { mallctl(name.as_ptr(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), 0) };
+
+    if ret != 0 {
+        return Err(MemoryError::Io(std::io::Error::from_raw_os_error(ret)));
+    }
+
+    Ok(())
+}
+
+/// Advance the stats epoch so that subsequent reads are fresh.
+fn advance_epoch() -> bool {
+    let mut epoch: u64 = 1;
+    let mut len = mem::size_of::<u64>();
+    let name = CString::new("epoch").unwrap();
+
+    let ret = unsafe {
+        mallctl(
+            name.as_ptr(),
+            &mut epoch as *mut u64 as *mut c_void,
+            &mut len,
+            &mut epoch as *mut u64 as *mut c_void,
+            mem::size_of::<u64>(),
+        )
+    };
+
+    ret == 0
+}
+
+/// Read a `size_t` valued mallctl.
+fn read_size(name: &str) -> Option<u64> {
+    let name = CString::new(name).ok()?;
+    let mut value: usize = 0;
+    let mut len = mem::size_of::<usize>();
+
+    let ret = unsafe {
+        mallctl(
+            name.as_ptr(),
+            &mut value as *mut usize as *mut c_void,
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+
+    if ret == 0 {
+        Some(value as u64)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,89 @@
+//! Non-blocking variants of the stat collection and cache release calls.
+//!
+//! Only compiled with the `tokio` feature; the synchronous functions in the
+//! parent module are unaffected.
+
+use super::MemoryStats;
+
+/// Get current memory statistics without blocking the calling thread.
+pub async fn async_get_memory_stats() -> MemoryStats {
+    #[cfg(target_os = "linux")]
+    return async_get_memory_stats_linux().await;
+
+    #[cfg(target_os = "macos")]
+    return async_get_memory_stats_macos().await;
+
+    // GlobalMemoryStatusEx is a plain syscall; just keep it off the reactor
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    return tokio::task::spawn_blocking(super::get_memory_stats)
+        .await
+        .unwrap_or_else(|_| super::get_memory_stats());
+}
+
+/// Release memory cache without blocking the calling thread.
+pub async fn async_release_memory_cache() -> bool {
+    #[cfg(target_os = "linux")]
+    return async_release_memory_cache_linux().await;
+
+    #[cfg(target_os = "macos")]
+    return tokio::process::Command::new("purge").status().await.is_ok();
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    return tokio::task::spawn_blocking(super::release_memory_cache)
+        .await
+        .unwrap_or(false);
+}
+
+#[cfg(target_os = "linux")]
+async fn async_get_memory_stats_linux() -> MemoryStats {
+    use std::collections::HashMap;
+    use tokio::fs::File;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let mut mem_info = HashMap::new();
+
+    if let Ok(file) = File::open("/proc/meminfo").await {
+        let mut lines = BufReader::new(file).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            super::parse_meminfo_line(&line, &mut mem_info);
+        }
+    }
+
+    super::memory_stats_from_meminfo(&mem_info)
+}
+
+#[cfg(target_os = "linux")]
+async fn async_release_memory_cache_linux() -> bool {
+    use tokio::process::Command;
+
+    // First, sync to disk to ensure data is safe
+    let sync_result = Command::new("sync").status().await;
+
+    // Try to drop caches
+    let drop_caches_result = tokio::fs::write("/proc/sys/vm/drop_caches", b"3").await.is_ok();
+
+    sync_result.is_ok() || drop_caches_result
+}
+
+#[cfg(target_os = "macos")]
+async fn async_get_memory_stats_macos() -> MemoryStats {
+    use tokio::process::Command;
+
+    let memsize = Command::new("sysctl")
+        .args(["-n", "hw.memsize"])
+        .output()
+        .await
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+
+    let vm_stat = Command::new("vm_stat")
+        .output()
+        .await
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+
+    super::memory_stats_from_macos(&memsize, &vm_stat)
+}
@@ -0,0 +1,36 @@
+//! The `no_std` subset of the memory module: allocation helpers that need
+//! nothing but a global allocator, and `MemoryStats` with its string
+//! encodings.
+//!
+//! With the `serde` feature (and serde's `alloc` feature) `MemoryStats` and
+//! the pool types keep their `Serialize`/`Deserialize` derives. `format`
+//! holds the StatsD, Prometheus, InfluxDB and CSV renderers; the bincode,
+//! MessagePack and YAML encoders are not available here, since those crates
+//! need std.
+
+#[macro_use]
+#[path = "instrument.rs"]
+mod instrument;
+
+#[path = "memory_stats.rs"]
+mod memory_stats;
+#[path = "text.rs"]
+pub mod format;
+
+#[path = "pool.rs"]
+pub mod pool;
+#[path = "guard.rs"]
+pub mod guard;
+#[path = "secure.rs"]
+pub mod secure;
+
+pub use self::memory_stats::MemoryStats;
+
+#[cfg(feature = "prometheus")]
+pub use self::format::format_prometheus;
+
+#[cfg(feature = "influx")]
+pub use self::format::format_influx;
+
+#[cfg(feature = "csv")]
+pub use self::format::{csv_header, to_csv_row};
@@ -0,0 +1,188 @@
+//! Memory quotas for subsystems such as caches and connection pools.
+//!
+//! Budgets only count what callers report through `allocate`; they do not
+//! hook the allocator.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Why `MemoryBudget::allocate` refused a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetError {
+    /// The request would take usage past the limit.
+    ExceededQuota { requested: u64, available: u64 },
+}
+
+impl fmt::Display for BudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BudgetError::ExceededQuota { requested, available } => {
+                write!(f, "memory budget exceeded: requested {} bytes, {} available", requested, available)
+            }
+        }
+    }
+}
+
+impl Error for BudgetError {}
+
+/// Byte quota shared by everything that allocates on behalf of one subsystem.
+#[derive(Debug)]
+pub struct MemoryBudget {
+    limit: u64,
+    used: AtomicU64,
+}
+
+/// Bytes charged to a `MemoryBudget`, given back when dropped.
+#[derive(Debug)]
+pub struct BudgetGuard<'a> {
+    budget: &'a MemoryBudget,
+    bytes: u64,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: u64) -> MemoryBudget {
+        MemoryBudget {
+            limit,
+            used: AtomicU64::new(0),
+        }
+    }
+
+    /// Charge `bytes` to the budget until the returned guard is dropped.
+    ///
+    /// Fails without waiting if `used + bytes` would exceed the limit.
+    pub fn allocate(&self, bytes: u64) -> Result<BudgetGuard<'_>, BudgetError> {
+        let mut used = self.used.load(Ordering::Relaxed);
+        loop {
+            let new_used = match used.checked_add(bytes) {
+                Some(new_used) if new_used <= self.limit => new_used,
+                _ => {
+                    return Err(BudgetError::ExceededQuota {
+                        requested: bytes,
+                        available: self.limit.saturating_sub(used),
+                    })
+                }
+            };
+
+            // Retry if another thread changed usage since it was read
+            match self.used.compare_exchange_weak(used, new_used, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => return Ok(BudgetGuard { budget: self, bytes }),
+                Err(current) => used = current,
+            }
+        }
+    }
+
+    /// Fraction of the limit in use, from 0.0 to 1.0; a zero limit counts as full.
+    pub fn utilization(&self) -> f64 {
+        if self.limit == 0 {
+            return 1.0;
+        }
+        self.used() as f64 / self.limit as f64
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub fn used(&self) -> u64 {
+        self.used.load(Ordering::Acquire)
+    }
+
+    pub fn available(&self) -> u64 {
+        self.limit.saturating_sub(self.used())
+    }
+}
+
+impl BudgetGuard<'_> {
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+impl Drop for BudgetGuard<'_> {
+    fn drop(&mut self) {
+        self.budget.used.fetch_sub(self.bytes, Ordering::AcqRel);
+    }
+}
+
+/// Usage of one registered budget.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BudgetUsage {
+    pub name: String,     // Name given to `BudgetRegistry::register`
+    pub limit: u64,       // Quota in bytes
+    pub used: u64,        // Bytes currently charged
+    pub utilization: f64, // used / limit
+}
+
+/// Named budgets, for reporting usage across subsystems.
+#[derive(Debug, Default)]
+pub struct BudgetRegistry {
+    budgets: Mutex<Vec<(String, Arc<MemoryBudget>)>>,
+}
+
+impl BudgetRegistry {
+    pub fn new() -> BudgetRegistry {
+        BudgetRegistry::default()
+    }
+
+    /// Create a budget of `limit` bytes under `name`.
+    ///
+    /// Registering a name again replaces the earlier budget in reports;
+    /// holders of the earlier budget can keep using it.
+    pub fn register(&self, name: &str, limit: u64) -> Arc<MemoryBudget> {
+        let budget = Arc::new(MemoryBudget::new(limit));
+        self.insert(name, Arc::clone(&budget));
+        budget
+    }
+
+    /// Report an existing budget under `name`.
+    pub fn insert(&self, name: &str, budget: Arc<MemoryBudget>) {
+        let mut budgets = self.lock();
+        budgets.retain(|(existing, _)| existing != name);
+        budgets.push((name.to_string(), budget));
+    }
+
+    /// Stop reporting `name`; returns whether it was registered.
+    pub fn unregister(&self, name: &str) -> bool {
+        let mut budgets = self.lock();
+        let before = budgets.len();
+        budgets.retain(|(existing, _)| existing != name);
+        budgets.len() != before
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<MemoryBudget>> {
+        self.lock()
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, budget)| Arc::clone(budget))
+    }
+
+    /// Usage of every budget, in registration order.
+    pub fn usage(&self) -> Vec<BudgetUsage> {
+        self.lock()
+            .iter()
+            .map(|(name, budget)| BudgetUsage {
+                name: name.clone(),
+                limit: budget.limit(),
+                used: budget.used(),
+                utilization: budget.utilization(),
+            })
+            .collect()
+    }
+
+    /// Bytes charged across all budgets.
+    pub fn total_used(&self) -> u64 {
+        self.lock().iter().map(|(_, budget)| budget.used()).sum()
+    }
+
+    /// Sum of all limits.
+    pub fn total_limit(&self) -> u64 {
+        self.lock().iter().map(|(_, budget)| budget.limit()).sum()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(String, Arc<MemoryBudget>)>> {
+        self.budgets.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,80 @@
+//! Periodically refreshed `MemoryStats` for callers polling at high rates.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+
+use super::{get_memory_stats, MemoryStats};
+
+/// Shared between the cache and its refresh thread.
+struct CacheState {
+    stats: ArcSwap<MemoryStats>,
+    created: Instant,
+    fetched_ms: AtomicU64, // Milliseconds after `created` of the last refresh
+}
+
+/// `MemoryStats` refreshed every `ttl` on a background thread.
+///
+/// Readers never touch /proc or take a lock; they get whatever the refresh
+/// thread stored last.
+pub struct CachedMemoryStats {
+    state: Arc<CacheState>,
+    ttl: Duration,
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl CachedMemoryStats {
+    /// Take a first sample and start refreshing it every `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        let state = Arc::new(CacheState {
+            stats: ArcSwap::from_pointee(get_memory_stats()),
+            created: Instant::now(),
+            fetched_ms: AtomicU64::new(0),
+        });
+        let (stop, stop_receiver) = mpsc::channel::<()>();
+
+        let shared = Arc::clone(&state);
+        let handle = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(ttl) {
+                shared.stats.store(Arc::new(get_memory_stats()));
+                shared.fetched_ms.store(shared.created.elapsed().as_millis() as u64, Ordering::Release);
+            }
+        });
+
+        CachedMemoryStats {
+            state,
+            ttl,
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    /// Cached snapshot, at most about one `ttl` old.
+    pub fn get(&self) -> Arc<MemoryStats> {
+        self.state.stats.load_full()
+    }
+
+    /// Time since the cached snapshot was taken.
+    pub fn age(&self) -> Duration {
+        let fetched = Duration::from_millis(self.state.fetched_ms.load(Ordering::Acquire));
+        self.state.created.elapsed().saturating_sub(fetched)
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+}
+
+impl Drop for CachedMemoryStats {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
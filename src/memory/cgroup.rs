@@ -0,0 +1,113 @@
+//! cgroup v2 memory controller limits and usage.
+
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+
+/// Memory limits and usage of the cgroup the current process runs in.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CgroupMemoryStats {
+    pub limit: Option<u64>,      // memory.max in bytes, None when "max"
+    pub current: u64,            // memory.current in bytes
+    pub swap_limit: Option<u64>, // memory.swap.max in bytes, None when "max"
+    pub swap_current: u64,       // memory.swap.current in bytes
+    pub events_oom: u64,         // Times the limit was hit and reclaim failed
+    pub events_oom_kill: u64,    // Processes killed by the OOM killer
+}
+
+/// Get memory stats of the current cgroup.
+///
+/// Returns `None` outside a cgroup v2 hierarchy, in the root cgroup (which
+/// has no memory.current) and on non-Linux platforms.
+pub fn get_cgroup_memory_stats() -> Option<CgroupMemoryStats> {
+    #[cfg(target_os = "linux")]
+    return get_cgroup_memory_stats_linux();
+
+    #[cfg(not(target_os = "linux"))]
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_cgroup_memory_stats_linux() -> Option<CgroupMemoryStats> {
+    let dir = cgroup_dir()?;
+    let current = read_counter(&dir.join("memory.current"))?;
+
+    let (events_oom, events_oom_kill) = fs::read_to_string(dir.join("memory.events"))
+        .map(|contents| parse_memory_events(&contents))
+        .unwrap_or((0, 0));
+
+    Some(CgroupMemoryStats {
+        limit: read_limit(&dir.join("memory.max")),
+        current,
+        swap_limit: read_limit(&dir.join("memory.swap.max")),
+        swap_current: read_counter(&dir.join("memory.swap.current")).unwrap_or(0),
+        events_oom,
+        events_oom_kill,
+    })
+}
+
+/// Locate the cgroup v2 directory of the current process.
+#[cfg(target_os = "linux")]
+fn cgroup_dir() -> Option<PathBuf> {
+    let root = Path::new("/sys/fs/cgroup");
+    if !root.join("cgroup.controllers").exists() {
+        return None;
+    }
+
+    // The unified hierarchy entry is "0::/path"; inside a cgroup namespace
+    // it is "0::/" and the container's own files sit at the mount root
+    let relative = fs::read_to_string("/proc/self/cgroup")
+        .ok()
+        .and_then(|contents| {
+            contents
+                .lines()
+                .find_map(|line| line.strip_prefix("0::").map(|path| path.trim().to_string()))
+        })
+        .unwrap_or_default();
+
+    let dir = root.join(relative.trim_start_matches('/'));
+    if dir.join("memory.current").exists() {
+        Some(dir)
+    } else {
+        Some(root.to_path_buf())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_counter(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()
+}
+
+/// Read a limit file, which holds either a byte count or "max".
+#[cfg(target_os = "linux")]
+fn read_limit(path: &Path) -> Option<u64> {
+    match fs::read_to_string(path).ok()?.trim() {
+        "max" => None,
+        value => value.parse::<u64>().ok(),
+    }
+}
+
+/// Parse the "oom" and "oom_kill" counters out of memory.events.
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_memory_events(contents: &str) -> (u64, u64) {
+    let mut oom = 0;
+    let mut oom_kill = 0;
+
+    for line in contents.lines() {
+        let mut parts = line.split_whitespace();
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key, value.parse::<u64>().unwrap_or(0)),
+            _ => continue,
+        };
+
+        match key {
+            "oom" => oom = value,
+            "oom_kill" => oom_kill = value,
+            _ => {}
+        }
+    }
+
+    (oom, oom_kill)
+}
@@ -0,0 +1,85 @@
+//! Post-mortem dumps of process memory state.
+//!
+//! These helpers are meant to be called when OOM is imminent so that
+//! operators have something to look at after the process is gone.
+
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use super::MemoryError;
+
+/// Write the parsed memory maps of `pid` to `path` as newline-delimited JSON.
+///
+/// Returns the number of bytes written.
+#[cfg(all(target_os = "linux", feature = "serde"))]
+pub fn dump_memory_maps_to_file(pid: u32, path: &Path) -> Result<u64, MemoryError> {
+    use std::fs::File;
+    use std::io::{BufWriter, Write};
+
+    let entries = super::linux::maps::get_memory_maps(pid)?;
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    let mut written: u64 = 0;
+
+    for entry in &entries {
+        let line = serde_json::to_string(entry)?;
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+        written += line.len() as u64 + 1;
+    }
+
+    writer.flush()?;
+    Ok(written)
+}
+
+/// Copy `/proc/<pid>/smaps` verbatim to `path`.
+///
+/// Returns the number of bytes copied.
+#[cfg(target_os = "linux")]
+pub fn dump_smaps_to_file(pid: u32, path: &Path) -> Result<u64, MemoryError> {
+    use std::fs::File;
+    use std::io;
+
+    let mut source = File::open(format!("/proc/{}/smaps", pid))?;
+    let mut dest = File::create(path)?;
+
+    let copied = io::copy(&mut source, &mut dest)?;
+    dest.sync_all()?;
+
+    Ok(copied)
+}
+
+/// Keep only the `keep` most recently modified files in `dir`.
+///
+/// `dir` should be dedicated to dump files: every regular file in it is
+/// considered for removal. Returns the number of files removed.
+pub fn rotate_dump_files(dir: &Path, keep: usize) -> Result<usize, MemoryError> {
+    let mut files: Vec<(SystemTime, std::path::PathBuf)> = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        files.push((modified, entry.path()));
+    }
+
+    if files.len() <= keep {
+        return Ok(0);
+    }
+
+    // Newest first, so everything past `keep` is the oldest
+    files.sort_by_key(|file| std::cmp::Reverse(file.0));
+
+    let mut removed = 0;
+    for (_, path) in files.into_iter().skip(keep) {
+        fs::remove_file(&path)?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
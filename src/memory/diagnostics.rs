@@ -0,0 +1,273 @@
+//! Heuristic "doctor" checks that turn raw memory numbers into advice.
+
+use std::collections::HashMap;
+
+use super::util::format_bytes;
+use super::{get_memory_stats, MemoryStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Confidence {
+    Low,    // Only portable statistics were available
+    Medium, // Some kernel detail was missing
+    High,   // Full kernel memory accounting was available
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Finding {
+    pub severity: Severity,
+    pub description: String,
+    pub evidence: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Recommendation {
+    pub action: String, // What to do
+    pub reason: String, // Which finding motivates it
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiagnosisResult {
+    pub findings: Vec<Finding>,
+    pub recommendations: Vec<Recommendation>,
+    pub confidence: Confidence,
+}
+
+/// Snapshot of everything the diagnostic checks look at.
+#[derive(Debug)]
+pub struct MemoryDiagnosticReport {
+    pub stats: MemoryStats,
+    pub meminfo: HashMap<String, u64>, // Raw /proc/meminfo on Linux, empty elsewhere
+}
+
+impl MemoryDiagnosticReport {
+    /// Collect a fresh report from the running system.
+    pub fn collect() -> Self {
+        #[cfg(target_os = "linux")]
+        let meminfo = super::read_meminfo();
+
+        #[cfg(not(target_os = "linux"))]
+        let meminfo = HashMap::new();
+
+        MemoryDiagnosticReport {
+            stats: get_memory_stats(),
+            meminfo,
+        }
+    }
+
+    /// Collect a report and diagnose it in one step.
+    pub fn auto_diagnose() -> DiagnosisResult {
+        Self::collect().diagnose()
+    }
+
+    /// Run all heuristic checks against this report.
+    pub fn diagnose(&self) -> DiagnosisResult {
+        let mut findings = Vec::new();
+        let mut recommendations = Vec::new();
+
+        self.check_usage(&mut findings, &mut recommendations);
+        self.check_slab(&mut findings, &mut recommendations);
+        self.check_swap(&mut findings, &mut recommendations);
+        self.check_overcommit(&mut findings, &mut recommendations);
+        self.check_dirty(&mut findings, &mut recommendations);
+
+        // Most severe first
+        findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+
+        let confidence = if self.meminfo.contains_key("SUnreclaim") && self.meminfo.contains_key("CommitLimit") {
+            Confidence::High
+        } else if !self.meminfo.is_empty() {
+            Confidence::Medium
+        } else {
+            Confidence::Low
+        };
+
+        DiagnosisResult {
+            findings,
+            recommendations,
+            confidence,
+        }
+    }
+
+    fn check_usage(&self, findings: &mut Vec<Finding>, recommendations: &mut Vec<Recommendation>) {
+        let stats = &self.stats;
+        if stats.total == 0 {
+            return;
+        }
+
+        let severity = if stats.used_percent >= 95.0 {
+            Severity::Critical
+        } else if stats.used_percent >= 85.0 {
+            Severity::Warning
+        } else {
+            return;
+        };
+
+        let mut evidence = HashMap::new();
+        evidence.insert(String::from("used_percent"), format!("{:.1}", stats.used_percent));
+        evidence.insert(String::from("available"), format_bytes(stats.available));
+
+        findings.push(Finding {
+            severity,
+            description: format!(
+                "Memory usage is at {:.1}% with {} available",
+                stats.used_percent,
+                format_bytes(stats.available)
+            ),
+            evidence,
+        });
+
+        if let Some(cached) = stats.cached {
+            if cached > stats.total / 4 {
+                recommendations.push(Recommendation {
+                    action: String::from("Drop page cache for large, cold files"),
+                    reason: format!("{} of memory is page cache", format_bytes(cached)),
+                });
+            }
+        }
+
+        recommendations.push(Recommendation {
+            action: String::from("Identify and restart the largest memory consumers"),
+            reason: format!("Memory usage is at {:.1}%", stats.used_percent),
+        });
+    }
+
+    fn check_slab(&self, findings: &mut Vec<Finding>, recommendations: &mut Vec<Recommendation>) {
+        let slab = self.meminfo.get("Slab").cloned().unwrap_or(0);
+        let unreclaimable = self.meminfo.get("SUnreclaim").cloned().unwrap_or(0);
+        if slab == 0 || self.stats.total == 0 {
+            return;
+        }
+
+        // Kernel slab above 10% of RAM is unusual outside of file servers
+        if slab * 10 < self.stats.total {
+            return;
+        }
+
+        let unreclaimable_percent = (unreclaimable as f64 / slab as f64) * 100.0;
+
+        let mut evidence = HashMap::new();
+        evidence.insert(String::from("Slab"), format_bytes(slab));
+        evidence.insert(String::from("SUnreclaim"), format_bytes(unreclaimable));
+
+        findings.push(Finding {
+            severity: if unreclaimable_percent >= 50.0 { Severity::Warning } else { Severity::Info },
+            description: format!(
+                "Kernel slab caches are using {} and {:.0}% is unreclaimable",
+                format_bytes(slab),
+                unreclaimable_percent
+            ),
+            evidence,
+        });
+
+        recommendations.push(Recommendation {
+            action: String::from("Inspect /proc/slabinfo for growing kernel caches"),
+            reason: format!("Slab usage is {}", format_bytes(slab)),
+        });
+    }
+
+    fn check_swap(&self, findings: &mut Vec<Finding>, recommendations: &mut Vec<Recommendation>) {
+        let swap_total = match self.meminfo.get("SwapTotal") {
+            Some(total) => *total,
+            None => return,
+        };
+        let swap_free = self.meminfo.get("SwapFree").cloned().unwrap_or(0);
+
+        if swap_total == 0 {
+            if self.stats.used_percent >= 80.0 {
+                recommendations.push(Recommendation {
+                    action: String::from("Enable zswap or add a swap device"),
+                    reason: String::from("No swap is configured and memory usage is high"),
+                });
+            }
+            return;
+        }
+
+        let swap_used = swap_total.saturating_sub(swap_free);
+        let swap_percent = (swap_used as f64 / swap_total as f64) * 100.0;
+        if swap_percent < 50.0 {
+            return;
+        }
+
+        let mut evidence = HashMap::new();
+        evidence.insert(String::from("SwapTotal"), format_bytes(swap_total));
+        evidence.insert(String::from("SwapFree"), format_bytes(swap_free));
+
+        findings.push(Finding {
+            severity: if swap_percent >= 90.0 { Severity::Critical } else { Severity::Warning },
+            description: format!("Swap is {:.0}% used ({} of {})", swap_percent, format_bytes(swap_used), format_bytes(swap_total)),
+            evidence,
+        });
+
+        recommendations.push(Recommendation {
+            action: String::from("Reduce resident working sets or add memory"),
+            reason: format!("Swap is {:.0}% used", swap_percent),
+        });
+    }
+
+    fn check_overcommit(&self, findings: &mut Vec<Finding>, recommendations: &mut Vec<Recommendation>) {
+        let committed = match self.meminfo.get("Committed_AS") {
+            Some(committed) => *committed,
+            None => return,
+        };
+        let limit = match self.meminfo.get("CommitLimit") {
+            Some(limit) => *limit,
+            None => return,
+        };
+
+        if committed <= limit {
+            return;
+        }
+
+        let mut evidence = HashMap::new();
+        evidence.insert(String::from("Committed_AS"), format_bytes(committed));
+        evidence.insert(String::from("CommitLimit"), format_bytes(limit));
+
+        findings.push(Finding {
+            severity: Severity::Warning,
+            description: format!(
+                "Committed virtual memory ({}) exceeds the commit limit ({})",
+                format_bytes(committed),
+                format_bytes(limit)
+            ),
+            evidence,
+        });
+
+        recommendations.push(Recommendation {
+            action: String::from("Check vm.overcommit_memory; large allocations may fail with ENOMEM"),
+            reason: String::from("Committed_AS is above CommitLimit"),
+        });
+    }
+
+    fn check_dirty(&self, findings: &mut Vec<Finding>, recommendations: &mut Vec<Recommendation>) {
+        let dirty = self.meminfo.get("Dirty").cloned().unwrap_or(0);
+        if self.stats.total == 0 || dirty * 10 < self.stats.total {
+            return;
+        }
+
+        let mut evidence = HashMap::new();
+        evidence.insert(String::from("Dirty"), format_bytes(dirty));
+
+        findings.push(Finding {
+            severity: Severity::Info,
+            description: format!("{} of dirty pages are waiting for writeback", format_bytes(dirty)),
+            evidence,
+        });
+
+        recommendations.push(Recommendation {
+            action: String::from("Lower vm.dirty_ratio or vm.dirty_background_ratio"),
+            reason: format!("Dirty pages are {}", format_bytes(dirty)),
+        });
+    }
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Error type shared by the memory inspection and healing helpers.
+#[derive(Debug)]
+pub enum MemoryError {
+    /// An OS call or file operation failed.
+    Io(io::Error),
+    /// A kernel interface returned data that could not be parsed.
+    Parse(String),
+    /// Serializing a report or snapshot failed.
+    #[cfg(feature = "serde")]
+    Serialization(serde_json::Error),
+    /// The operation is not available on this platform or kernel.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemoryError::Io(err) => write!(f, "I/O error: {}", err),
+            MemoryError::Parse(msg) => write!(f, "parse error: {}", msg),
+            #[cfg(feature = "serde")]
+            MemoryError::Serialization(err) => write!(f, "serialization error: {}", err),
+            MemoryError::Unsupported(what) => write!(f, "unsupported: {}", what),
+        }
+    }
+}
+
+impl Error for MemoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MemoryError::Io(err) => Some(err),
+            #[cfg(feature = "serde")]
+            MemoryError::Serialization(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MemoryError {
+    fn from(err: io::Error) -> Self {
+        MemoryError::Io(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for MemoryError {
+    fn from(err: serde_json::Error) -> Self {
+        MemoryError::Serialization(err)
+    }
+}
@@ -0,0 +1,343 @@
+//! Text encodings of `MemoryStats` for metrics pipelines.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use super::text::render_statsd;
+use super::{MemoryError, MemoryStats};
+
+pub use super::text::{to_statsd, to_statsd_with_format, StatsdFormat};
+#[cfg(feature = "prometheus")]
+pub use super::text::format_prometheus;
+#[cfg(feature = "influx")]
+pub use super::text::format_influx;
+#[cfg(feature = "csv")]
+pub use super::text::{csv_header, to_csv_row};
+
+/// Send `stats` to a StatsD server as `{prefix}.memory.<field>_bytes` gauges.
+///
+/// Opens a socket per call; use `StatsdEmitter` for periodic emission.
+pub fn emit_statsd(stats: &MemoryStats, host: &str, port: u16, prefix: &str) -> io::Result<()> {
+    send_statsd(host, port, &render_statsd(stats, prefix, "_bytes", StatsdFormat::Plain, &[]))
+}
+
+/// Send `stats` to a DogStatsD agent with `tags` attached to every gauge.
+#[cfg(feature = "dogstatsd")]
+pub fn emit_dogstatsd(stats: &MemoryStats, host: &str, port: u16, prefix: &str, tags: &[(&str, &str)]) -> io::Result<()> {
+    send_statsd(host, port, &render_statsd(stats, prefix, "_bytes", StatsdFormat::DogStatsd, tags))
+}
+
+fn send_statsd(host: &str, port: u16, payload: &str) -> io::Result<()> {
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "statsd host did not resolve"))?;
+
+    let bind_addr = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(bind_addr)?;
+    socket.send_to(payload.as_bytes(), addr)?;
+    Ok(())
+}
+
+/// Encode `stats` as MessagePack, with field names so it stays self-describing.
+#[cfg(feature = "msgpack")]
+pub fn to_msgpack(stats: &MemoryStats) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    rmp_serde::to_vec_named(stats)
+}
+
+/// Decode `MemoryStats` previously encoded with `to_msgpack`.
+#[cfg(feature = "msgpack")]
+pub fn from_msgpack(data: &[u8]) -> Result<MemoryStats, rmp_serde::decode::Error> {
+    rmp_serde::from_slice(data)
+}
+
+/// Appends `MemoryStats` rows to a CSV stream.
+#[cfg(feature = "csv")]
+pub struct MemoryStatsCsvWriter<W: io::Write> {
+    writer: W,
+}
+
+#[cfg(feature = "csv")]
+impl<W: io::Write> MemoryStatsCsvWriter<W> {
+    /// Wrap `writer`, writing the header row first.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "{}", csv_header())?;
+        Ok(MemoryStatsCsvWriter { writer })
+    }
+
+    pub fn write(&mut self, stats: &MemoryStats) -> io::Result<()> {
+        writeln!(self.writer, "{}", to_csv_row(stats))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Serialize `stats` as a YAML document.
+#[cfg(feature = "yaml")]
+pub fn to_yaml(stats: &MemoryStats) -> Result<String, serde_yaml::Error> {
+    serde_yaml::to_string(stats)
+}
+
+/// Parse `MemoryStats` from a YAML document.
+///
+/// `timestamp` stays a plain string even though it looks like a YAML
+/// timestamp, since the target field is a `String`.
+#[cfg(feature = "yaml")]
+pub fn from_yaml(s: &str) -> Result<MemoryStats, serde_yaml::Error> {
+    serde_yaml::from_str(s)
+}
+
+/// Describe the change from `before` to `after` as an RFC 6902 JSON Patch.
+///
+/// Fields present in both with different values become `replace`
+/// operations; optional fields that appear or disappear become `add` or
+/// `remove`. Fields are compared through their serde representation, so new
+/// `MemoryStats` fields are picked up without changes here.
+#[cfg(feature = "serde")]
+pub fn json_patch(before: &MemoryStats, after: &MemoryStats) -> String {
+    let mut operations = Vec::new();
+    match (serde_json::to_value(before), serde_json::to_value(after)) {
+        (Ok(before), Ok(after)) => diff_values("", &before, &after, &mut operations),
+        _ => return String::from("[]"),
+    }
+
+    serde_json::Value::Array(operations).to_string()
+}
+
+/// Append the operations turning `before` into `after` at `path`.
+#[cfg(feature = "serde")]
+fn diff_values(path: &str, before: &serde_json::Value, after: &serde_json::Value, operations: &mut Vec<serde_json::Value>) {
+    use serde_json::{json, Value};
+
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (key, old) in before {
+                let field_path = format!("{}/{}", path, escape_pointer(key));
+                match after.get(key) {
+                    Some(new) => diff_values(&field_path, old, new, operations),
+                    None => operations.push(json!({ "op": "remove", "path": field_path })),
+                }
+            }
+            for (key, new) in after {
+                if !before.contains_key(key) {
+                    let field_path = format!("{}/{}", path, escape_pointer(key));
+                    operations.push(json!({ "op": "add", "path": field_path, "value": new }));
+                }
+            }
+        }
+        _ if before != after => operations.push(json!({ "op": "replace", "path": path, "value": after })),
+        _ => {}
+    }
+}
+
+/// Escape a key for use in a JSON Pointer (RFC 6901).
+#[cfg(feature = "serde")]
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// `MemoryStats` without `skip_serializing_if`, which a positional format
+/// like bincode cannot decode.
+#[cfg(feature = "bincode")]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct BincodeStats {
+    total: u64,
+    free: u64,
+    available: u64,
+    used: u64,
+    used_percent: f64,
+    buffers: Option<u64>,
+    cached: Option<u64>,
+    speculative: Option<u64>,
+    fragmentation_score: Option<f64>,
+    swap_total: Option<u64>,
+    swap_free: Option<u64>,
+    dirty: Option<u64>,
+    writeback: Option<u64>,
+    timestamp: String,
+}
+
+/// Encode `stats` with bincode.
+#[cfg(feature = "bincode")]
+pub fn to_bincode(stats: &MemoryStats) -> Result<Vec<u8>, bincode::Error> {
+    bincode::serialize(&BincodeStats {
+        total: stats.total,
+        free: stats.free,
+        available: stats.available,
+        used: stats.used,
+        used_percent: stats.used_percent,
+        buffers: stats.buffers,
+        cached: stats.cached,
+        speculative: stats.speculative,
+        fragmentation_score: stats.fragmentation_score,
+        swap_total: stats.swap_total,
+        swap_free: stats.swap_free,
+        dirty: stats.dirty,
+        writeback: stats.writeback,
+        timestamp: stats.timestamp.clone(),
+    })
+}
+
+/// Decode `MemoryStats` previously encoded with `to_bincode`.
+#[cfg(feature = "bincode")]
+pub fn from_bincode(data: &[u8]) -> Result<MemoryStats, bincode::Error> {
+    let stats: BincodeStats = bincode::deserialize(data)?;
+
+    Ok(MemoryStats {
+        total: stats.total,
+        free: stats.free,
+        available: stats.available,
+        used: stats.used,
+        used_percent: stats.used_percent,
+        buffers: stats.buffers,
+        cached: stats.cached,
+        speculative: stats.speculative,
+        fragmentation_score: stats.fragmentation_score,
+        swap_total: stats.swap_total,
+        swap_free: stats.swap_free,
+        dirty: stats.dirty,
+        writeback: stats.writeback,
+        timestamp: stats.timestamp,
+    })
+}
+
+/// Sends `MemoryStats` to a StatsD server over UDP from a background thread.
+pub struct StatsdEmitter {
+    prefix: String,
+    format: StatsdFormat,
+    tags: Vec<(String, String)>,
+    sender: Option<Sender<String>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl StatsdEmitter {
+    /// Create an emitter sending plain StatsD to `addr`.
+    pub fn new(addr: SocketAddr, prefix: String) -> Result<Self, MemoryError> {
+        let bind_addr = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.connect(addr)?;
+
+        let (sender, receiver) = mpsc::channel::<String>();
+        let handle = thread::spawn(move || {
+            // Exits once the emitter, and with it the sender, is dropped
+            for payload in receiver {
+                // StatsD is fire-and-forget; a lost datagram is not an error
+                let _ = socket.send(payload.as_bytes());
+            }
+        });
+
+        Ok(StatsdEmitter {
+            prefix,
+            format: StatsdFormat::Plain,
+            tags: Vec::new(),
+            sender: Some(sender),
+            handle: Some(handle),
+        })
+    }
+
+    /// Switch to the given dialect and tags.
+    pub fn with_format(mut self, format: StatsdFormat, tags: Vec<(String, String)>) -> Self {
+        self.format = format;
+        self.tags = tags;
+        self
+    }
+
+    /// Queue one datagram with all gauges for `stats`.
+    pub fn emit(&self, stats: &MemoryStats) -> Result<(), MemoryError> {
+        let tags: Vec<(&str, &str)> = self.tags.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+        let payload = to_statsd_with_format(stats, &self.prefix, self.format, &tags);
+
+        match self.sender {
+            Some(ref sender) => sender
+                .send(payload)
+                .map_err(|_| MemoryError::Io(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "statsd sender thread exited"))),
+            None => Err(MemoryError::Unsupported("emitter is shut down")),
+        }
+    }
+}
+
+impl Drop for StatsdEmitter {
+    fn drop(&mut self) {
+        // Closing the channel lets the thread drain and exit
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "bincode"))]
+mod tests {
+    use super::*;
+
+    fn sample_stats() -> MemoryStats {
+        MemoryStats {
+            total: 1000,
+            free: 200,
+            available: 300,
+            used: 700,
+            used_percent: 70.0,
+            buffers: Some(10),
+            cached: None,
+            speculative: None,
+            fragmentation_score: None,
+            swap_total: None,
+            swap_free: None,
+            dirty: None,
+            writeback: None,
+            timestamp: String::from("1970-01-01T00:00:00Z"),
+        }
+    }
+
+    fn assert_bincode_round_trip(stats: &MemoryStats) {
+        let decoded = from_bincode(&to_bincode(stats).unwrap()).unwrap();
+        assert_eq!(decoded.total, stats.total);
+        assert_eq!(decoded.free, stats.free);
+        assert_eq!(decoded.available, stats.available);
+        assert_eq!(decoded.used, stats.used);
+        assert_eq!(decoded.used_percent, stats.used_percent);
+        assert_eq!(decoded.buffers, stats.buffers);
+        assert_eq!(decoded.cached, stats.cached);
+        assert_eq!(decoded.speculative, stats.speculative);
+        assert_eq!(decoded.fragmentation_score, stats.fragmentation_score);
+        assert_eq!(decoded.swap_total, stats.swap_total);
+        assert_eq!(decoded.swap_free, stats.swap_free);
+        assert_eq!(decoded.dirty, stats.dirty);
+        assert_eq!(decoded.writeback, stats.writeback);
+        assert_eq!(decoded.timestamp, stats.timestamp);
+    }
+
+    #[test]
+    fn bincode_round_trip_with_every_optional_field() {
+        let stats = MemoryStats {
+            buffers: Some(10),
+            cached: Some(20),
+            speculative: Some(30),
+            fragmentation_score: Some(0.25),
+            swap_total: Some(4096),
+            swap_free: Some(1024),
+            dirty: Some(5),
+            writeback: Some(6),
+            ..sample_stats()
+        };
+        assert_bincode_round_trip(&stats);
+    }
+
+    #[test]
+    fn bincode_round_trip_without_optional_fields() {
+        let stats = MemoryStats {
+            buffers: None,
+            ..sample_stats()
+        };
+        assert_bincode_round_trip(&stats);
+    }
+}
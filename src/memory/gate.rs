@@ -0,0 +1,62 @@
+//! Back-pressure for worker threads based on memory usage.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::cache::CachedMemoryStats;
+
+// First sleep of `wait_for_capacity`; doubles up to the cache ttl
+const INITIAL_BACKOFF: Duration = Duration::from_millis(1);
+
+/// Admits work only while used_percent is below a threshold.
+///
+/// Checks read a `CachedMemoryStats`, so calling `allow()` on every request
+/// costs no system calls. Put a gate in front of worker entry points to shed
+/// or delay load before the system runs out of memory.
+pub struct MemoryGate {
+    threshold: f64,
+    stats_cache: CachedMemoryStats,
+}
+
+impl MemoryGate {
+    /// Gate at `threshold` percent used, with stats refreshed every `ttl`.
+    pub fn new(threshold: f64, ttl: Duration) -> Self {
+        MemoryGate::with_cache(threshold, CachedMemoryStats::new(ttl))
+    }
+
+    /// Gate at `threshold` percent used, reading stats from `stats_cache`.
+    pub fn with_cache(threshold: f64, stats_cache: CachedMemoryStats) -> Self {
+        MemoryGate { threshold, stats_cache }
+    }
+
+    /// Whether used_percent is below the threshold.
+    pub fn allow(&self) -> bool {
+        self.stats_cache.get().used_percent < self.threshold
+    }
+
+    /// Block until `allow()` is true or `timeout` passes; returns the final answer.
+    ///
+    /// Sleeps start at 1 ms and double after each refused check, capped at
+    /// the cache ttl since the stats cannot change faster than that.
+    pub fn wait_for_capacity(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            if self.allow() {
+                return true;
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            thread::sleep(backoff.min(remaining));
+            backoff = (backoff * 2).min(self.stats_cache.ttl().max(INITIAL_BACKOFF));
+        }
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+}
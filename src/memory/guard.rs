@@ -0,0 +1,140 @@
+//! Canary-guarded allocations for catching buffer overruns.
+
+use alloc::alloc::{alloc, dealloc, Layout};
+
+/// Bytes reserved for each canary.
+pub const CANARY_SIZE: usize = 8;
+
+/// Pattern written before and after the user region.
+const CANARY: [u8; CANARY_SIZE] = [0xDE, 0xAD, 0xBE, 0xEF, 0xFE, 0xED, 0xFA, 0xCE];
+
+/// Heap buffer bracketed by canaries that are checked on drop.
+///
+/// Layout: `[canary][size user bytes][canary]`.
+pub struct GuardedAlloc {
+    ptr: *mut u8,
+    layout: Layout,
+    size: usize,
+}
+
+unsafe impl Send for GuardedAlloc {}
+
+impl GuardedAlloc {
+    /// Allocate a zeroed `size` byte region with canaries on both sides.
+    ///
+    /// Returns `None` if the allocation fails.
+    pub fn new(size: usize) -> Option<Self> {
+        let total = size.checked_add(2 * CANARY_SIZE)?;
+        let layout = Layout::from_size_align(total, CANARY_SIZE).ok()?;
+
+        let ptr = unsafe { alloc(layout) };
+        if ptr.is_null() {
+            return None;
+        }
+
+        unsafe {
+            core::ptr::copy_nonoverlapping(CANARY.as_ptr(), ptr, CANARY_SIZE);
+            core::ptr::write_bytes(ptr.add(CANARY_SIZE), 0, size);
+            core::ptr::copy_nonoverlapping(CANARY.as_ptr(), ptr.add(CANARY_SIZE + size), CANARY_SIZE);
+        }
+
+        Some(GuardedAlloc { ptr, layout, size })
+    }
+
+    /// Size of the user region in bytes.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// The user region, excluding the canaries.
+    pub fn as_slice_mut(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.add(CANARY_SIZE), self.size) }
+    }
+
+    /// Pointer to the start of the user region, for writes the borrow checker
+    /// cannot see, e.g. from C.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        unsafe { self.ptr.add(CANARY_SIZE) }
+    }
+
+    /// Whether both canaries are intact.
+    pub fn check_integrity(&self) -> bool {
+        let (head, tail) = unsafe {
+            (
+                core::slice::from_raw_parts(self.ptr, CANARY_SIZE),
+                core::slice::from_raw_parts(self.ptr.add(CANARY_SIZE + self.size), CANARY_SIZE),
+            )
+        };
+        head == CANARY && tail == CANARY
+    }
+}
+
+impl Drop for GuardedAlloc {
+    fn drop(&mut self) {
+        if !self.check_integrity() {
+            error_event!("GuardedAlloc: canary corrupted, a {} byte buffer was overrun", self.size);
+
+            // An overrun may have hit allocator metadata too, so leak the
+            // buffer rather than hand it back; skip the panic while already
+            // unwinding to avoid an abort
+            #[cfg(not(feature = "no_std"))]
+            if std::thread::panicking() {
+                return;
+            }
+            panic!("GuardedAlloc: canary corrupted, a {} byte buffer was overrun", self.size);
+        }
+
+        unsafe {
+            dealloc(self.ptr, self.layout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intact_buffer_drops_cleanly() {
+        let mut guarded = GuardedAlloc::new(32).unwrap();
+        assert_eq!(guarded.len(), 32);
+        assert!(guarded.as_slice_mut().iter().all(|&byte| byte == 0));
+
+        guarded.as_slice_mut().fill(0xAB);
+        assert!(guarded.check_integrity());
+        drop(guarded);
+    }
+
+    #[test]
+    fn empty_buffer_keeps_both_canaries() {
+        let guarded = GuardedAlloc::new(0).unwrap();
+        assert!(guarded.is_empty());
+        assert!(guarded.check_integrity());
+    }
+
+    #[test]
+    #[should_panic(expected = "canary corrupted")]
+    fn head_corruption_panics_on_drop() {
+        let mut guarded = GuardedAlloc::new(16).unwrap();
+        unsafe {
+            *guarded.as_mut_ptr().sub(1) = 0;
+        }
+        assert!(!guarded.check_integrity());
+        drop(guarded);
+    }
+
+    #[test]
+    #[should_panic(expected = "canary corrupted")]
+    fn tail_corruption_panics_on_drop() {
+        let mut guarded = GuardedAlloc::new(16).unwrap();
+        unsafe {
+            *guarded.as_mut_ptr().add(16) = 0;
+        }
+        assert!(!guarded.check_integrity());
+        drop(guarded);
+    }
+}
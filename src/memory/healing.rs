@@ -0,0 +1,364 @@
+//! Healing actions, the engine that runs them, and targeted operations that
+//! are less disruptive than the global `release_memory_cache`.
+
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use super::{MemoryError, MemoryStats};
+
+/// Outcome of one healing action.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HealingResult {
+    pub action: String,       // Name of the action that ran
+    pub success: bool,        // Whether the action reported success
+    pub bytes_reclaimed: i64, // Change in free memory, negative if it shrank
+    pub duration_ms: u64,     // Wall time spent in the action
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dry_run: bool,        // Whether the action was only simulated
+}
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Make every `HealingEngine` simulate its actions instead of running them.
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::SeqCst);
+}
+
+/// Whether `set_dry_run` is in effect.
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::SeqCst)
+}
+
+/// A self-contained step the `HealingEngine` can run to recover memory.
+pub trait HealingAction: Send + Sync {
+    /// Short name used in logs and results.
+    fn name(&self) -> &str;
+
+    /// Run the action once.
+    fn execute(&self) -> HealingResult;
+
+    /// Actions with a higher priority run first.
+    fn priority(&self) -> u8;
+}
+
+/// Runs registered healing actions in priority order.
+pub struct HealingEngine {
+    actions: Vec<Box<dyn HealingAction>>,
+}
+
+impl HealingEngine {
+    /// Create an engine with no actions.
+    pub fn new() -> Self {
+        HealingEngine { actions: Vec::new() }
+    }
+
+    /// Add an action; actions of equal priority run in registration order.
+    pub fn register(&mut self, action: Box<dyn HealingAction>) {
+        self.actions.push(action);
+        self.actions.sort_by_key(|action| std::cmp::Reverse(action.priority()));
+    }
+
+    /// Names of the registered actions in the order they run.
+    pub fn actions(&self) -> Vec<&str> {
+        self.actions.iter().map(|action| action.name()).collect()
+    }
+
+    /// Run every action, highest priority first, and collect their results.
+    ///
+    /// A failing action does not stop the ones after it. Under `set_dry_run`
+    /// the actions are only simulated.
+    pub fn heal(&self) -> Vec<HealingResult> {
+        self.actions.iter().map(|action| run(action.as_ref())).collect()
+    }
+
+    /// Like `heal`, but also append a `HealingEvent` per action to `log`.
+    pub fn heal_logged(&self, log: &Mutex<HealingLog>) -> Vec<HealingResult> {
+        self.actions
+            .iter()
+            .map(|action| {
+                let timestamp = super::format_timestamp();
+                let stats_before = super::get_memory_stats();
+                let result = run(action.as_ref());
+                let stats_after = super::get_memory_stats();
+
+                log.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).append(HealingEvent {
+                    timestamp,
+                    action: result.action.clone(),
+                    bytes_reclaimed: result.bytes_reclaimed,
+                    success: result.success,
+                    dry_run: result.dry_run,
+                    stats_before,
+                    stats_after,
+                });
+                result
+            })
+            .collect()
+    }
+}
+
+impl Default for HealingEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Record of one healing action, for auditing what automatic healing did.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HealingEvent {
+    pub timestamp: String,         // When the action started
+    pub action: String,            // Name of the action
+    pub bytes_reclaimed: i64,      // As reported by the action
+    pub success: bool,             // Whether the action reported success
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dry_run: bool,             // Whether the action was only simulated
+    pub stats_before: MemoryStats, // Sample taken right before the action
+    pub stats_after: MemoryStats,  // Sample taken right after the action
+}
+
+/// Append-only audit log of healing events.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HealingLog {
+    events: Vec<HealingEvent>,
+}
+
+impl HealingLog {
+    pub fn new() -> Self {
+        HealingLog { events: Vec::new() }
+    }
+
+    pub fn append(&mut self, event: HealingEvent) {
+        self.events.push(event);
+    }
+
+    /// Events in the order they were appended.
+    pub fn events(&self) -> &[HealingEvent] {
+        &self.events
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The events as a JSON array.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.events).unwrap_or_else(|_| String::from("[]"))
+    }
+}
+
+/// Wraps an action so that executing it only simulates the wrapped action.
+///
+/// Lets operators preview what a healing setup would do before enabling it;
+/// the results, and any `HealingLog` entries, carry `dry_run: true`.
+pub struct DryRun<A> {
+    inner: A,
+}
+
+impl<A: HealingAction> DryRun<A> {
+    pub fn new(inner: A) -> Self {
+        DryRun { inner }
+    }
+
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<A: HealingAction> HealingAction for DryRun<A> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn execute(&self) -> HealingResult {
+        simulate(&self.inner)
+    }
+
+    fn priority(&self) -> u8 {
+        self.inner.priority()
+    }
+}
+
+/// Drops the OS page cache through `release_memory_cache`.
+pub struct DropCacheAction;
+
+impl HealingAction for DropCacheAction {
+    fn name(&self) -> &str {
+        "drop_cache"
+    }
+
+    fn execute(&self) -> HealingResult {
+        measure(self.name(), super::release_memory_cache)
+    }
+
+    fn priority(&self) -> u8 {
+        100
+    }
+}
+
+/// Returns unused allocator pages through `defragment_memory`.
+///
+/// Cheap and local to the process, so it runs before dropping caches.
+pub struct DefragAction;
+
+impl HealingAction for DefragAction {
+    fn name(&self) -> &str {
+        "defragment"
+    }
+
+    fn execute(&self) -> HealingResult {
+        let result = super::defragment_memory(None);
+        HealingResult {
+            action: self.name().to_string(),
+            success: result.success,
+            bytes_reclaimed: result.bytes_reclaimed,
+            duration_ms: result.duration_ms,
+            dry_run: false,
+        }
+    }
+
+    fn priority(&self) -> u8 {
+        200
+    }
+}
+
+/// Sets a process's `oom_score_adj`, e.g. to make an expendable worker the
+/// OOM killer's preferred victim.
+///
+/// Frees nothing by itself, so it runs last.
+pub struct OomAdjAction {
+    pid: u32,
+    score_adj: i16,
+}
+
+impl OomAdjAction {
+    /// Adjust `pid` to `score_adj`, clamped to -1000..=1000.
+    pub fn new(pid: u32, score_adj: i16) -> Self {
+        OomAdjAction { pid, score_adj }
+    }
+}
+
+impl HealingAction for OomAdjAction {
+    fn name(&self) -> &str {
+        "oom_score_adj"
+    }
+
+    fn execute(&self) -> HealingResult {
+        measure(self.name(), || super::set_oom_score_adj(self.pid, self.score_adj).is_ok())
+    }
+
+    fn priority(&self) -> u8 {
+        10
+    }
+}
+
+/// Run `action` and report the change in free memory it caused.
+fn measure<F: FnOnce() -> bool>(name: &str, action: F) -> HealingResult {
+    let before = super::get_memory_stats();
+    let started = Instant::now();
+
+    let success = action();
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let after = super::get_memory_stats();
+
+    HealingResult {
+        action: name.to_string(),
+        success,
+        bytes_reclaimed: after.free as i64 - before.free as i64,
+        duration_ms,
+        dry_run: false,
+    }
+}
+
+/// Execute `action`, or only simulate it while dry-run mode is on.
+fn run(action: &dyn HealingAction) -> HealingResult {
+    if is_dry_run() {
+        return simulate(action);
+    }
+    action.execute()
+}
+
+/// Result of pretending to run `action`.
+///
+/// Stats are sampled around a no-op, so `bytes_reclaimed` shows how much free
+/// memory drifts on its own over the time an action would take.
+fn simulate(action: &dyn HealingAction) -> HealingResult {
+    info_event!("dry run: would run {} (priority {})", action.name(), action.priority());
+
+    let mut result = measure(action.name(), || true);
+    result.dry_run = true;
+    result
+}
+
+/// Drops page cache for individual files instead of the whole system.
+pub struct SelectivePageCache;
+
+impl SelectivePageCache {
+    /// Drop cached pages of a single file.
+    ///
+    /// Returns the estimated number of bytes freed, taken from the file size.
+    /// Dirty pages stay cached until they have been written back.
+    pub fn drop_file_cache(path: &Path) -> Result<u64, MemoryError> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+
+        fadvise_dontneed(&file)?;
+
+        Ok(size)
+    }
+
+    /// Drop cached pages of every regular file in `dir`.
+    ///
+    /// Files that cannot be opened are skipped. Symlinks are not followed.
+    /// Returns the estimated total number of bytes freed.
+    pub fn drop_directory_cache(dir: &Path, recursive: bool) -> Result<u64, MemoryError> {
+        let mut freed = 0;
+
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+
+            if file_type.is_file() {
+                if let Ok(bytes) = Self::drop_file_cache(&path) {
+                    freed += bytes;
+                }
+            } else if file_type.is_dir() && recursive {
+                freed += Self::drop_directory_cache(&path, true)?;
+            }
+        }
+
+        Ok(freed)
+    }
+}
+
+/// Advise the kernel that the whole file will not be needed again.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn fadvise_dontneed(file: &File) -> Result<(), MemoryError> {
+    use std::os::unix::io::AsRawFd;
+
+    // A length of 0 means "to the end of the file"
+    let ret = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+
+    // posix_fadvise returns the error number instead of setting errno
+    if ret != 0 {
+        return Err(MemoryError::Io(std::io::Error::from_raw_os_error(ret)));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+fn fadvise_dontneed(_file: &File) -> Result<(), MemoryError> {
+    Err(MemoryError::Unsupported("posix_fadvise is not available on this platform"))
+}
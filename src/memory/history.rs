@@ -0,0 +1,319 @@
+//! Fixed-size history of `MemoryStats` snapshots and rolling statistics over it.
+
+use std::collections::VecDeque;
+
+use super::MemoryStats;
+
+/// Mean and standard deviation over the last `capacity` values.
+///
+/// Uses Welford's online algorithm, extended to remove the value leaving the
+/// window, so every update and query is O(1).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MovingAverage {
+    window: VecDeque<f64>,
+    capacity: usize,
+    mean: f64,
+    m2: f64, // Sum of squared deviations from the mean
+}
+
+impl MovingAverage {
+    /// Create an empty average over at most `capacity` values.
+    pub fn new(capacity: usize) -> MovingAverage {
+        MovingAverage {
+            window: VecDeque::with_capacity(capacity),
+            capacity,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    /// Add `value`, evicting the oldest one when full, and return the new mean.
+    pub fn push(&mut self, value: f64) -> f64 {
+        if self.capacity == 0 {
+            return self.mean;
+        }
+        if self.window.len() == self.capacity {
+            if let Some(oldest) = self.window.pop_front() {
+                self.remove(oldest);
+            }
+        }
+
+        self.window.push_back(value);
+        let delta = value - self.mean;
+        self.mean += delta / self.window.len() as f64;
+        self.m2 += delta * (value - self.mean);
+        self.mean
+    }
+
+    /// Undo the contribution of `value`, which has already left the window.
+    fn remove(&mut self, value: f64) {
+        let count = self.window.len();
+        if count == 0 {
+            self.mean = 0.0;
+            self.m2 = 0.0;
+            return;
+        }
+
+        let mean = (self.mean * (count + 1) as f64 - value) / count as f64;
+        // Rounding can push the sum slightly below zero
+        self.m2 = (self.m2 - (value - self.mean) * (value - mean)).max(0.0);
+        self.mean = mean;
+    }
+
+    /// Forget all values.
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.mean = 0.0;
+        self.m2 = 0.0;
+    }
+
+    /// Mean of the window, 0.0 when empty.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Population standard deviation of the window, 0.0 when empty.
+    pub fn std_dev(&self) -> f64 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        (self.m2 / self.window.len() as f64).sqrt()
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+}
+
+/// Slopes below this many percentage points per sample count as stable.
+const TREND_EPSILON: f64 = 0.05;
+
+/// Direction of used_percent over a `MemoryHistory` window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MemoryTrend {
+    Rising(f64),  // Slope in percentage points per sample
+    Stable,
+    Falling(f64), // Slope in percentage points per sample, negative
+}
+
+/// Ring buffer keeping the most recent `capacity` snapshots.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryHistory {
+    capacity: usize,
+    samples: VecDeque<MemoryStats>,
+    used_percent_average: MovingAverage, // Rolling used_percent over the same window
+}
+
+impl MemoryHistory {
+    /// Create an empty history holding at most `capacity` snapshots.
+    pub fn new(capacity: usize) -> MemoryHistory {
+        MemoryHistory {
+            capacity,
+            // Room for twice the window, so `push` only has to rotate the
+            // buffer back into one piece once every `capacity` samples
+            samples: VecDeque::with_capacity(capacity.saturating_mul(2)),
+            used_percent_average: MovingAverage::new(capacity),
+        }
+    }
+
+    /// Add a snapshot, evicting the oldest one when full.
+    pub fn push(&mut self, stats: MemoryStats) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.used_percent_average.push(stats.used_percent);
+        self.samples.push_back(stats);
+        if !self.samples.as_slices().1.is_empty() {
+            self.samples.make_contiguous();
+        }
+    }
+
+    /// Snapshots, oldest first.
+    pub fn samples(&self) -> &[MemoryStats] {
+        // `push` keeps the buffer in one piece
+        self.samples.as_slices().0
+    }
+
+    /// Snapshots, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &MemoryStats> + '_ {
+        self.samples.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Highest used memory in bytes across all snapshots.
+    pub fn peak_used(&self) -> Option<u64> {
+        self.samples.iter().map(|stats| stats.used).max()
+    }
+
+    /// Mean used percentage, 0.0 when empty.
+    pub fn average_used_percent(&self) -> f64 {
+        self.used_percent_average.mean()
+    }
+
+    /// Standard deviation of the used percentage, 0.0 when empty.
+    pub fn used_percent_std_dev(&self) -> f64 {
+        self.used_percent_average.std_dev()
+    }
+
+    /// The newest sample if its used_percent is more than `z_threshold`
+    /// standard deviations away from the window mean.
+    ///
+    /// Only the newest sample is judged, against the rolling statistics, so
+    /// the check is O(1); call it after each `push`. A threshold of 3.0 suits
+    /// production alerting, lower values flag ordinary fluctuation. The newest
+    /// sample is part of the window, which caps the score at sqrt(len - 1),
+    /// so 3.0 needs at least 11 samples. A window whose values are all equal
+    /// never reports a spike.
+    pub fn detect_spike(&self, z_threshold: f64) -> Option<&MemoryStats> {
+        let latest = self.samples.back()?;
+        let std_dev = self.used_percent_average.std_dev();
+        if std_dev == 0.0 {
+            return None;
+        }
+
+        let z = (latest.used_percent - self.used_percent_average.mean()).abs() / std_dev;
+        if z > z_threshold {
+            Some(latest)
+        } else {
+            None
+        }
+    }
+
+    /// Classify the least-squares slope of used_percent over the window.
+    ///
+    /// Returns `None` with fewer than `min_samples` samples, or fewer than two.
+    pub fn trend(&self, min_samples: usize) -> Option<MemoryTrend> {
+        let count = self.samples.len();
+        if count < min_samples.max(2) {
+            return None;
+        }
+
+        // x is the sample index, so the slope is per sample
+        let n = count as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = self.samples.iter().map(|stats| stats.used_percent).sum::<f64>() / n;
+        let (covariance, variance) = self.samples.iter().enumerate().fold((0.0, 0.0), |(cov, var), (index, stats)| {
+            let dx = index as f64 - mean_x;
+            (cov + dx * (stats.used_percent - mean_y), var + dx * dx)
+        });
+        let slope = covariance / variance;
+
+        Some(if slope.abs() < TREND_EPSILON {
+            MemoryTrend::Stable
+        } else if slope > 0.0 {
+            MemoryTrend::Rising(slope)
+        } else {
+            MemoryTrend::Falling(slope)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_with_used_percent(used_percent: f64) -> MemoryStats {
+        MemoryStats {
+            total: 100,
+            free: 0,
+            available: 0,
+            used: used_percent as u64,
+            used_percent,
+            buffers: None,
+            cached: None,
+            speculative: None,
+            fragmentation_score: None,
+            swap_total: None,
+            swap_free: None,
+            dirty: None,
+            writeback: None,
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn samples_stay_ordered_after_wrapping() {
+        let mut history = MemoryHistory::new(3);
+        for value in 1..=5 {
+            history.push(stats_with_used_percent(value as f64));
+        }
+
+        let expected = [3.0, 4.0, 5.0];
+        let iterated: Vec<f64> = history.iter().map(|stats| stats.used_percent).collect();
+        assert_eq!(iterated, expected);
+        let sliced: Vec<f64> = history.samples().iter().map(|stats| stats.used_percent).collect();
+        assert_eq!(sliced, expected);
+    }
+
+    #[test]
+    fn samples_stay_whole_through_many_wraps() {
+        let mut history = MemoryHistory::new(4);
+        for value in 1..=50 {
+            history.push(stats_with_used_percent(value as f64));
+
+            let shared = &history;
+            let newest: Vec<f64> = shared.iter().map(|stats| stats.used_percent).collect();
+            let sliced: Vec<f64> = shared.samples().iter().map(|stats| stats.used_percent).collect();
+            assert_eq!(sliced, newest);
+        }
+        assert_eq!(history.samples().len(), 4);
+    }
+
+    #[test]
+    fn increasing_values_trend_rising() {
+        let mut history = MemoryHistory::new(10);
+        for value in 0..10 {
+            history.push(stats_with_used_percent(50.0 + value as f64));
+        }
+
+        match history.trend(5) {
+            Some(MemoryTrend::Rising(slope)) => assert!((slope - 1.0).abs() < 1e-9),
+            other => panic!("expected a rising trend, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decreasing_and_flat_values() {
+        let mut falling = MemoryHistory::new(5);
+        let mut flat = MemoryHistory::new(5);
+        for value in 0..5 {
+            falling.push(stats_with_used_percent(80.0 - 2.0 * value as f64));
+            flat.push(stats_with_used_percent(60.0));
+        }
+
+        assert!(matches!(falling.trend(2), Some(MemoryTrend::Falling(slope)) if (slope + 2.0).abs() < 1e-9));
+        assert_eq!(flat.trend(2), Some(MemoryTrend::Stable));
+    }
+
+    #[test]
+    fn too_few_samples_have_no_trend() {
+        let mut history = MemoryHistory::new(10);
+        history.push(stats_with_used_percent(10.0));
+        assert_eq!(history.trend(1), None);
+
+        history.push(stats_with_used_percent(20.0));
+        assert_eq!(history.trend(3), None);
+        assert!(history.trend(2).is_some());
+    }
+}
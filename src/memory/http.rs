@@ -0,0 +1,105 @@
+//! Minimal HTTP endpoint exposing memory statistics for scraping.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use super::format::format_prometheus;
+use super::get_memory_stats;
+
+/// `/health` reports unhealthy at or above this used percentage.
+const HEALTHY_USED_PERCENT: f64 = 90.0;
+
+/// Handle to a server started by `serve_metrics`; dropping it stops the server.
+pub struct HttpServer {
+    server: Arc<Server>,
+    stopping: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HttpServer {
+    /// Address the server is listening on, useful after binding port 0.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Stop accepting requests and wait for the server thread to exit.
+    pub fn stop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Serve memory statistics on `addr`, e.g. `"0.0.0.0:9100"`, from a background thread.
+///
+/// * `GET /metrics` - Prometheus text format
+/// * `GET /stats` - `MemoryStats` as JSON
+/// * `GET /health` - `200 OK` while used_percent is below 90, `503` otherwise
+pub fn serve_metrics(addr: &str) -> io::Result<HttpServer> {
+    let server = Arc::new(Server::http(addr).map_err(io::Error::other)?);
+    let stopping = Arc::new(AtomicBool::new(false));
+
+    let shared = Arc::clone(&server);
+    let shared_stopping = Arc::clone(&stopping);
+    let handle = thread::spawn(move || loop {
+        match shared.recv() {
+            Ok(request) => handle_request(request),
+            // recv fails when unblocked by stop() and on accept errors
+            Err(_) if shared_stopping.load(Ordering::SeqCst) => break,
+            Err(_) => {}
+        }
+    });
+
+    Ok(HttpServer {
+        server,
+        stopping,
+        handle: Some(handle),
+    })
+}
+
+fn handle_request(request: Request) {
+    if *request.method() != Method::Get {
+        let _ = request.respond(Response::from_string("Method Not Allowed\n").with_status_code(405));
+        return;
+    }
+
+    // Ignore any query string
+    let path = request.url().split('?').next().unwrap_or("");
+    let response = match path {
+        "/metrics" => {
+            Response::from_string(format_prometheus(&get_memory_stats())).with_header(content_type("text/plain; version=0.0.4"))
+        }
+        "/stats" => match serde_json::to_string(&get_memory_stats()) {
+            Ok(json) => Response::from_string(json).with_header(content_type("application/json")),
+            Err(_) => Response::from_string("Failed to serialize memory statistics\n").with_status_code(500),
+        },
+        "/health" => {
+            if get_memory_stats().used_percent < HEALTHY_USED_PERCENT {
+                Response::from_string("OK\n")
+            } else {
+                Response::from_string("Memory usage critical\n").with_status_code(503)
+            }
+        }
+        _ => Response::from_string("Not Found\n").with_status_code(404),
+    };
+
+    // The client may have gone away; nothing useful to do about it
+    let _ = request.respond(response);
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes("Content-Type", value).expect("static header is valid")
+}
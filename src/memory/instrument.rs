@@ -0,0 +1,44 @@
+//! Event macros for the `tracing` and `logging` features.
+//!
+//! Events go to `tracing` when it is enabled, otherwise to `log`. With both
+//! features, `tracing` forwards to `log` when no subscriber is installed, so
+//! every event is emitted once. With neither, the arguments are still
+//! type-checked through `format_args!`, but nothing is formatted or emitted.
+
+// Not every feature set and target uses every level
+#![allow(unused_macros)]
+
+macro_rules! emit_event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+        #[cfg(all(feature = "logging", not(feature = "tracing")))]
+        log::$level!($($arg)*);
+        #[cfg(not(any(feature = "logging", feature = "tracing")))]
+        let _ = format_args!($($arg)*);
+    };
+}
+
+macro_rules! debug_event {
+    ($($arg:tt)*) => {
+        emit_event!(debug, $($arg)*)
+    };
+}
+
+macro_rules! info_event {
+    ($($arg:tt)*) => {
+        emit_event!(info, $($arg)*)
+    };
+}
+
+macro_rules! warn_event {
+    ($($arg:tt)*) => {
+        emit_event!(warn, $($arg)*)
+    };
+}
+
+macro_rules! error_event {
+    ($($arg:tt)*) => {
+        emit_event!(error, $($arg)*)
+    };
+}
@@ -0,0 +1,49 @@
+//! Kernel same-page merging counters.
+
+/// Counters from `/sys/kernel/mm/ksm`.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KsmStats {
+    pub pages_shared: u64,   // Deduplicated pages in use
+    pub pages_sharing: u64,  // Additional mappings of those pages, i.e. pages saved
+    pub pages_unshared: u64, // Unique pages scanned repeatedly without a match
+    pub pages_volatile: u64, // Pages changing too fast to merge
+    pub full_scans: u64,     // Completed passes over all mergeable memory
+}
+
+impl KsmStats {
+    /// Estimated memory saved by merging, in bytes.
+    pub fn savings_bytes(&self, page_size: u64) -> u64 {
+        self.pages_sharing.saturating_mul(page_size)
+    }
+}
+
+/// Get KSM counters.
+///
+/// Returns `None` on kernels built without KSM and on non-Linux platforms.
+pub fn get_ksm_stats() -> Option<KsmStats> {
+    #[cfg(target_os = "linux")]
+    return get_ksm_stats_linux();
+
+    #[cfg(not(target_os = "linux"))]
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_ksm_stats_linux() -> Option<KsmStats> {
+    let read = |name: &str| -> Option<u64> {
+        std::fs::read_to_string(format!("/sys/kernel/mm/ksm/{}", name))
+            .ok()?
+            .trim()
+            .parse::<u64>()
+            .ok()
+    };
+
+    Some(KsmStats {
+        pages_shared: read("pages_shared")?,
+        pages_sharing: read("pages_sharing")?,
+        pages_unshared: read("pages_unshared").unwrap_or(0),
+        pages_volatile: read("pages_volatile").unwrap_or(0),
+        full_scans: read("full_scans").unwrap_or(0),
+    })
+}
@@ -0,0 +1,8 @@
+//! Linux-specific memory interfaces backed by `/proc` and `/sys`.
+
+pub mod maps;
+pub mod kpagecount;
+pub mod memfd_seal;
+pub mod nid_to_cpus;
+pub mod overcommit_accounting;
+pub mod transparent_hugepage;
@@ -0,0 +1,146 @@
+//! Physical page sharing counts via `/proc/kpagecount`.
+//!
+//! Reading `/proc/kpagecount` and the PFNs in `/proc/<pid>/pagemap`
+//! requires `CAP_SYS_ADMIN`; without it the kernel reports zero PFNs.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::os::unix::fs::FileExt;
+
+use super::super::MemoryError;
+use super::maps::get_memory_maps;
+
+const PAGEMAP_PRESENT: u64 = 1 << 63;
+const PAGEMAP_PFN_MASK: u64 = (1 << 55) - 1;
+// Cap on entries per pagemap or kpagecount read, 512 KiB of buffer
+const MAX_BATCH_ENTRIES: usize = 64 * 1024;
+
+/// Get the number of times physical page frame `pfn` is mapped.
+pub fn get_page_sharing(pfn: u64) -> Result<u64, MemoryError> {
+    let mut file = File::open("/proc/kpagecount")?;
+    read_u64_at(&mut file, pfn)
+}
+
+/// Map every resident page of `pid` to its share count (pfn -> share_count).
+pub fn get_process_page_sharing_distribution(pid: u32) -> Result<HashMap<u64, u64>, MemoryError> {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+    let maps = get_memory_maps(pid)?;
+
+    let pagemap = File::open(format!("/proc/{}/pagemap", pid))?;
+    let kpagecount = File::open("/proc/kpagecount")?;
+
+    let mut pfns = Vec::new();
+    for map in &maps {
+        let first_page = map.start / page_size;
+        let last_page = map.end / page_size;
+
+        // Read each mapping's entries in as few preads as the batch cap
+        // allows; unreadable ranges like [vsyscall] fail or come back short
+        let mut page = first_page;
+        while page < last_page {
+            let count = ((last_page - page) as usize).min(MAX_BATCH_ENTRIES);
+            let entries = match read_u64s_at(&pagemap, page, count) {
+                Ok(entries) => entries,
+                Err(_) => break,
+            };
+            let complete = entries.len() == count;
+
+            pfns.extend(
+                entries
+                    .into_iter()
+                    .filter(|entry| entry & PAGEMAP_PRESENT != 0)
+                    .map(|entry| entry & PAGEMAP_PFN_MASK)
+                    .filter(|&pfn| pfn != 0),
+            );
+
+            if !complete {
+                break;
+            }
+            page += count as u64;
+        }
+    }
+
+    pfns.sort_unstable();
+    pfns.dedup();
+
+    // Look up runs of consecutive PFNs with a single read each
+    let mut distribution = HashMap::with_capacity(pfns.len());
+    let mut run_start = 0;
+    while run_start < pfns.len() {
+        let mut run_end = run_start + 1;
+        while run_end < pfns.len()
+            && pfns[run_end] == pfns[run_end - 1] + 1
+            && run_end - run_start < MAX_BATCH_ENTRIES
+        {
+            run_end += 1;
+        }
+
+        let counts = read_u64s_at(&kpagecount, pfns[run_start], run_end - run_start)?;
+        if counts.len() < run_end - run_start {
+            return Err(MemoryError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "short read from /proc/kpagecount",
+            )));
+        }
+        for (&pfn, count) in pfns[run_start..run_end].iter().zip(counts) {
+            distribution.insert(pfn, count);
+        }
+
+        run_start = run_end;
+    }
+
+    Ok(distribution)
+}
+
+/// Mean share count across all resident pages of `pid`.
+///
+/// A value close to 1.0 means almost nothing is shared, so KSM or
+/// `MADV_MERGEABLE` could help if the process holds duplicate data.
+pub fn mean_page_sharing_ratio(pid: u32) -> Result<f64, MemoryError> {
+    let distribution = get_process_page_sharing_distribution(pid)?;
+
+    if distribution.is_empty() {
+        return Ok(0.0);
+    }
+
+    let total: u64 = distribution.values().sum();
+    Ok(total as f64 / distribution.len() as f64)
+}
+
+/// Read the `index`th native-endian u64 from a kernel page table file.
+fn read_u64_at(file: &mut File, index: u64) -> Result<u64, MemoryError> {
+    let mut buf = [0u8; 8];
+    file.seek(SeekFrom::Start(index * 8))?;
+    file.read_exact(&mut buf)?;
+    Ok(u64::from_ne_bytes(buf))
+}
+
+/// Read up to `count` native-endian u64s starting at the `index`th.
+///
+/// Stops early at end of file, so the result may be shorter than `count`.
+fn read_u64s_at(file: &File, index: u64, count: usize) -> Result<Vec<u64>, MemoryError> {
+    let mut buf = vec![0u8; count * 8];
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read_at(&mut buf[filled..], index * 8 + filled as u64) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) if filled > 0 => {
+                debug_event!("pagemap read stopped after {} bytes: {}", filled, err);
+                break;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(buf[..filled - filled % 8]
+        .chunks_exact(8)
+        .map(|chunk| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            u64::from_ne_bytes(bytes)
+        })
+        .collect())
+}
@@ -0,0 +1,72 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use super::super::MemoryError;
+
+/// A single mapping from `/proc/<pid>/maps`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryMapEntry {
+    pub start: u64,               // Start address of the mapping
+    pub end: u64,                 // End address of the mapping (exclusive)
+    pub permissions: String,      // Permission flags, e.g. "r-xp"
+    pub offset: u64,              // Offset into the backing file
+    pub device: String,           // Device as "major:minor"
+    pub inode: u64,               // Inode of the backing file, 0 for anonymous
+    pub pathname: Option<String>, // Backing file or pseudo-path like "[heap]"
+}
+
+impl MemoryMapEntry {
+    /// Size of the mapping in bytes.
+    pub fn size(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// Parse the memory maps of a process.
+pub fn get_memory_maps(pid: u32) -> Result<Vec<MemoryMapEntry>, MemoryError> {
+    let file = File::open(format!("/proc/{}/maps", pid))?;
+    let reader = BufReader::new(file);
+
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        match parse_maps_line(&line) {
+            Some(entry) => entries.push(entry),
+            None => return Err(MemoryError::Parse(format!("invalid maps line: {}", line))),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Parse one line of `/proc/<pid>/maps`.
+///
+/// Format: `start-end perms offset dev inode [pathname]`
+pub fn parse_maps_line(line: &str) -> Option<MemoryMapEntry> {
+    let mut parts = line.split_whitespace();
+
+    let range = parts.next()?;
+    let permissions = parts.next()?.to_string();
+    let offset = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let device = parts.next()?.to_string();
+    let inode = parts.next()?.parse::<u64>().ok()?;
+
+    // The pathname may contain spaces, so join whatever is left
+    let rest: Vec<&str> = parts.collect();
+    let pathname = if rest.is_empty() { None } else { Some(rest.join(" ")) };
+
+    let mut bounds = range.splitn(2, '-');
+    let start = u64::from_str_radix(bounds.next()?, 16).ok()?;
+    let end = u64::from_str_radix(bounds.next()?, 16).ok()?;
+
+    Some(MemoryMapEntry {
+        start,
+        end,
+        permissions,
+        offset,
+        device,
+        inode,
+        pathname,
+    })
+}
@@ -0,0 +1,130 @@
+//! Sealed `memfd` buffers for sharing read-only data across processes.
+//!
+//! Once sealed, neither this process nor any process the descriptor is
+//! passed to can modify or resize the contents, so all of them can map the
+//! same physical pages instead of keeping private copies.
+
+use std::cell::Cell;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::super::MemoryError;
+
+static SEALED_BUFFERS: AtomicU64 = AtomicU64::new(0);
+static SEALED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Totals across all live `SealedMemoryBuffer`s in this process.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemfdStats {
+    pub buffers: u64,      // Number of live sealed buffers
+    pub sealed_bytes: u64, // Total size of their contents in bytes
+}
+
+/// Get statistics about sealed memfd buffers.
+pub fn get_memfd_stats() -> MemfdStats {
+    MemfdStats {
+        buffers: SEALED_BUFFERS.load(Ordering::Relaxed),
+        sealed_bytes: SEALED_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+/// A `memfd` whose contents are sealed against writes and resizing.
+pub struct SealedMemoryBuffer {
+    file: File,
+    len: usize,
+    mapping: Cell<*mut libc::c_void>,
+}
+
+impl SealedMemoryBuffer {
+    /// Create a memfd named `name`, fill it with `data` and seal it.
+    pub fn new(name: &str, data: &[u8]) -> Result<Self, MemoryError> {
+        let c_name = CString::new(name)
+            .map_err(|_| MemoryError::Parse(String::from("memfd name contains a NUL byte")))?;
+
+        let fd = unsafe { libc::memfd_create(c_name.as_ptr(), libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING) };
+        if fd < 0 {
+            return Err(MemoryError::Io(io::Error::last_os_error()));
+        }
+
+        // Take ownership right away so the fd is closed on every error path
+        let mut file = unsafe { File::from_raw_fd(fd) };
+        file.write_all(data)?;
+
+        let seals = libc::F_SEAL_WRITE | libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_SEAL;
+        if unsafe { libc::fcntl(fd, libc::F_ADD_SEALS, seals) } < 0 {
+            return Err(MemoryError::Io(io::Error::last_os_error()));
+        }
+
+        SEALED_BUFFERS.fetch_add(1, Ordering::Relaxed);
+        SEALED_BYTES.fetch_add(data.len() as u64, Ordering::Relaxed);
+
+        Ok(SealedMemoryBuffer {
+            file,
+            len: data.len(),
+            mapping: Cell::new(ptr::null_mut()),
+        })
+    }
+
+    /// The underlying descriptor, suitable for passing to child processes.
+    pub fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+
+    /// Size of the sealed contents in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the buffer holds no data.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Map the contents read-only into this process.
+    ///
+    /// The mapping is created on first use and reused afterwards.
+    pub fn mmap_read_only(&self) -> Result<&[u8], MemoryError> {
+        if self.len == 0 {
+            return Ok(&[]);
+        }
+
+        if self.mapping.get().is_null() {
+            let addr = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    self.len,
+                    libc::PROT_READ,
+                    libc::MAP_SHARED,
+                    self.file.as_raw_fd(),
+                    0,
+                )
+            };
+            if addr == libc::MAP_FAILED {
+                return Err(MemoryError::Io(io::Error::last_os_error()));
+            }
+            self.mapping.set(addr);
+        }
+
+        Ok(unsafe { slice::from_raw_parts(self.mapping.get() as *const u8, self.len) })
+    }
+}
+
+impl Drop for SealedMemoryBuffer {
+    fn drop(&mut self) {
+        let addr = self.mapping.get();
+        if !addr.is_null() {
+            unsafe {
+                libc::munmap(addr, self.len);
+            }
+        }
+
+        SEALED_BUFFERS.fetch_sub(1, Ordering::Relaxed);
+        SEALED_BYTES.fetch_sub(self.len as u64, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,135 @@
+//! NUMA topology from `/sys/devices/system/node`.
+
+use std::fs;
+
+use super::super::MemoryError;
+
+pub use super::super::NumaNodeStats;
+
+const NODE_ROOT: &str = "/sys/devices/system/node";
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NumaNode {
+    pub id: u32,
+    pub cpus: Vec<u32>,      // CPUs local to this node
+    pub distances: Vec<u64>, // Distance to every node, indexed by position in `nodes`
+    pub memory_stats: NumaNodeStats,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NumaTopology {
+    pub nodes: Vec<NumaNode>,
+}
+
+impl NumaTopology {
+    /// Find the node a CPU belongs to.
+    pub fn local_node_for_cpu(&self, cpu: u32) -> Option<u32> {
+        self.nodes
+            .iter()
+            .find(|node| node.cpus.contains(&cpu))
+            .map(|node| node.id)
+    }
+
+    /// Find the node with the most free memory, the natural target when
+    /// pinning a new memory-hungry thread.
+    pub fn node_with_most_free_memory(&self) -> Option<u32> {
+        self.nodes
+            .iter()
+            .max_by_key(|node| node.memory_stats.free)
+            .map(|node| node.id)
+    }
+}
+
+/// Build the NUMA topology of this machine.
+pub fn build_numa_topology() -> Result<NumaTopology, MemoryError> {
+    let mut nodes = Vec::new();
+
+    for entry in fs::read_dir(NODE_ROOT)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        let id = match name.strip_prefix("node").and_then(|id| id.parse::<u32>().ok()) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let cpulist = fs::read_to_string(entry.path().join("cpulist"))?;
+        let memory_stats = read_node_meminfo(id)?;
+
+        nodes.push(NumaNode {
+            id,
+            cpus: parse_cpulist(&cpulist)?,
+            distances: memory_stats.distance_map.iter().map(|&distance| distance as u64).collect(),
+            memory_stats,
+        });
+    }
+
+    nodes.sort_by_key(|node| node.id);
+
+    Ok(NumaTopology { nodes })
+}
+
+/// Read `node<id>/meminfo` and `node<id>/distance`.
+pub fn read_node_meminfo(id: u32) -> Result<NumaNodeStats, MemoryError> {
+    let meminfo = fs::read_to_string(format!("{}/node{}/meminfo", NODE_ROOT, id))?;
+    let mut stats = NumaNodeStats {
+        node_id: id,
+        distance_map: read_node_distances(id)?,
+        ..NumaNodeStats::default()
+    };
+
+    // Lines look like "Node 0 MemTotal:       16303572 kB"
+    for line in meminfo.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 {
+            continue;
+        }
+
+        let value = match parts[3].parse::<u64>() {
+            Ok(value) => value * 1024, // Convert KB to bytes
+            Err(_) => continue,
+        };
+
+        match parts[2] {
+            "MemTotal:" => stats.total = value,
+            "MemFree:" => stats.free = value,
+            _ => {}
+        }
+    }
+
+    stats.used = stats.total.saturating_sub(stats.free);
+    Ok(stats)
+}
+
+/// Read `node<id>/distance`, the relative access cost to every node.
+pub fn read_node_distances(id: u32) -> Result<Vec<u32>, MemoryError> {
+    let distance = fs::read_to_string(format!("{}/node{}/distance", NODE_ROOT, id))?;
+
+    distance
+        .split_whitespace()
+        .map(|value| value.parse::<u32>())
+        .collect::<Result<Vec<u32>, _>>()
+        .map_err(|_| MemoryError::Parse(format!("invalid distance for node {}: {}", id, distance.trim())))
+}
+
+/// Parse a kernel CPU list such as `0-3,8-11`.
+pub fn parse_cpulist(list: &str) -> Result<Vec<u32>, MemoryError> {
+    let mut cpus = Vec::new();
+    let invalid = || MemoryError::Parse(format!("invalid cpulist: {}", list.trim()));
+
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        let mut bounds = range.splitn(2, '-');
+        let start = bounds.next().and_then(|v| v.parse::<u32>().ok()).ok_or_else(invalid)?;
+        let end = match bounds.next() {
+            Some(end) => end.parse::<u32>().map_err(|_| invalid())?,
+            None => start,
+        };
+
+        cpus.extend(start..=end);
+    }
+
+    Ok(cpus)
+}
@@ -0,0 +1,51 @@
+//! Virtual memory commit accounting from `/proc/meminfo`.
+//!
+//! With `vm.overcommit_memory = 2` the kernel refuses allocations once
+//! `Committed_AS` would exceed `CommitLimit`, even if RAM is free.
+
+use std::fs;
+
+use super::super::{read_meminfo, MemoryError};
+
+/// Total virtual memory currently promised to processes, in bytes.
+pub fn get_committed_as() -> Result<u64, MemoryError> {
+    read_field("Committed_AS")
+}
+
+/// The system's overcommit budget, in bytes.
+pub fn get_commit_limit() -> Result<u64, MemoryError> {
+    read_field("CommitLimit")
+}
+
+/// Bytes that can still be committed; negative means already over the limit.
+pub fn overcommit_headroom_bytes() -> Result<i64, MemoryError> {
+    let mem_info = read_meminfo();
+    let committed = field(&mem_info, "Committed_AS")?;
+    let limit = field(&mem_info, "CommitLimit")?;
+
+    Ok(limit as i64 - committed as i64)
+}
+
+/// Whether an allocation of `bytes` would fit in the commit budget.
+///
+/// The budget is only enforced in strict mode (`vm.overcommit_memory = 2`);
+/// in the heuristic and always-overcommit modes this returns `true`.
+pub fn will_allocation_succeed(bytes: u64) -> Result<bool, MemoryError> {
+    let mode = fs::read_to_string("/proc/sys/vm/overcommit_memory")?;
+    if mode.trim() != "2" {
+        return Ok(true);
+    }
+
+    Ok(overcommit_headroom_bytes()? >= bytes as i64)
+}
+
+fn read_field(key: &str) -> Result<u64, MemoryError> {
+    field(&read_meminfo(), key)
+}
+
+fn field(mem_info: &std::collections::HashMap<String, u64>, key: &str) -> Result<u64, MemoryError> {
+    mem_info
+        .get(key)
+        .cloned()
+        .ok_or_else(|| MemoryError::Parse(format!("{} missing from /proc/meminfo", key)))
+}
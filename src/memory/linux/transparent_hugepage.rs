@@ -0,0 +1,159 @@
+//! Per-range transparent huge page hints.
+//!
+//! These are the fine-grained counterparts to the system-wide THP mode in
+//! `/sys/kernel/mm/transparent_hugepage/enabled`.
+
+use std::ffi::CStr;
+use std::fs;
+use std::io;
+
+use super::super::util::align_to_page;
+use super::super::{read_meminfo, MemoryError};
+
+// Not exported by libc for every Linux target yet
+const MADV_COLLAPSE: libc::c_int = 25;
+
+const DEFAULT_HUGEPAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// System-wide THP mode from `/sys/kernel/mm/transparent_hugepage/enabled`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ThpMode {
+    Always,  // Huge pages wherever possible
+    Madvise, // Only for ranges marked MADV_HUGEPAGE
+    Never,   // THP disabled
+    Unknown, // Kernel without THP or unreadable sysfs
+}
+
+/// Transparent and hugetlbfs huge page usage.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThpStats {
+    pub mode: ThpMode,
+    pub anon_huge_pages: u64, // Anonymous memory backed by THP in bytes
+    pub anon_pages: u64,      // All anonymous memory in bytes
+    pub hugepages_total: u64, // Preallocated hugetlbfs pages
+    pub hugepages_free: u64,  // Unused hugetlbfs pages
+    pub hugepage_size: u64,   // Default hugetlbfs page size in bytes
+}
+
+impl ThpStats {
+    /// Percentage of anonymous memory backed by transparent huge pages.
+    pub fn thp_coverage_percent(&self) -> f64 {
+        if self.anon_pages == 0 {
+            return 0.0;
+        }
+
+        (self.anon_huge_pages as f64 / self.anon_pages as f64 * 100.0).min(100.0)
+    }
+}
+
+/// Get the THP mode and huge page counters from sysfs and /proc/meminfo.
+pub fn get_thp_stats() -> ThpStats {
+    let mem_info = read_meminfo();
+    let field = |key: &str| mem_info.get(key).cloned().unwrap_or(0);
+
+    ThpStats {
+        mode: thp_mode(),
+        anon_huge_pages: field("AnonHugePages"),
+        anon_pages: field("AnonPages"),
+        hugepages_total: field("HugePages_Total"),
+        hugepages_free: field("HugePages_Free"),
+        hugepage_size: field("Hugepagesize"),
+    }
+}
+
+/// The bracketed entry of "always [madvise] never" is the active mode.
+fn thp_mode() -> ThpMode {
+    let enabled = match fs::read_to_string("/sys/kernel/mm/transparent_hugepage/enabled") {
+        Ok(enabled) => enabled,
+        Err(_) => return ThpMode::Unknown,
+    };
+
+    let active = enabled
+        .split_whitespace()
+        .find(|mode| mode.starts_with('['))
+        .map(|mode| mode.trim_matches(|c| c == '[' || c == ']'));
+
+    match active {
+        Some("always") => ThpMode::Always,
+        Some("madvise") => ThpMode::Madvise,
+        Some("never") => ThpMode::Never,
+        _ => ThpMode::Unknown,
+    }
+}
+
+/// Ask the kernel to back `[ptr, ptr + len)` with huge pages.
+pub fn madvise_hugepage_range(ptr: *mut u8, len: usize) -> Result<(), MemoryError> {
+    madvise_range(ptr, len, libc::MADV_HUGEPAGE)
+}
+
+/// Ask the kernel never to back `[ptr, ptr + len)` with huge pages.
+pub fn madvise_nohugepage_range(ptr: *mut u8, len: usize) -> Result<(), MemoryError> {
+    madvise_range(ptr, len, libc::MADV_NOHUGEPAGE)
+}
+
+/// Synchronously collapse `[ptr, ptr + len)` into huge pages (Linux 6.1+).
+///
+/// Returns the number of huge pages the range covers, i.e. the number
+/// collapsed when the call succeeds.
+pub fn madvise_collapse(ptr: *mut u8, len: usize) -> Result<u64, MemoryError> {
+    if !is_madv_collapse_supported() {
+        return Err(MemoryError::Unsupported("MADV_COLLAPSE requires Linux 6.1 or newer"));
+    }
+
+    madvise_range(ptr, len, MADV_COLLAPSE)?;
+
+    // Only huge-page-aligned chunks fully inside the range can be collapsed
+    let hugepage = hugepage_size();
+    let start = (ptr as usize).div_ceil(hugepage) * hugepage;
+    let end = (ptr as usize + len) / hugepage * hugepage;
+
+    Ok(if end > start { ((end - start) / hugepage) as u64 } else { 0 })
+}
+
+/// Whether the running kernel supports `MADV_COLLAPSE`.
+pub fn is_madv_collapse_supported() -> bool {
+    match kernel_version() {
+        Some((major, minor)) => (major, minor) >= (6, 1),
+        None => false,
+    }
+}
+
+fn madvise_range(ptr: *mut u8, len: usize, advice: libc::c_int) -> Result<(), MemoryError> {
+    let (base, aligned_len) = align_to_page(ptr as usize, len);
+    if aligned_len == 0 {
+        return Ok(());
+    }
+
+    let ret = unsafe { libc::madvise(base as *mut libc::c_void, aligned_len, advice) };
+    if ret != 0 {
+        return Err(MemoryError::Io(io::Error::last_os_error()));
+    }
+
+    Ok(())
+}
+
+/// PMD huge page size, normally 2 MiB on x86_64.
+fn hugepage_size() -> usize {
+    fs::read_to_string("/sys/kernel/mm/transparent_hugepage/hpage_pmd_size")
+        .ok()
+        .and_then(|size| size.trim().parse::<usize>().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(DEFAULT_HUGEPAGE_SIZE)
+}
+
+/// Kernel (major, minor) version from `uname`.
+fn kernel_version() -> Option<(u32, u32)> {
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return None;
+    }
+
+    let release = unsafe { CStr::from_ptr(uts.release.as_ptr()) }.to_string_lossy();
+    let mut parts = release.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse::<u32>().ok()?;
+    let minor = parts.next()?.parse::<u32>().ok()?;
+
+    Some((major, minor))
+}
@@ -0,0 +1,194 @@
+//! Installed memory modules from the IOKit device tree.
+
+use std::ffi::CStr;
+use std::io;
+use std::os::raw::{c_char, c_void};
+
+type KernReturn = i32;
+type IoObject = u32;
+type CFTypeRef = *const c_void;
+type CFIndex = isize;
+
+const KERN_SUCCESS: KernReturn = 0;
+const IO_MASTER_PORT_DEFAULT: u32 = 0;
+const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+    fn IOServiceGetMatchingServices(master_port: u32, matching: *mut c_void, existing: *mut IoObject) -> KernReturn;
+    fn IOIteratorNext(iterator: IoObject) -> IoObject;
+    fn IOObjectRelease(object: IoObject) -> KernReturn;
+    fn IORegistryEntryGetChildIterator(entry: IoObject, plane: *const c_char, iterator: *mut IoObject) -> KernReturn;
+    fn IORegistryEntryGetName(entry: IoObject, name: *mut c_char) -> KernReturn;
+    fn IORegistryEntryCreateCFProperty(entry: IoObject, key: CFTypeRef, allocator: CFTypeRef, options: u32) -> CFTypeRef;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFStringCreateWithCString(allocator: CFTypeRef, string: *const c_char, encoding: u32) -> CFTypeRef;
+    fn CFGetTypeID(object: CFTypeRef) -> usize;
+    fn CFDataGetTypeID() -> usize;
+    fn CFDataGetLength(data: CFTypeRef) -> CFIndex;
+    fn CFDataGetBytePtr(data: CFTypeRef) -> *const u8;
+    fn CFRelease(object: CFTypeRef);
+}
+
+/// One populated memory slot.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DimmInfo {
+    pub slot: String,        // Slot name, e.g. "DIMM0/BANK 0"
+    pub size_bytes: u64,     // Module size in bytes
+    pub speed_mhz: u32,      // Rated speed, 0 if not reported
+    pub memory_type: String, // e.g. "DDR4", empty if not reported
+}
+
+/// Get the installed memory modules.
+///
+/// Finds the `IOPlatformExpertDevice` and reads the `memory` node below it
+/// in the device tree. Apple silicon Macs have unified memory and no such
+/// node, so the list is empty there.
+pub fn get_physical_memory_info() -> io::Result<Vec<DimmInfo>> {
+    let platform = platform_expert()?;
+    let memory = child_named(platform, b"memory\0");
+    unsafe {
+        IOObjectRelease(platform);
+    }
+    let memory = match memory {
+        Some(memory) => memory,
+        None => return Ok(Vec::new()),
+    };
+
+    let slots = strings_property(memory, b"slot-names\0");
+    let types = strings_property(memory, b"dimm-types\0");
+    let speeds = strings_property(memory, b"dimm-speeds\0");
+    // One little-endian 64-bit size per slot
+    let sizes: Vec<u64> = data_property(memory, b"reg\0")
+        .chunks_exact(8)
+        .map(|chunk| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            u64::from_le_bytes(bytes)
+        })
+        .collect();
+    unsafe {
+        IOObjectRelease(memory);
+    }
+
+    let dimms = slots
+        .iter()
+        .enumerate()
+        .map(|(index, slot)| DimmInfo {
+            slot: slot.clone(),
+            size_bytes: sizes.get(index).copied().unwrap_or(0),
+            speed_mhz: speeds.get(index).map(|speed| parse_speed_mhz(speed)).unwrap_or(0),
+            memory_type: types.get(index).cloned().unwrap_or_default(),
+        })
+        // Empty slots are listed with a size of zero; keep everything if sizes are missing
+        .filter(|dimm| sizes.is_empty() || dimm.size_bytes > 0)
+        .collect();
+    Ok(dimms)
+}
+
+/// The first `IOPlatformExpertDevice`, which the caller must release.
+fn platform_expert() -> io::Result<IoObject> {
+    let mut iterator: IoObject = 0;
+    let result = unsafe {
+        // The matching dictionary is consumed by IOServiceGetMatchingServices
+        let matching = IOServiceMatching(b"IOPlatformExpertDevice\0".as_ptr() as *const c_char);
+        IOServiceGetMatchingServices(IO_MASTER_PORT_DEFAULT, matching, &mut iterator)
+    };
+    if result != KERN_SUCCESS {
+        return Err(io::Error::other(format!("IOServiceGetMatchingServices failed with {:#x}", result)));
+    }
+
+    let platform = unsafe { IOIteratorNext(iterator) };
+    unsafe {
+        IOObjectRelease(iterator);
+    }
+    if platform == 0 {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "no IOPlatformExpertDevice"));
+    }
+    Ok(platform)
+}
+
+/// The device tree child of `entry` called `name`, which the caller must release.
+fn child_named(entry: IoObject, name: &[u8]) -> Option<IoObject> {
+    let mut iterator: IoObject = 0;
+    let plane = b"IODeviceTree\0".as_ptr() as *const c_char;
+    if unsafe { IORegistryEntryGetChildIterator(entry, plane, &mut iterator) } != KERN_SUCCESS {
+        return None;
+    }
+
+    let wanted = CStr::from_bytes_with_nul(name).ok()?;
+    let mut found = None;
+    loop {
+        let child = unsafe { IOIteratorNext(iterator) };
+        if child == 0 {
+            break;
+        }
+
+        // io_name_t is a fixed 128-byte buffer
+        let mut child_name = [0 as c_char; 128];
+        let matches = unsafe {
+            IORegistryEntryGetName(child, child_name.as_mut_ptr()) == KERN_SUCCESS
+                && CStr::from_ptr(child_name.as_ptr()) == wanted
+        };
+        if matches {
+            found = Some(child);
+            break;
+        }
+        unsafe {
+            IOObjectRelease(child);
+        }
+    }
+
+    unsafe {
+        IOObjectRelease(iterator);
+    }
+    found
+}
+
+/// Bytes of the data property `key` of `entry`, empty if it is missing.
+fn data_property(entry: IoObject, key: &[u8]) -> Vec<u8> {
+    unsafe {
+        let key = CFStringCreateWithCString(std::ptr::null(), key.as_ptr() as *const c_char, CF_STRING_ENCODING_UTF8);
+        if key.is_null() {
+            return Vec::new();
+        }
+        let value = IORegistryEntryCreateCFProperty(entry, key, std::ptr::null(), 0);
+        CFRelease(key);
+        if value.is_null() {
+            return Vec::new();
+        }
+
+        let length = if CFGetTypeID(value) == CFDataGetTypeID() {
+            CFDataGetLength(value).max(0) as usize
+        } else {
+            0
+        };
+        let bytes = if length > 0 {
+            std::slice::from_raw_parts(CFDataGetBytePtr(value), length).to_vec()
+        } else {
+            Vec::new()
+        };
+        CFRelease(value);
+        bytes
+    }
+}
+
+/// A data property holding NUL-separated strings, one per slot.
+fn strings_property(entry: IoObject, key: &[u8]) -> Vec<String> {
+    let bytes = data_property(entry, key);
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(&bytes);
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+    bytes.split(|&byte| byte == 0).map(|part| String::from_utf8_lossy(part).into_owned()).collect()
+}
+
+/// Parse a speed such as "2667 MHz".
+fn parse_speed_mhz(speed: &str) -> u32 {
+    speed.split_whitespace().next().and_then(|value| value.parse().ok()).unwrap_or(0)
+}
@@ -0,0 +1,134 @@
+//! Memory pressure notifications from Grand Central Dispatch.
+
+use std::os::raw::{c_long, c_ulong, c_void};
+
+// libdispatch is part of libSystem, so no extra link flags are needed
+#[repr(C)]
+struct DispatchSourceType {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    static _dispatch_source_type_memorypressure: DispatchSourceType;
+
+    fn dispatch_get_global_queue(identifier: c_long, flags: c_ulong) -> *mut c_void;
+    fn dispatch_source_create(
+        source_type: *const DispatchSourceType,
+        handle: usize,
+        mask: c_ulong,
+        queue: *mut c_void,
+    ) -> *mut c_void;
+    fn dispatch_set_context(object: *mut c_void, context: *mut c_void);
+    fn dispatch_source_set_event_handler_f(source: *mut c_void, handler: extern "C" fn(*mut c_void));
+    fn dispatch_source_set_cancel_handler_f(source: *mut c_void, handler: extern "C" fn(*mut c_void));
+    fn dispatch_source_get_data(source: *mut c_void) -> c_ulong;
+    fn dispatch_resume(object: *mut c_void);
+    fn dispatch_source_cancel(source: *mut c_void);
+    fn dispatch_release(object: *mut c_void);
+}
+
+const DISPATCH_MEMORYPRESSURE_NORMAL: c_ulong = 0x1;
+const DISPATCH_MEMORYPRESSURE_WARN: c_ulong = 0x2;
+const DISPATCH_MEMORYPRESSURE_CRITICAL: c_ulong = 0x4;
+
+const DISPATCH_QUEUE_PRIORITY_DEFAULT: c_long = 0;
+
+/// Pressure level reported by the system; the discriminants match the
+/// `DISPATCH_MEMORYPRESSURE_*` masks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MacosPressureLevel {
+    Normal = 1,
+    Warning = 2,
+    Critical = 4,
+}
+
+/// Context handed to the dispatch handlers, which only receive this pointer.
+struct HandlerContext {
+    source: *mut c_void,
+    callback: Box<dyn Fn(MacosPressureLevel) + Send>,
+}
+
+/// Registered memory pressure dispatch source.
+///
+/// The callback runs on a global dispatch queue, one event at a time.
+pub struct MacosPressureSource {
+    source: *mut c_void,
+}
+
+// dispatch objects are thread-safe, and the callback itself is Send
+unsafe impl Send for MacosPressureSource {}
+
+impl MacosPressureSource {
+    /// Start delivering pressure transitions to `callback`.
+    ///
+    /// Returns `None` if the dispatch source could not be created.
+    pub fn register<F>(callback: F) -> Option<Self>
+    where
+        F: Fn(MacosPressureLevel) + Send + 'static,
+    {
+        let mask = DISPATCH_MEMORYPRESSURE_NORMAL | DISPATCH_MEMORYPRESSURE_WARN | DISPATCH_MEMORYPRESSURE_CRITICAL;
+
+        unsafe {
+            let queue = dispatch_get_global_queue(DISPATCH_QUEUE_PRIORITY_DEFAULT, 0);
+            let source = dispatch_source_create(&_dispatch_source_type_memorypressure, 0, mask, queue);
+            if source.is_null() {
+                return None;
+            }
+
+            let context = Box::new(HandlerContext {
+                source,
+                callback: Box::new(callback),
+            });
+            dispatch_set_context(source, Box::into_raw(context) as *mut c_void);
+            dispatch_source_set_event_handler_f(source, handle_event);
+            dispatch_source_set_cancel_handler_f(source, handle_cancel);
+            dispatch_resume(source);
+
+            Some(MacosPressureSource { source })
+        }
+    }
+
+    /// Cancel the dispatch source; no new callbacks start after this returns.
+    pub fn unregister(mut self) {
+        self.cancel();
+    }
+
+    fn cancel(&mut self) {
+        if self.source.is_null() {
+            return;
+        }
+
+        // The cancel handler frees the callback once any running event finishes
+        unsafe {
+            dispatch_source_cancel(self.source);
+            dispatch_release(self.source);
+        }
+        self.source = std::ptr::null_mut();
+    }
+}
+
+impl Drop for MacosPressureSource {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+extern "C" fn handle_event(context: *mut c_void) {
+    let context = unsafe { &*(context as *const HandlerContext) };
+    let data = unsafe { dispatch_source_get_data(context.source) };
+
+    let level = if data & DISPATCH_MEMORYPRESSURE_CRITICAL != 0 {
+        MacosPressureLevel::Critical
+    } else if data & DISPATCH_MEMORYPRESSURE_WARN != 0 {
+        MacosPressureLevel::Warning
+    } else {
+        MacosPressureLevel::Normal
+    };
+
+    (context.callback)(level);
+}
+
+extern "C" fn handle_cancel(context: *mut c_void) {
+    drop(unsafe { Box::from_raw(context as *mut HandlerContext) });
+}
@@ -0,0 +1,121 @@
+//! `MemoryStats`, the snapshot type, and the methods on it that need only
+//! `core` and `alloc`, so they are shared with the `no_std` build.
+
+use alloc::string::String;
+use core::cmp::Ordering;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryStats {
+    pub total: u64,       // Total physical memory in bytes
+    pub free: u64,        // Free physical memory in bytes
+    pub available: u64,   // Available memory in bytes
+    pub used: u64,        // Used physical memory in bytes
+    pub used_percent: f64, // Used memory as a percentage
+    pub buffers: Option<u64>, // Memory used for buffers (Linux specific)
+    pub cached: Option<u64>,  // Memory used for cache (Linux specific)
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub speculative: Option<u64>, // Speculatively cached pages counted as free (macOS specific)
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub fragmentation_score: Option<f64>, // Share of free pages in order-0 blocks, only with `MemoryStatsConfig::include_fragmentation` (Linux specific)
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub swap_total: Option<u64>, // Total swap in bytes, only with `MemoryStatsConfig::include_swap`
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub swap_free: Option<u64>,  // Free swap in bytes, only with `MemoryStatsConfig::include_swap`
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub dirty: Option<u64>,      // Modified pages waiting to be written back (Linux specific)
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub writeback: Option<u64>,  // Pages being written back right now (Linux specific)
+    pub timestamp: String,    // ISO8601 timestamp, epoch nanoseconds without the `timestamps` feature
+}
+
+impl MemoryStats {
+    /// Whether used_percent has reached `threshold`.
+    pub fn is_critical(&self, threshold: f64) -> bool {
+        self.used_percent >= threshold
+    }
+
+    /// Whether available memory is below `free_threshold_bytes`.
+    pub fn is_low_memory(&self, free_threshold_bytes: u64) -> bool {
+        self.available < free_threshold_bytes
+    }
+
+    /// Dirty and writeback pages as a percentage of total memory.
+    ///
+    /// `None` where the platform does not report dirty pages.
+    pub fn dirty_percent(&self) -> Option<f64> {
+        if self.total == 0 {
+            return None;
+        }
+        let dirty = self.dirty?.saturating_add(self.writeback.unwrap_or(0));
+        Some(dirty as f64 / self.total as f64 * 100.0)
+    }
+}
+
+/// Snapshots order by how full memory was, then by when they were taken.
+///
+/// used_percent is compared with `total_cmp`, so the order is total even
+/// with NaN; timestamps are fixed-width and sort chronologically as strings.
+/// The remaining fields only break exact ties, which keeps `==` field-wise.
+impl Ord for MemoryStats {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn cmp_score(a: Option<f64>, b: Option<f64>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                _ => a.is_some().cmp(&b.is_some()),
+            }
+        }
+
+        let counts = |stats: &MemoryStats| {
+            (
+                stats.total,
+                stats.free,
+                stats.available,
+                stats.used,
+                stats.buffers,
+                stats.cached,
+                stats.speculative,
+                stats.swap_total,
+                stats.swap_free,
+                stats.dirty,
+                stats.writeback,
+            )
+        };
+
+        self.used_percent
+            .total_cmp(&other.used_percent)
+            .then_with(|| self.timestamp.cmp(&other.timestamp))
+            .then_with(|| counts(self).cmp(&counts(other)))
+            .then_with(|| cmp_score(self.fragmentation_score, other.fragmentation_score))
+    }
+}
+
+impl PartialOrd for MemoryStats {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for MemoryStats {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MemoryStats {}
+
+/// Parse a snapshot timestamp into milliseconds since the epoch.
+#[cfg(all(feature = "timestamps", not(feature = "no_std")))]
+pub(crate) fn parse_timestamp_ms(timestamp: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|datetime| datetime.timestamp_millis())
+}
+
+/// Parse a snapshot timestamp into milliseconds since the epoch.
+#[cfg(any(not(feature = "timestamps"), feature = "no_std"))]
+// Without std only `format_influx` reads timestamps
+#[cfg_attr(all(feature = "no_std", not(feature = "influx")), allow(dead_code))]
+pub(crate) fn parse_timestamp_ms(timestamp: &str) -> Option<i64> {
+    timestamp.parse::<u128>().ok().map(|nanos| (nanos / 1_000_000) as i64)
+}
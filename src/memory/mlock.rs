@@ -0,0 +1,140 @@
+//! Page locking with `mlock`, keeping track of what this process locked.
+
+use std::io;
+use std::sync::Mutex;
+
+/// A range passed to `lock_region`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LockedRegion {
+    pub addr: usize, // Start address as given to `lock_region`
+    pub len: usize,  // Length in bytes as given to `lock_region`
+}
+
+impl LockedRegion {
+    fn end(&self) -> usize {
+        self.addr.saturating_add(self.len)
+    }
+}
+
+// Regions locked through this module and not yet unlocked
+static LOCKED_REGIONS: Mutex<Vec<LockedRegion>> = Mutex::new(Vec::new());
+
+/// Lock `[ptr, ptr + len)` into RAM so it cannot be swapped out.
+///
+/// The kernel locks whole pages, so neighbouring bytes on the same pages are
+/// locked too. Fails, typically with `ENOMEM` or `EPERM`, when the lock would
+/// exceed `RLIMIT_MEMLOCK`.
+pub fn lock_region(ptr: *mut u8, len: usize) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        if unsafe { libc::mlock(ptr as *const libc::c_void, len) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        regions().push(LockedRegion { addr: ptr as usize, len });
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (ptr, len);
+        Err(io::Error::new(io::ErrorKind::Unsupported, "mlock is not available on this platform"))
+    }
+}
+
+/// Unlock `[ptr, ptr + len)`, which may cover all or part of locked regions.
+pub fn unlock_region(ptr: *mut u8, len: usize) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        if unsafe { libc::munlock(ptr as *const libc::c_void, len) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let unlocked = LockedRegion { addr: ptr as usize, len };
+        let mut regions = regions();
+        let remaining = regions.iter().flat_map(|region| subtract(*region, unlocked)).collect();
+        *regions = remaining;
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (ptr, len);
+        Err(io::Error::new(io::ErrorKind::Unsupported, "munlock is not available on this platform"))
+    }
+}
+
+/// Lock every current and future page of the process with `mlockall`.
+pub fn lock_all_memory() -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        if unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    Err(io::Error::new(io::ErrorKind::Unsupported, "mlockall is not available on this platform"))
+}
+
+/// Undo `lock_all_memory` and every `lock_region` with `munlockall`.
+pub fn unlock_all_memory() -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        if unsafe { libc::munlockall() } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        regions().clear();
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    Err(io::Error::new(io::ErrorKind::Unsupported, "munlockall is not available on this platform"))
+}
+
+/// Regions currently locked through `lock_region`.
+pub fn locked_regions() -> Vec<LockedRegion> {
+    regions().clone()
+}
+
+/// Bytes currently locked through `lock_region`.
+///
+/// Overlapping regions are counted once. Pages locked by `lock_all_memory`
+/// are not included.
+pub fn locked_memory_bytes() -> u64 {
+    let mut regions = locked_regions();
+    regions.sort_by_key(|region| region.addr);
+
+    let mut total: u64 = 0;
+    let mut covered_to = 0;
+    for region in regions {
+        let start = region.addr.max(covered_to);
+        if region.end() > start {
+            total += (region.end() - start) as u64;
+            covered_to = region.end();
+        }
+    }
+    total
+}
+
+fn regions() -> std::sync::MutexGuard<'static, Vec<LockedRegion>> {
+    LOCKED_REGIONS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// What is left of `region` after removing `unlocked`: up to two pieces.
+#[cfg(unix)]
+fn subtract(region: LockedRegion, unlocked: LockedRegion) -> impl Iterator<Item = LockedRegion> {
+    let before = LockedRegion {
+        addr: region.addr,
+        len: unlocked.addr.clamp(region.addr, region.end()) - region.addr,
+    };
+    let after_start = unlocked.end().clamp(region.addr, region.end());
+    let after = LockedRegion {
+        addr: after_start,
+        len: region.end() - after_start,
+    };
+    IntoIterator::into_iter([before, after]).filter(|piece| piece.len > 0)
+}
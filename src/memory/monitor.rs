@@ -0,0 +1,282 @@
+//! Background polling of `MemoryStats`.
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::healing::{HealingEngine, HealingLog};
+use super::history::MemoryHistory;
+use super::reserve::EmergencyReserve;
+use super::{get_memory_stats, MemoryStats};
+#[cfg(target_os = "linux")]
+use super::{VmStatDelta, VmStatPoller};
+
+/// Thresholds and callbacks for `MemoryMonitor::with_alerts`.
+pub struct MemoryAlertConfig {
+    pub used_percent_warn: f64, // Fire `on_warn` when used_percent rises past this
+    pub used_percent_crit: f64, // Fire `on_crit` when used_percent rises past this
+    pub on_warn: Box<dyn Fn(&MemoryStats) + Send>,
+    pub on_crit: Box<dyn Fn(&MemoryStats) + Send>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum AlertLevel {
+    Normal,
+    Warn,
+    Crit,
+}
+
+/// Edge detection run on the polling thread; callbacks run on the alert thread.
+struct AlertTrigger {
+    warn: f64,
+    crit: f64,
+    level: AlertLevel,
+    sender: Sender<(AlertLevel, MemoryStats)>,
+}
+
+impl AlertTrigger {
+    fn observe(&mut self, stats: &MemoryStats) {
+        let level = if stats.used_percent >= self.crit {
+            AlertLevel::Crit
+        } else if stats.used_percent >= self.warn {
+            AlertLevel::Warn
+        } else {
+            AlertLevel::Normal
+        };
+
+        // Only fire when crossing upwards; staying above a threshold is silent
+        if level > self.level {
+            let _ = self.sender.send((level, stats.clone()));
+        }
+        self.level = level;
+    }
+}
+
+/// Healing run on the polling thread when used_percent rises past a threshold.
+struct HealingTrigger {
+    engine: HealingEngine,
+    used_percent: f64,
+    above: bool,
+    log: Option<Arc<Mutex<HealingLog>>>,
+}
+
+impl HealingTrigger {
+    fn observe(&mut self, stats: &MemoryStats) {
+        let above = stats.used_percent >= self.used_percent;
+
+        // Heal once per excursion rather than on every sample above the threshold
+        if above && !self.above {
+            match &self.log {
+                Some(log) => {
+                    self.engine.heal_logged(log);
+                }
+                None => {
+                    self.engine.heal();
+                }
+            }
+        }
+        self.above = above;
+    }
+}
+
+/// used_percent above which an attached `EmergencyReserve` is released.
+pub const EMERGENCY_RELEASE_PERCENT: f64 = 95.0;
+
+/// Polls `get_memory_stats()` on a background thread and caches the result.
+pub struct MemoryMonitor {
+    latest: Arc<Mutex<MemoryStats>>,
+    alerts: Arc<Mutex<Option<AlertTrigger>>>,
+    history: Arc<Mutex<Option<MemoryHistory>>>,
+    healing: Arc<Mutex<Option<HealingTrigger>>>,
+    emergency: Arc<Mutex<Option<Arc<Mutex<EmergencyReserve>>>>>,
+    #[cfg(target_os = "linux")]
+    vmstat: Arc<Mutex<Option<(VmStatPoller, VmStatDelta)>>>,
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+    alert_handle: Option<JoinHandle<()>>,
+}
+
+impl MemoryMonitor {
+    /// Take a first sample and start refreshing it every `interval`.
+    pub fn start(interval: Duration) -> MemoryMonitor {
+        let latest = Arc::new(Mutex::new(get_memory_stats()));
+        let (stop, stop_receiver) = mpsc::channel::<()>();
+
+        let alerts: Arc<Mutex<Option<AlertTrigger>>> = Arc::new(Mutex::new(None));
+        let history: Arc<Mutex<Option<MemoryHistory>>> = Arc::new(Mutex::new(None));
+        let healing: Arc<Mutex<Option<HealingTrigger>>> = Arc::new(Mutex::new(None));
+        let emergency: Arc<Mutex<Option<Arc<Mutex<EmergencyReserve>>>>> = Arc::new(Mutex::new(None));
+        #[cfg(target_os = "linux")]
+        let vmstat: Arc<Mutex<Option<(VmStatPoller, VmStatDelta)>>> = Arc::new(Mutex::new(None));
+
+        let shared = Arc::clone(&latest);
+        let shared_alerts = Arc::clone(&alerts);
+        let shared_history = Arc::clone(&history);
+        let shared_healing = Arc::clone(&healing);
+        let shared_emergency = Arc::clone(&emergency);
+        #[cfg(target_os = "linux")]
+        let shared_vmstat = Arc::clone(&vmstat);
+        let handle = thread::spawn(move || {
+            // Waiting on the channel rather than sleeping lets stop() return promptly
+            while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(interval) {
+                let stats = get_memory_stats();
+                if let Some(trigger) = shared_alerts.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).as_mut() {
+                    trigger.observe(&stats);
+                }
+                if let Some(history) = shared_history.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).as_mut() {
+                    history.push(stats.clone());
+                }
+                if let Some(trigger) = shared_healing.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).as_mut() {
+                    trigger.observe(&stats);
+                }
+                if stats.used_percent > EMERGENCY_RELEASE_PERCENT {
+                    if let Some(reserve) = shared_emergency.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref() {
+                        // Released reserves stay attached but are skipped until replenished
+                        let mut reserve = reserve.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                        if reserve.is_active() {
+                            reserve.release();
+                        }
+                    }
+                }
+                #[cfg(target_os = "linux")]
+                if let Some((poller, delta)) = shared_vmstat.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).as_mut() {
+                    *delta = poller.poll();
+                }
+                *shared.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = stats;
+            }
+        });
+
+        MemoryMonitor {
+            latest,
+            alerts,
+            history,
+            healing,
+            emergency,
+            #[cfg(target_os = "linux")]
+            vmstat,
+            stop: Some(stop),
+            handle: Some(handle),
+            alert_handle: None,
+        }
+    }
+
+    /// Invoke `config` callbacks when used_percent crosses a threshold.
+    ///
+    /// Callbacks run on a dedicated thread so a slow handler never delays polling.
+    /// Replaces any alerts configured earlier.
+    pub fn with_alerts(mut self, config: MemoryAlertConfig) -> Self {
+        let (sender, receiver) = mpsc::channel::<(AlertLevel, MemoryStats)>();
+        let trigger = AlertTrigger {
+            warn: config.used_percent_warn,
+            crit: config.used_percent_crit,
+            level: AlertLevel::Normal,
+            sender,
+        };
+
+        let alert_handle = thread::spawn(move || {
+            // Exits once the trigger, and with it the sender, is dropped
+            for (level, stats) in receiver {
+                match level {
+                    AlertLevel::Warn => (config.on_warn)(&stats),
+                    AlertLevel::Crit => (config.on_crit)(&stats),
+                    AlertLevel::Normal => {}
+                }
+            }
+        });
+
+        // Dropping a previous trigger closes its channel and ends its thread
+        *self.alerts.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(trigger);
+        if let Some(previous) = self.alert_handle.replace(alert_handle) {
+            let _ = previous.join();
+        }
+        self
+    }
+
+    /// Keep the last `capacity` samples, starting with the current one.
+    pub fn with_history(self, capacity: usize) -> Self {
+        let mut history = MemoryHistory::new(capacity);
+        history.push(self.latest());
+
+        *self.history.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(history);
+        self
+    }
+
+    /// Run `engine` whenever used_percent rises past `used_percent`.
+    ///
+    /// Actions run on the polling thread, which pauses while they do. Every
+    /// invocation is recorded in `log` if one is given. Replaces any healing
+    /// configured earlier.
+    pub fn with_healing(self, engine: HealingEngine, used_percent: f64, log: Option<Arc<Mutex<HealingLog>>>) -> Self {
+        let trigger = HealingTrigger {
+            engine,
+            used_percent,
+            above: false,
+            log,
+        };
+
+        *self.healing.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(trigger);
+        self
+    }
+
+    /// Release `reserve` once used_percent rises above `EMERGENCY_RELEASE_PERCENT`.
+    ///
+    /// The reserve stays shared with the caller, who can `replenish()` it once
+    /// pressure subsides; the monitor releases it again on the next spike.
+    /// Replaces any reserve attached earlier.
+    pub fn with_emergency_reserve(self, reserve: Arc<Mutex<EmergencyReserve>>) -> Self {
+        *self.emergency.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(reserve);
+        self
+    }
+
+    /// Track paging and reclaim rates from /proc/vmstat on every poll (Linux only).
+    ///
+    /// Rates are measured from the moment this is called; `vmstat_delta()`
+    /// reports zeros until the first poll after it.
+    #[cfg(target_os = "linux")]
+    pub fn with_vmstat(self) -> Self {
+        *self.vmstat.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((VmStatPoller::new(), VmStatDelta::default()));
+        self
+    }
+
+    /// Rates between the last two polls, if `with_vmstat` was used.
+    #[cfg(target_os = "linux")]
+    pub fn vmstat_delta(&self) -> Option<VmStatDelta> {
+        self.vmstat
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .as_ref()
+            .map(|(_, delta)| delta.clone())
+    }
+
+    /// Copy of the recorded samples, if `with_history` was used.
+    pub fn history(&self) -> Option<MemoryHistory> {
+        self.history.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Most recent sample.
+    pub fn latest(&self) -> MemoryStats {
+        self.latest.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Signal the polling thread to exit and wait for it.
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        // Let queued alerts finish before returning
+        self.alerts.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+        if let Some(alert_handle) = self.alert_handle.take() {
+            let _ = alert_handle.join();
+        }
+    }
+}
+
+impl Drop for MemoryMonitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,145 @@
+//! Memory statistics as OpenTelemetry gauges.
+//!
+//! Instrument names and the `system.memory.state` attribute follow the
+//! OpenTelemetry semantic conventions for system metrics; the fragmentation
+//! score has no convention and uses a crate-specific name.
+
+use std::sync::Mutex;
+
+use opentelemetry::global;
+use opentelemetry::metrics::{CallbackRegistration, Meter, ObservableGauge, Result, Unit};
+use opentelemetry::KeyValue;
+
+use super::MemoryStatsConfig;
+
+/// Gauges created by `register_otel_instruments`, observed on every collection.
+pub struct OtelMemoryInstruments {
+    pub limit: ObservableGauge<u64>,         // system.memory.limit, from `total`
+    pub usage: ObservableGauge<u64>,         // system.memory.usage, by state
+    pub available: ObservableGauge<u64>,     // system.linux.memory.available
+    pub utilization: ObservableGauge<f64>,   // system.memory.utilization, by state
+    pub paging_usage: ObservableGauge<u64>,  // system.paging.usage, by state
+    pub fragmentation: ObservableGauge<f64>, // memory_core.fragmentation.score
+    registration: Mutex<Option<Box<dyn CallbackRegistration>>>,
+}
+
+/// Create gauges for every numeric `MemoryStats` field on `meter`.
+///
+/// A single callback samples memory once per collection, with swap and
+/// fragmentation included, and records all gauges from it. `speculative` is
+/// counted in `free` and not reported separately. If the meter rejects the
+/// callback the error goes to the global OpenTelemetry error handler and the
+/// gauges stay empty.
+pub fn register_otel_instruments(meter: &Meter) -> OtelMemoryInstruments {
+    let limit = meter
+        .u64_observable_gauge("system.memory.limit")
+        .with_description("Total physical memory")
+        .with_unit(Unit::new("By"))
+        .init();
+    let usage = meter
+        .u64_observable_gauge("system.memory.usage")
+        .with_description("Physical memory in use, by state")
+        .with_unit(Unit::new("By"))
+        .init();
+    let available = meter
+        .u64_observable_gauge("system.linux.memory.available")
+        .with_description("Memory available for new allocations without swapping")
+        .with_unit(Unit::new("By"))
+        .init();
+    let utilization = meter
+        .f64_observable_gauge("system.memory.utilization")
+        .with_description("Share of physical memory in use, by state")
+        .with_unit(Unit::new("1"))
+        .init();
+    let paging_usage = meter
+        .u64_observable_gauge("system.paging.usage")
+        .with_description("Swap space in use, by state")
+        .with_unit(Unit::new("By"))
+        .init();
+    let fragmentation = meter
+        .f64_observable_gauge("memory_core.fragmentation.score")
+        .with_description("Share of free pages stranded in order-0 blocks")
+        .with_unit(Unit::new("1"))
+        .init();
+
+    let instruments = [
+        limit.as_any(),
+        usage.as_any(),
+        available.as_any(),
+        utilization.as_any(),
+        paging_usage.as_any(),
+        fragmentation.as_any(),
+    ];
+    let gauges = (
+        limit.clone(),
+        usage.clone(),
+        available.clone(),
+        utilization.clone(),
+        paging_usage.clone(),
+        fragmentation.clone(),
+    );
+    let config = MemoryStatsConfig::new()
+        .include_timestamp(false)
+        .include_swap(true)
+        .include_fragmentation(true);
+    let registration = meter.register_callback(&instruments, move |observer| {
+        let (limit, usage, available, utilization, paging_usage, fragmentation) = &gauges;
+        let stats = config.fetch();
+
+        observer.observe_u64(limit, stats.total, &[]);
+        observer.observe_u64(available, stats.available, &[]);
+        observer.observe_u64(usage, stats.used, &[state("used")]);
+        observer.observe_u64(usage, stats.free, &[state("free")]);
+        if let Some(buffers) = stats.buffers {
+            observer.observe_u64(usage, buffers, &[state("buffers")]);
+        }
+        if let Some(cached) = stats.cached {
+            observer.observe_u64(usage, cached, &[state("cached")]);
+        }
+        observer.observe_f64(utilization, stats.used_percent / 100.0, &[state("used")]);
+
+        if let (Some(total), Some(free)) = (stats.swap_total, stats.swap_free) {
+            let paging_state = |value: &'static str| KeyValue::new("system.paging.state", value);
+            observer.observe_u64(paging_usage, total.saturating_sub(free), &[paging_state("used")]);
+            observer.observe_u64(paging_usage, free, &[paging_state("free")]);
+        }
+        if let Some(score) = stats.fragmentation_score {
+            observer.observe_f64(fragmentation, score, &[]);
+        }
+    });
+
+    let registration = match registration {
+        Ok(registration) => Some(registration),
+        Err(err) => {
+            global::handle_error(err);
+            None
+        }
+    };
+
+    OtelMemoryInstruments {
+        limit,
+        usage,
+        available,
+        utilization,
+        paging_usage,
+        fragmentation,
+        registration: Mutex::new(registration),
+    }
+}
+
+impl OtelMemoryInstruments {
+    /// Remove the callback; the gauges report nothing afterwards.
+    ///
+    /// Calling it again is a no-op.
+    pub fn unregister(&self) -> Result<()> {
+        let registration = self.registration.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+        match registration {
+            Some(mut registration) => registration.unregister(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn state(value: &'static str) -> KeyValue {
+    KeyValue::new("system.memory.state", value)
+}
@@ -0,0 +1,274 @@
+//! Platform details for bug reports, and checks for what the crate can do here.
+
+use std::path::Path;
+
+use super::get_memory_stats;
+use super::util::page_size;
+
+/// Optional kernel features the crate can make use of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PlatformFeature {
+    Psi,          // /proc/pressure/memory
+    Thp,          // Transparent huge pages
+    Ksm,          // Kernel same-page merging
+    CgroupV2,     // Unified cgroup hierarchy
+    Numa,         // More than one NUMA node
+    Memfd,        // memfd_create and sealing
+    KPageCount,   // /proc/kpagecount
+    DropCaches,   // Writable /proc/sys/vm/drop_caches
+}
+
+/// Which operations and data sources work on the running system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryCapabilities {
+    pub can_release_cache: bool, // release_memory_cache() has the access it needs
+    pub can_defragment: bool,    // defragment_memory() has an allocator to trim
+    pub has_buddyinfo: bool,     // get_buddyinfo() has /proc/buddyinfo to read
+    pub has_psi: bool,           // /proc/pressure/memory
+    pub has_ksm: bool,           // /sys/kernel/mm/ksm
+    pub has_thp: bool,           // /sys/kernel/mm/transparent_hugepage
+    pub has_cgroup_v2: bool,     // Unified hierarchy mounted at /sys/fs/cgroup
+    pub has_numa: bool,          // More than one NUMA node
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlatformInfo {
+    pub os: String,                     // e.g. "linux", "macos", "windows"
+    pub os_version: String,             // Distribution or product version
+    pub kernel_version: Option<String>, // Kernel release where there is one
+    pub cpu_arch: String,               // e.g. "x86_64", "aarch64"
+    pub physical_cpus: u32,             // Physical cores, logical CPUs if unknown
+    pub numa_nodes: u32,                // NUMA nodes, 1 if not NUMA
+    pub memory_total_gb: f64,           // Physical memory in GiB
+    pub page_size_kb: u32,              // Base page size in KiB
+    pub supported_features: Vec<PlatformFeature>,
+}
+
+/// Collect platform details for the running system.
+pub fn get_platform_info() -> PlatformInfo {
+    let stats = get_memory_stats();
+
+    PlatformInfo {
+        os: std::env::consts::OS.to_string(),
+        os_version: os_version(),
+        kernel_version: kernel_version(),
+        cpu_arch: std::env::consts::ARCH.to_string(),
+        physical_cpus: physical_cpus(),
+        numa_nodes: numa_nodes(),
+        memory_total_gb: stats.total as f64 / (1024.0 * 1024.0 * 1024.0),
+        page_size_kb: (page_size() / 1024) as u32,
+        supported_features: supported_features(),
+    }
+}
+
+/// Check which operations will work before calling them.
+/// 
+/// Checks only look at files, permissions and build configuration, so they
+/// are cheap, but an operation can still fail for reasons found only by
+/// trying it.
+pub fn capabilities() -> MemoryCapabilities {
+    let linux = cfg!(target_os = "linux");
+    let exists = |path: &str| linux && Path::new(path).exists();
+
+    MemoryCapabilities {
+        can_release_cache: can_release_cache(),
+        can_defragment: cfg!(feature = "jemalloc") || cfg!(all(target_os = "linux", target_env = "gnu")),
+        has_buddyinfo: exists("/proc/buddyinfo"),
+        has_psi: exists("/proc/pressure/memory"),
+        has_ksm: exists("/sys/kernel/mm/ksm/run"),
+        has_thp: exists("/sys/kernel/mm/transparent_hugepage/enabled"),
+        has_cgroup_v2: exists("/sys/fs/cgroup/cgroup.controllers"),
+        has_numa: numa_nodes() > 1,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn can_release_cache() -> bool {
+    drop_caches_writable()
+}
+
+#[cfg(target_os = "macos")]
+fn can_release_cache() -> bool {
+    // purge refuses to run without root
+    Path::new("/usr/sbin/purge").exists() && unsafe { libc::geteuid() } == 0
+}
+
+#[cfg(target_os = "windows")]
+fn can_release_cache() -> bool {
+    // Emptying our own working set needs no privileges
+    true
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn can_release_cache() -> bool {
+    false
+}
+
+fn drop_caches_writable() -> bool {
+    std::fs::OpenOptions::new().write(true).open("/proc/sys/vm/drop_caches").is_ok()
+}
+
+fn logical_cpus() -> u32 {
+    std::thread::available_parallelism()
+        .map(|count| count.get() as u32)
+        .unwrap_or(1)
+}
+
+#[cfg(target_os = "linux")]
+fn os_version() -> String {
+    // PRETTY_NAME="Ubuntu 22.04.3 LTS"
+    std::fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|release| {
+            release
+                .lines()
+                .find(|line| line.starts_with("PRETTY_NAME="))
+                .map(|line| line["PRETTY_NAME=".len()..].trim_matches('"').to_string())
+        })
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+#[cfg(target_os = "macos")]
+fn os_version() -> String {
+    use std::process::Command;
+
+    Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn os_version() -> String {
+    String::from("unknown")
+}
+
+#[cfg(unix)]
+fn kernel_version() -> Option<String> {
+    use std::ffi::CStr;
+
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return None;
+    }
+
+    let release = unsafe { CStr::from_ptr(uts.release.as_ptr()) };
+    Some(release.to_string_lossy().into_owned())
+}
+
+#[cfg(not(unix))]
+fn kernel_version() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn physical_cpus() -> u32 {
+    use std::collections::HashSet;
+
+    let cpuinfo = match std::fs::read_to_string("/proc/cpuinfo") {
+        Ok(cpuinfo) => cpuinfo,
+        Err(_) => return logical_cpus(),
+    };
+
+    // A physical core is a unique (physical id, core id) pair
+    let mut cores = HashSet::new();
+    let mut physical_id = None;
+    for line in cpuinfo.lines() {
+        let mut parts = line.splitn(2, ':');
+        let key = parts.next().unwrap_or("").trim();
+        let value = parts.next().unwrap_or("").trim();
+
+        match key {
+            "physical id" => physical_id = Some(value.to_string()),
+            "core id" => {
+                cores.insert((physical_id.clone(), value.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    if cores.is_empty() {
+        logical_cpus()
+    } else {
+        cores.len() as u32
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn physical_cpus() -> u32 {
+    use std::process::Command;
+
+    Command::new("sysctl")
+        .args(["-n", "hw.physicalcpu"])
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|count| count.trim().parse::<u32>().ok())
+        .unwrap_or_else(logical_cpus)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn physical_cpus() -> u32 {
+    logical_cpus()
+}
+
+#[cfg(target_os = "linux")]
+fn numa_nodes() -> u32 {
+    let count = std::fs::read_dir("/sys/devices/system/node")
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    name.starts_with("node") && name[4..].parse::<u32>().is_ok()
+                })
+                .count() as u32
+        })
+        .unwrap_or(0);
+
+    count.max(1)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn numa_nodes() -> u32 {
+    1
+}
+
+fn supported_features() -> Vec<PlatformFeature> {
+    let mut features = Vec::new();
+
+    if cfg!(target_os = "linux") {
+        let checks = [
+            (PlatformFeature::Psi, "/proc/pressure/memory"),
+            (PlatformFeature::Thp, "/sys/kernel/mm/transparent_hugepage/enabled"),
+            (PlatformFeature::Ksm, "/sys/kernel/mm/ksm/run"),
+            (PlatformFeature::CgroupV2, "/sys/fs/cgroup/cgroup.controllers"),
+            (PlatformFeature::KPageCount, "/proc/kpagecount"),
+        ];
+        for (feature, path) in checks.iter() {
+            if Path::new(path).exists() {
+                features.push(*feature);
+            }
+        }
+
+        // memfd_create has been available since Linux 3.17
+        features.push(PlatformFeature::Memfd);
+
+        if drop_caches_writable() {
+            features.push(PlatformFeature::DropCaches);
+        }
+    }
+
+    if numa_nodes() > 1 {
+        features.push(PlatformFeature::Numa);
+    }
+
+    features
+}
@@ -0,0 +1,163 @@
+//! Healing policies decide when to run a healing action based on the
+//! current memory state.
+
+use std::time::{Duration, Instant};
+
+use super::stats::NormalizedStats;
+
+/// A healing action together with the decision of whether to run it.
+///
+/// Policies receive normalized stats so that thresholds mean the same
+/// thing on every platform.
+pub trait HealingPolicy: Send {
+    /// Short name used in logs.
+    fn name(&self) -> &str;
+
+    /// Heal if the policy decides it is necessary.
+    ///
+    /// Returns `true` if an action ran and succeeded.
+    fn heal(&mut self, stats: &NormalizedStats) -> bool;
+}
+
+/// Releases the OS memory cache unconditionally.
+pub struct ReleaseCachePolicy;
+
+impl HealingPolicy for ReleaseCachePolicy {
+    fn name(&self) -> &str {
+        "release_memory_cache"
+    }
+
+    fn heal(&mut self, _stats: &NormalizedStats) -> bool {
+        super::release_memory_cache()
+    }
+}
+
+/// Defragments memory unconditionally.
+pub struct DefragmentPolicy;
+
+impl HealingPolicy for DefragmentPolicy {
+    fn name(&self) -> &str {
+        "defragment_memory"
+    }
+
+    fn heal(&mut self, _stats: &NormalizedStats) -> bool {
+        super::defragment_memory(None).success
+    }
+}
+
+/// Adapts a closure into a policy.
+pub struct FnPolicy<F> {
+    name: String,
+    action: F,
+}
+
+impl<F> FnPolicy<F>
+where
+    F: FnMut(&NormalizedStats) -> bool + Send,
+{
+    pub fn new(name: &str, action: F) -> Self {
+        FnPolicy {
+            name: name.to_string(),
+            action,
+        }
+    }
+}
+
+impl<F> HealingPolicy for FnPolicy<F>
+where
+    F: FnMut(&NormalizedStats) -> bool + Send,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn heal(&mut self, stats: &NormalizedStats) -> bool {
+        (self.action)(stats)
+    }
+}
+
+/// One escalation step of a `MultiTierHealingPolicy`.
+pub struct HealingStage {
+    pub trigger_percent: f64,           // Used percentage at which this stage may fire
+    pub action: Box<dyn HealingPolicy>, // What to run when it fires
+    pub cooldown: Duration,             // How long to wait for the action to take effect
+}
+
+/// Escalates through increasingly disruptive stages while memory stays high.
+///
+/// A stage only hands over to the next one if, once its cooldown has passed,
+/// usage is still above its `trigger_percent - hysteresis`. Once usage drops
+/// below the first stage's `trigger_percent - hysteresis` the policy starts
+/// over at the first stage.
+pub struct MultiTierHealingPolicy {
+    stages: Vec<HealingStage>,
+    hysteresis: f64,
+    current: usize,
+    last_fired: Option<Instant>,
+}
+
+impl MultiTierHealingPolicy {
+    /// Create a policy from stages ordered from least to most disruptive.
+    pub fn new(stages: Vec<HealingStage>) -> Self {
+        MultiTierHealingPolicy {
+            stages,
+            hysteresis: 5.0,
+            current: 0,
+            last_fired: None,
+        }
+    }
+
+    /// Set the hysteresis in percentage points (default 5.0).
+    pub fn with_hysteresis(mut self, hysteresis: f64) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Index of the stage that will fire next.
+    pub fn current_stage(&self) -> usize {
+        self.current
+    }
+}
+
+impl HealingPolicy for MultiTierHealingPolicy {
+    fn name(&self) -> &str {
+        "multi_tier"
+    }
+
+    fn heal(&mut self, stats: &NormalizedStats) -> bool {
+        if self.stages.is_empty() {
+            return false;
+        }
+
+        let used = stats.used_percent;
+
+        // Fully recovered: start over at the least disruptive stage
+        if used < self.stages[0].trigger_percent - self.hysteresis {
+            self.current = 0;
+            self.last_fired = None;
+            return false;
+        }
+
+        if let Some(last_fired) = self.last_fired {
+            let stage = &self.stages[self.current];
+            if last_fired.elapsed() < stage.cooldown {
+                return false;
+            }
+
+            // The current stage had its chance; escalate if it didn't help
+            let recovered = used < stage.trigger_percent - self.hysteresis;
+            let next = self.current + 1;
+            if !recovered && next < self.stages.len() && used >= self.stages[next].trigger_percent {
+                self.current = next;
+            }
+        }
+
+        let stage = &mut self.stages[self.current];
+        if used < stage.trigger_percent {
+            return false;
+        }
+
+        self.last_fired = Some(Instant::now());
+        stage.action.heal(stats)
+    }
+}
@@ -0,0 +1,272 @@
+//! Pre-allocated pools for reusable allocations.
+
+use alloc::alloc::{alloc, dealloc, Layout};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Largest alignment given to pool blocks, and the alignment of arena buffers.
+const MAX_BLOCK_ALIGN: usize = 16;
+
+/// Fixed-size block pool carved out of a single slab.
+///
+/// Blocks are aligned to the largest power of two dividing `BLOCK_SIZE`, up to
+/// 16 bytes. Not synchronised; share it between threads inside a `Mutex`.
+pub struct MemoryPool<const BLOCK_SIZE: usize> {
+    slab: *mut u8,
+    layout: Layout,
+    free_list: Vec<*mut u8>,
+    in_use: Vec<bool>,
+}
+
+// The pool owns its slab exclusively; pointers handed out are the caller's
+// to synchronise
+unsafe impl<const BLOCK_SIZE: usize> Send for MemoryPool<BLOCK_SIZE> {}
+
+impl<const BLOCK_SIZE: usize> MemoryPool<BLOCK_SIZE> {
+    /// Allocate a slab holding `capacity` blocks.
+    ///
+    /// Returns `None` if `BLOCK_SIZE` or `capacity` is zero or the allocation fails.
+    pub fn new(capacity: usize) -> Option<Self> {
+        if BLOCK_SIZE == 0 || capacity == 0 {
+            return None;
+        }
+
+        let align = (1 << BLOCK_SIZE.trailing_zeros()).min(MAX_BLOCK_ALIGN);
+        let layout = Layout::from_size_align(BLOCK_SIZE.checked_mul(capacity)?, align).ok()?;
+
+        let slab = unsafe { alloc(layout) };
+        if slab.is_null() {
+            return None;
+        }
+
+        // Reversed so blocks are handed out from the start of the slab
+        let free_list = (0..capacity).rev().map(|index| unsafe { slab.add(index * BLOCK_SIZE) }).collect();
+
+        Some(MemoryPool {
+            slab,
+            layout,
+            free_list,
+            in_use: vec![false; capacity],
+        })
+    }
+
+    /// Take a free block, or `None` when the pool is exhausted.
+    pub fn acquire(&mut self) -> Option<*mut u8> {
+        let ptr = self.free_list.pop()?;
+        let index = self.block_index(ptr)?;
+        self.in_use[index] = true;
+        Some(ptr)
+    }
+
+    /// Return a block to the pool.
+    ///
+    /// Pointers that did not come from this pool, or that were already
+    /// released, are ignored so a double release cannot corrupt the free list.
+    pub fn release(&mut self, ptr: *mut u8) {
+        let index = match self.block_index(ptr) {
+            Some(index) => index,
+            None => return,
+        };
+        if !self.in_use[index] {
+            return;
+        }
+
+        self.in_use[index] = false;
+        self.free_list.push(ptr);
+    }
+
+    /// Total number of blocks.
+    pub fn capacity(&self) -> usize {
+        self.in_use.len()
+    }
+
+    /// Number of blocks ready to be acquired.
+    pub fn available(&self) -> usize {
+        self.free_list.len()
+    }
+
+    /// Index of the block starting at `ptr`, if it is one of ours.
+    fn block_index(&self, ptr: *mut u8) -> Option<usize> {
+        let offset = (ptr as usize).checked_sub(self.slab as usize)?;
+        if offset >= self.layout.size() || offset % BLOCK_SIZE != 0 {
+            return None;
+        }
+        Some(offset / BLOCK_SIZE)
+    }
+}
+
+impl<const BLOCK_SIZE: usize> Drop for MemoryPool<BLOCK_SIZE> {
+    fn drop(&mut self) {
+        // Blocks live inside the slab, so one dealloc frees them all
+        unsafe {
+            dealloc(self.slab, self.layout);
+        }
+    }
+}
+
+/// Usage counters of a `MemoryArena`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ArenaStats {
+    pub bytes_used: usize,      // Bytes consumed, including alignment padding
+    pub bytes_remaining: usize, // Bytes left before the arena is full
+    pub num_allocations: usize, // Allocations since creation or the last reset
+}
+
+/// Bump allocator over one pre-allocated buffer.
+///
+/// Individual allocations are never freed; `reset` reclaims them all at once,
+/// which suits request-scoped bursts.
+pub struct MemoryArena {
+    buffer: *mut u8,
+    layout: Layout,
+    offset: usize,
+    num_allocations: usize,
+}
+
+unsafe impl Send for MemoryArena {}
+
+impl MemoryArena {
+    /// Allocate a `capacity` byte buffer.
+    ///
+    /// Returns `None` if `capacity` is zero or the allocation fails.
+    pub fn new(capacity: usize) -> Option<Self> {
+        if capacity == 0 {
+            return None;
+        }
+
+        let layout = Layout::from_size_align(capacity, MAX_BLOCK_ALIGN).ok()?;
+        let buffer = unsafe { alloc(layout) };
+        if buffer.is_null() {
+            return None;
+        }
+
+        Some(MemoryArena {
+            buffer,
+            layout,
+            offset: 0,
+            num_allocations: 0,
+        })
+    }
+
+    /// Carve `size` bytes aligned to `align` off the buffer.
+    ///
+    /// Returns `None` when the arena is full or `align` is not a power of two.
+    pub fn alloc_bytes(&mut self, size: usize, align: usize) -> Option<*mut u8> {
+        if !align.is_power_of_two() {
+            return None;
+        }
+
+        // Align the address rather than the offset so alignments above the
+        // buffer's own are honoured too
+        let base = self.buffer as usize;
+        let start = (base + self.offset).checked_add(align - 1)? & !(align - 1);
+        let end = start.checked_add(size)?;
+        if end > base + self.layout.size() {
+            return None;
+        }
+
+        self.offset = end - base;
+        self.num_allocations += 1;
+        Some(unsafe { self.buffer.add(start - base) })
+    }
+
+    /// Forget all allocations, keeping the buffer for reuse.
+    ///
+    /// Pointers handed out earlier must no longer be used.
+    pub fn reset(&mut self) {
+        self.offset = 0;
+        self.num_allocations = 0;
+    }
+
+    pub fn stats(&self) -> ArenaStats {
+        ArenaStats {
+            bytes_used: self.offset,
+            bytes_remaining: self.layout.size() - self.offset,
+            num_allocations: self.num_allocations,
+        }
+    }
+}
+
+impl Drop for MemoryArena {
+    fn drop(&mut self) {
+        unsafe {
+            dealloc(self.buffer, self.layout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_exhausts_at_capacity() {
+        let mut pool = MemoryPool::<64>::new(4).unwrap();
+        assert_eq!(pool.capacity(), 4);
+
+        let blocks: Vec<*mut u8> = (0..4).map(|_| pool.acquire().unwrap()).collect();
+        assert_eq!(pool.available(), 0);
+        assert!(pool.acquire().is_none());
+
+        for pair in blocks.windows(2) {
+            assert_eq!(pair[1] as usize - pair[0] as usize, 64);
+        }
+        assert_eq!(blocks[0] as usize % MAX_BLOCK_ALIGN, 0);
+    }
+
+    #[test]
+    fn double_release_is_ignored() {
+        let mut pool = MemoryPool::<32>::new(2).unwrap();
+        let first = pool.acquire().unwrap();
+        let _second = pool.acquire().unwrap();
+        assert_eq!(pool.available(), 0);
+
+        pool.release(first);
+        assert_eq!(pool.available(), 1);
+        pool.release(first);
+        assert_eq!(pool.available(), 1);
+
+        assert_eq!(pool.acquire(), Some(first));
+        assert!(pool.acquire().is_none());
+    }
+
+    #[test]
+    fn foreign_pointers_are_ignored() {
+        let mut pool = MemoryPool::<16>::new(2).unwrap();
+        let block = pool.acquire().unwrap();
+
+        let mut outside = 0u8;
+        pool.release(&mut outside as *mut u8);
+        pool.release(unsafe { block.add(1) });
+        assert_eq!(pool.available(), 1);
+    }
+
+    #[test]
+    fn empty_pools_are_rejected() {
+        assert!(MemoryPool::<0>::new(4).is_none());
+        assert!(MemoryPool::<8>::new(0).is_none());
+    }
+
+    #[test]
+    fn arena_aligns_and_resets() {
+        let mut arena = MemoryArena::new(128).unwrap();
+        let first = arena.alloc_bytes(3, 1).unwrap();
+        let second = arena.alloc_bytes(8, 8).unwrap();
+        assert_eq!(second as usize % 8, 0);
+        assert!(second as usize >= first as usize + 3);
+        assert!(arena.alloc_bytes(256, 1).is_none());
+        assert!(arena.alloc_bytes(1, 3).is_none());
+        assert_eq!(arena.stats().num_allocations, 2);
+
+        arena.reset();
+        assert_eq!(
+            arena.stats(),
+            ArenaStats {
+                bytes_used: 0,
+                bytes_remaining: 128,
+                num_allocations: 0
+            }
+        );
+    }
+}
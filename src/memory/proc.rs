@@ -0,0 +1,4 @@
+//! Kernel notification interfaces that are driven through file descriptors.
+
+#[cfg(target_os = "linux")]
+pub mod mem_pressure_fd;
@@ -0,0 +1,72 @@
+//! cgroup v1 memory threshold notifications.
+//!
+//! Writing `"<eventfd> <fd of memory.usage_in_bytes> <threshold>"` to
+//! `cgroup.event_control` makes the kernel signal the eventfd whenever the
+//! cgroup's usage crosses the threshold, with no polling involved.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::path::Path;
+
+use super::super::MemoryError;
+
+const DEFAULT_CGROUP: &str = "/sys/fs/cgroup/memory";
+
+/// An eventfd registered for a memory usage threshold.
+pub struct MemoryPressureFd {
+    event: File,
+    // Kept open for the lifetime of the registration
+    _usage: File,
+}
+
+impl MemoryPressureFd {
+    /// Register a threshold on the root memory cgroup.
+    pub fn open(threshold_bytes: u64) -> Result<Self, MemoryError> {
+        Self::open_in(Path::new(DEFAULT_CGROUP), threshold_bytes)
+    }
+
+    /// Register a threshold on the memory cgroup mounted at `cgroup_dir`.
+    pub fn open_in(cgroup_dir: &Path, threshold_bytes: u64) -> Result<Self, MemoryError> {
+        let usage = File::open(cgroup_dir.join("memory.usage_in_bytes"))?;
+
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(MemoryError::Io(io::Error::last_os_error()));
+        }
+        let event = unsafe { File::from_raw_fd(fd) };
+
+        let mut control = OpenOptions::new()
+            .write(true)
+            .open(cgroup_dir.join("cgroup.event_control"))?;
+        let registration = format!("{} {} {}", event.as_raw_fd(), usage.as_raw_fd(), threshold_bytes);
+        control.write_all(registration.as_bytes())?;
+
+        Ok(MemoryPressureFd { event, _usage: usage })
+    }
+
+    /// Block until the threshold is crossed.
+    pub fn wait(&self) -> Result<(), MemoryError> {
+        let mut counter = [0u8; 8];
+        (&self.event).read_exact(&mut counter)?;
+        Ok(())
+    }
+
+    /// The eventfd, for use with `poll`/`epoll` or an async reactor.
+    pub fn as_raw_fd(&self) -> RawFd {
+        self.event.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for MemoryPressureFd {
+    fn into_raw_fd(self) -> RawFd {
+        self.event.into_raw_fd()
+    }
+}
+
+/// Register a threshold on the root memory cgroup and return the eventfd.
+///
+/// The caller owns the returned descriptor and must close it.
+pub fn open_memory_pressure_fd(threshold_bytes: u64) -> Result<RawFd, MemoryError> {
+    Ok(MemoryPressureFd::open(threshold_bytes)?.into_raw_fd())
+}
@@ -0,0 +1,480 @@
+//! Memory footprint of a single process.
+
+use std::io;
+
+/// Memory usage and scheduling counters of one process.
+///
+/// Fields a platform cannot report are 0.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProcessStats {
+    pub pid: u32,
+    pub rss: u64,                      // Resident set size in bytes
+    pub vms: u64,                      // Virtual memory size in bytes
+    pub shared: u64,                   // Resident file-backed and shared memory in bytes
+    pub text: u64,                     // Executable code in bytes
+    pub data: u64,                     // Data, heap and stack segments in bytes
+    pub voluntary_ctx_switches: u64,   // Switches while waiting, e.g. on I/O or a lock
+    pub involuntary_ctx_switches: u64, // Preemptions by the scheduler
+}
+
+/// Get memory statistics for process `pid`.
+pub fn get_process_stats(pid: u32) -> io::Result<ProcessStats> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    return get_process_stats_linux(pid);
+
+    #[cfg(target_os = "macos")]
+    return get_process_stats_macos(pid);
+
+    #[cfg(target_os = "windows")]
+    return get_process_stats_windows(pid);
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "windows")))]
+    {
+        let _ = pid;
+        Err(io::Error::new(io::ErrorKind::Unsupported, "per-process stats are not supported on this platform"))
+    }
+}
+
+/// Get memory statistics for the calling process.
+pub fn get_current_process_stats() -> io::Result<ProcessStats> {
+    get_process_stats(std::process::id())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_process_stats_linux(pid: u32) -> io::Result<ProcessStats> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid))?;
+
+    let mut stats = ProcessStats {
+        pid,
+        ..ProcessStats::default()
+    };
+    for line in status.lines() {
+        parse_status_line(line, &mut stats);
+    }
+
+    Ok(stats)
+}
+
+/// Parse one "Name:   value [kB]" line of /proc/<pid>/status into `stats`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn parse_status_line(line: &str, stats: &mut ProcessStats) {
+    let (key, rest) = match line.split_once(':') {
+        Some(pair) => pair,
+        None => return,
+    };
+
+    let mut parts = rest.split_whitespace();
+    let value = match parts.next().and_then(|value| value.parse::<u64>().ok()) {
+        Some(value) => value,
+        None => return,
+    };
+    let bytes = if parts.next() == Some("kB") { value * 1024 } else { value };
+
+    match key {
+        "VmRSS" => stats.rss = bytes,
+        "VmSize" => stats.vms = bytes,
+        // Shared resident pages are split into file-backed and shmem
+        "RssFile" | "RssShmem" => stats.shared += bytes,
+        "VmExe" => stats.text = bytes,
+        "VmData" | "VmStk" => stats.data += bytes,
+        "voluntary_ctxt_switches" => stats.voluntary_ctx_switches = value,
+        "nonvoluntary_ctxt_switches" => stats.involuntary_ctx_switches = value,
+        _ => {}
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn get_process_stats_macos(pid: u32) -> io::Result<ProcessStats> {
+    if pid == std::process::id() {
+        return get_current_process_stats_macos(pid);
+    }
+
+    // task_for_pid() on another process needs root, proc_pidinfo() does not
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+    let written = unsafe {
+        libc::proc_pidinfo(
+            pid as libc::c_int,
+            libc::PROC_PIDTASKINFO,
+            0,
+            &mut info as *mut _ as *mut libc::c_void,
+            size,
+        )
+    };
+    if written != size {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(ProcessStats {
+        pid,
+        rss: info.pti_resident_size,
+        vms: info.pti_virtual_size,
+        // Only the total is reported; it cannot be split by cause
+        voluntary_ctx_switches: info.pti_csw.max(0) as u64,
+        ..ProcessStats::default()
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn get_current_process_stats_macos(pid: u32) -> io::Result<ProcessStats> {
+    let mut info: libc::mach_task_basic_info = unsafe { std::mem::zeroed() };
+    let mut count = libc::MACH_TASK_BASIC_INFO_COUNT;
+    // libc points at the mach2 crate for Mach APIs, but the binding is sound
+    #[allow(deprecated)]
+    let result = unsafe {
+        libc::task_info(
+            libc::mach_task_self(),
+            libc::MACH_TASK_BASIC_INFO,
+            &mut info as *mut _ as libc::task_info_t,
+            &mut count,
+        )
+    };
+    if result != libc::KERN_SUCCESS {
+        return Err(io::Error::other(format!("task_info failed with {}", result)));
+    }
+
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    unsafe {
+        libc::getrusage(libc::RUSAGE_SELF, &mut usage);
+    }
+
+    Ok(ProcessStats {
+        pid,
+        rss: info.resident_size,
+        vms: info.virtual_size,
+        voluntary_ctx_switches: usage.ru_nvcsw.max(0) as u64,
+        involuntary_ctx_switches: usage.ru_nivcsw.max(0) as u64,
+        ..ProcessStats::default()
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn get_process_stats_windows(pid: u32) -> io::Result<ProcessStats> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::psapi::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if handle.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    let ok = unsafe { GetProcessMemoryInfo(handle, &mut counters, size) };
+    let error = io::Error::last_os_error();
+    unsafe {
+        CloseHandle(handle);
+    }
+    if ok == 0 {
+        return Err(error);
+    }
+
+    Ok(ProcessStats {
+        pid,
+        rss: counters.WorkingSetSize as u64,
+        // Committed private bytes, the closest Windows has to a virtual size
+        vms: counters.PagefileUsage as u64,
+        ..ProcessStats::default()
+    })
+}
+
+/// Page fault counters of one process and its waited-for children.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PageFaultStats {
+    pub minor_faults: u64,   // Faults served without disk I/O
+    pub major_faults: u64,   // Faults that had to read a page from disk
+    pub children_minor: u64, // Minor faults of children that have been waited for
+    pub children_major: u64, // Major faults of children that have been waited for
+}
+
+/// Get the page fault counters of process `pid`.
+///
+/// Linux reads `/proc/<pid>/stat`; macOS can only report the calling process.
+pub fn get_page_fault_stats(pid: u32) -> io::Result<PageFaultStats> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid))?;
+        parse_page_faults(&stat).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed /proc/<pid>/stat"))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if pid != std::process::id() {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "page faults of other processes are not available on macOS"));
+        }
+
+        let mut own: libc::rusage = unsafe { std::mem::zeroed() };
+        let mut children: libc::rusage = unsafe { std::mem::zeroed() };
+        unsafe {
+            libc::getrusage(libc::RUSAGE_SELF, &mut own);
+            libc::getrusage(libc::RUSAGE_CHILDREN, &mut children);
+        }
+
+        Ok(PageFaultStats {
+            minor_faults: own.ru_minflt.max(0) as u64,
+            major_faults: own.ru_majflt.max(0) as u64,
+            children_minor: children.ru_minflt.max(0) as u64,
+            children_major: children.ru_majflt.max(0) as u64,
+        })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+    {
+        let _ = pid;
+        Err(io::Error::new(io::ErrorKind::Unsupported, "page fault counters are not supported on this platform"))
+    }
+}
+
+/// Parse minflt, cminflt, majflt and cmajflt out of a `/proc/<pid>/stat` line.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn parse_page_faults(stat: &str) -> Option<PageFaultStats> {
+    // comm may contain spaces and parentheses, so count fields from the last ')'
+    let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    let field = |index: usize| fields.get(index).and_then(|value| value.parse::<u64>().ok());
+
+    // fields[0] is the state, field 3 of the full line
+    Some(PageFaultStats {
+        minor_faults: field(7)?,
+        children_minor: field(8)?,
+        major_faults: field(9)?,
+        children_major: field(10)?,
+    })
+}
+
+/// Lowest and highest values accepted by `/proc/<pid>/oom_score_adj`.
+pub const OOM_SCORE_ADJ_MIN: i16 = -1000;
+pub const OOM_SCORE_ADJ_MAX: i16 = 1000;
+
+/// Get the OOM killer's current badness score for `pid`.
+///
+/// Higher scores are killed first.
+pub fn get_oom_score(pid: u32) -> io::Result<i32> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let score = std::fs::read_to_string(format!("/proc/{}/oom_score", pid))?;
+        score
+            .trim()
+            .parse::<i32>()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = pid;
+        Err(io::Error::new(io::ErrorKind::Unsupported, "OOM scores are Linux specific"))
+    }
+}
+
+/// Bias the OOM killer for `pid`, clamped to [-1000, 1000].
+///
+/// -1000 exempts the process entirely. Lowering the value below its current
+/// setting requires CAP_SYS_RESOURCE.
+pub fn set_oom_score_adj(pid: u32, adj: i16) -> io::Result<()> {
+    let adj = adj.clamp(OOM_SCORE_ADJ_MIN, OOM_SCORE_ADJ_MAX);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    return std::fs::write(format!("/proc/{}/oom_score_adj", pid), adj.to_string());
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = (pid, adj);
+        Err(io::Error::new(io::ErrorKind::Unsupported, "OOM scores are Linux specific"))
+    }
+}
+
+/// Aggregated mapping statistics from `/proc/<pid>/smaps_rollup`.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SmapsRollup {
+    pub rss: u64,           // Resident set size in bytes
+    pub pss: u64,           // Proportional set size: shared pages split among sharers
+    pub shared_clean: u64,  // Unmodified pages also mapped by other processes
+    pub shared_dirty: u64,  // Modified pages also mapped by other processes
+    pub private_clean: u64, // Unmodified pages mapped only by this process
+    pub private_dirty: u64, // Modified pages mapped only by this process
+    pub swap: u64,          // Anonymous memory swapped out in bytes
+}
+
+/// Get the smaps rollup of process `pid` (Linux 4.14+).
+pub fn get_process_smaps(pid: u32) -> io::Result<SmapsRollup> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let rollup = std::fs::read_to_string(format!("/proc/{}/smaps_rollup", pid))?;
+
+        let mut smaps = SmapsRollup::default();
+        for line in rollup.lines() {
+            parse_smaps_rollup_line(line, &mut smaps);
+        }
+        Ok(smaps)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = pid;
+        Err(io::Error::new(io::ErrorKind::Unsupported, "smaps_rollup is Linux specific"))
+    }
+}
+
+/// Parse one "Name:   value kB" line of smaps_rollup into `smaps`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn parse_smaps_rollup_line(line: &str, smaps: &mut SmapsRollup) {
+    let (key, rest) = match line.split_once(':') {
+        Some(pair) => pair,
+        None => return,
+    };
+
+    let bytes = match rest.split_whitespace().next().and_then(|value| value.parse::<u64>().ok()) {
+        Some(kb) => kb * 1024,
+        None => return,
+    };
+
+    match key {
+        "Rss" => smaps.rss = bytes,
+        "Pss" => smaps.pss = bytes,
+        "Shared_Clean" => smaps.shared_clean = bytes,
+        "Shared_Dirty" => smaps.shared_dirty = bytes,
+        "Private_Clean" => smaps.private_clean = bytes,
+        "Private_Dirty" => smaps.private_dirty = bytes,
+        "Swap" => smaps.swap = bytes,
+        _ => {}
+    }
+}
+
+/// One mapping from `/proc/<pid>/smaps` with its memory counters.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SmapRegion {
+    pub start_addr: u64,          // Start address of the mapping
+    pub end_addr: u64,            // End address of the mapping (exclusive)
+    pub permissions: String,      // Permission flags, e.g. "r-xp"
+    pub pathname: Option<String>, // Backing file or pseudo-path like "[heap]"
+    pub rss: u64,                 // Resident bytes
+    pub pss: u64,                 // Resident bytes with shared pages split among sharers
+    pub private_dirty: u64,       // Modified pages mapped only by this process
+    pub private_clean: u64,       // Unmodified pages mapped only by this process
+    pub shared_dirty: u64,        // Modified pages also mapped by other processes
+}
+
+impl SmapRegion {
+    /// Whether the mapping is backed by a file, as opposed to anonymous
+    /// memory or a pseudo-path like `[heap]` or `[stack]`.
+    pub fn is_file_backed(&self) -> bool {
+        self.pathname.as_deref().is_some_and(|path| path.starts_with('/'))
+    }
+}
+
+/// Anonymous and file-backed totals over a set of `SmapRegion`s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SmapsSummary {
+    pub anon_regions: usize, // Mappings without a backing file
+    pub anon_rss: u64,       // Resident bytes in anonymous mappings
+    pub anon_pss: u64,       // Proportional bytes in anonymous mappings
+    pub file_regions: usize, // Mappings backed by a file
+    pub file_rss: u64,       // Resident bytes in file-backed mappings
+    pub file_pss: u64,       // Proportional bytes in file-backed mappings
+}
+
+impl SmapsSummary {
+    pub fn from_regions(regions: &[SmapRegion]) -> SmapsSummary {
+        let mut summary = SmapsSummary::default();
+        for region in regions {
+            if region.is_file_backed() {
+                summary.file_regions += 1;
+                summary.file_rss += region.rss;
+                summary.file_pss += region.pss;
+            } else {
+                summary.anon_regions += 1;
+                summary.anon_rss += region.rss;
+                summary.anon_pss += region.pss;
+            }
+        }
+        summary
+    }
+}
+
+/// Get every mapping of process `pid` from `/proc/<pid>/smaps`.
+///
+/// Slower than `get_process_smaps`, which only reads the kernel's totals.
+pub fn get_smaps_detail(pid: u32) -> io::Result<Vec<SmapRegion>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let smaps = std::fs::read_to_string(format!("/proc/{}/smaps", pid))?;
+        parse_smaps(&smaps).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed /proc/<pid>/smaps"))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = pid;
+        Err(io::Error::new(io::ErrorKind::Unsupported, "smaps is Linux specific"))
+    }
+}
+
+/// Parse the contents of `/proc/<pid>/smaps` into regions.
+///
+/// Every mapping starts with a maps-style header line followed by
+/// "Name:   value kB" counters; returns `None` on a malformed header.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn parse_smaps(smaps: &str) -> Option<Vec<SmapRegion>> {
+    let mut regions: Vec<SmapRegion> = Vec::new();
+
+    for line in smaps.lines() {
+        let first = match line.split_whitespace().next() {
+            Some(first) => first,
+            None => continue,
+        };
+
+        if !first.ends_with(':') {
+            regions.push(parse_smaps_header(line)?);
+            continue;
+        }
+
+        let region = match regions.last_mut() {
+            Some(region) => region,
+            None => continue,
+        };
+        let bytes = match line[first.len()..].split_whitespace().next().and_then(|value| value.parse::<u64>().ok()) {
+            Some(kb) => kb.saturating_mul(1024),
+            None => continue,
+        };
+        match first {
+            "Rss:" => region.rss = bytes,
+            "Pss:" => region.pss = bytes,
+            "Private_Dirty:" => region.private_dirty = bytes,
+            "Private_Clean:" => region.private_clean = bytes,
+            "Shared_Dirty:" => region.shared_dirty = bytes,
+            _ => {}
+        }
+    }
+
+    Some(regions)
+}
+
+/// Parse a `start-end perms offset dev inode [pathname]` header line.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parse_smaps_header(line: &str) -> Option<SmapRegion> {
+    let mut parts = line.split_whitespace();
+
+    let (start, end) = parts.next()?.split_once('-')?;
+    let permissions = parts.next()?.to_string();
+    // offset, device and inode
+    for _ in 0..3 {
+        parts.next()?;
+    }
+
+    // The pathname may contain spaces, so join whatever is left
+    let rest: Vec<&str> = parts.collect();
+    let pathname = if rest.is_empty() { None } else { Some(rest.join(" ")) };
+
+    Some(SmapRegion {
+        start_addr: u64::from_str_radix(start, 16).ok()?,
+        end_addr: u64::from_str_radix(end, 16).ok()?,
+        permissions,
+        pathname,
+        ..SmapRegion::default()
+    })
+}
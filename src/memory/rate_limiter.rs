@@ -0,0 +1,287 @@
+//! Token-bucket rate limiting for healing policies.
+//!
+//! Under sustained pressure a policy would otherwise fire on every poll.
+//! The bucket is lock-free: tokens and the last refill time each live in an
+//! `AtomicU64`, with tokens stored as fixed-point thousandths.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::policy::HealingPolicy;
+use super::stats::NormalizedStats;
+
+// Fixed-point scale for the token counter
+const SCALE: u64 = 1000;
+
+/// Time source for the limiter, so tests can drive time by hand.
+pub trait Clock: Send + Sync {
+    /// Monotonic time since an arbitrary origin, in nanoseconds.
+    fn now_nanos(&self) -> u64;
+}
+
+/// Wall-clock-independent clock backed by `Instant`.
+pub struct MonotonicClock {
+    origin: Instant,
+}
+
+impl MonotonicClock {
+    pub fn new() -> Self {
+        MonotonicClock { origin: Instant::now() }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now_nanos(&self) -> u64 {
+        self.origin.elapsed().as_nanos() as u64
+    }
+}
+
+/// Manually advanced clock for tests.
+#[derive(Default)]
+pub struct MockClock {
+    nanos: AtomicU64,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.nanos.fetch_add(by.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_nanos(&self) -> u64 {
+        self.nanos.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitError {
+    /// Not enough tokens right now; enough will exist after `retry_after`.
+    Throttled { retry_after: Duration },
+    /// More tokens were requested than the bucket can ever hold.
+    ExceedsBurst { requested: u32, max_burst: u32 },
+}
+
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RateLimitError::Throttled { retry_after } => write!(f, "throttled, retry after {:?}", retry_after),
+            RateLimitError::ExceedsBurst { requested, max_burst } => {
+                write!(f, "requested {} tokens but the burst limit is {}", requested, max_burst)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RateLimitError {}
+
+pub struct HealingRateLimiter {
+    max_burst: u32,
+    refill_per_sec: f64,
+    tokens: AtomicU64,      // Available tokens times SCALE
+    last_refill: AtomicU64, // Clock time of the last refill in nanoseconds
+    clock: Arc<dyn Clock>,
+}
+
+impl HealingRateLimiter {
+    /// Create a full bucket holding at most `max_burst` tokens.
+    pub fn new(max_burst: u32, refill_rate_per_sec: f64) -> Self {
+        Self::with_clock(max_burst, refill_rate_per_sec, Arc::new(MonotonicClock::new()))
+    }
+
+    /// Create a full bucket driven by `clock`.
+    pub fn with_clock(max_burst: u32, refill_rate_per_sec: f64, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now_nanos();
+        HealingRateLimiter {
+            max_burst,
+            refill_per_sec: refill_rate_per_sec.max(0.0),
+            tokens: AtomicU64::new(max_burst as u64 * SCALE),
+            last_refill: AtomicU64::new(now),
+            clock,
+        }
+    }
+
+    /// Take `tokens` from the bucket or report when to try again.
+    pub fn try_consume(&self, tokens: u32) -> Result<(), RateLimitError> {
+        if tokens > self.max_burst {
+            return Err(RateLimitError::ExceedsBurst {
+                requested: tokens,
+                max_burst: self.max_burst,
+            });
+        }
+
+        self.refill();
+
+        let needed = tokens as u64 * SCALE;
+        let result = self
+            .tokens
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |available| available.checked_sub(needed));
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(available) => {
+                let missing = (needed - available) as f64 / SCALE as f64;
+                let retry_after = if self.refill_per_sec > 0.0 {
+                    Duration::from_secs_f64(missing / self.refill_per_sec)
+                } else {
+                    Duration::MAX
+                };
+                Err(RateLimitError::Throttled { retry_after })
+            }
+        }
+    }
+
+    /// Tokens currently available, after refilling.
+    pub fn available(&self) -> f64 {
+        self.refill();
+        self.tokens.load(Ordering::Acquire) as f64 / SCALE as f64
+    }
+
+    /// Credit tokens for the time elapsed since the last refill.
+    fn refill(&self) {
+        let now = self.clock.now_nanos();
+        let last = self.last_refill.load(Ordering::Acquire);
+        if now <= last {
+            return;
+        }
+
+        let elapsed_secs = (now - last) as f64 / 1_000_000_000.0;
+        let credit = (elapsed_secs * self.refill_per_sec * SCALE as f64) as u64;
+
+        // Leave the timestamp alone until a whole unit has accrued, otherwise
+        // frequent callers would keep discarding the fractional credit
+        if credit == 0 {
+            return;
+        }
+
+        // Whoever advances the timestamp credits that interval, exactly once
+        if self
+            .last_refill
+            .compare_exchange(last, now, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return;
+        }
+
+        let capacity = self.max_burst as u64 * SCALE;
+
+        let _ = self.tokens.fetch_update(Ordering::AcqRel, Ordering::Acquire, |available| {
+            Some(available.saturating_add(credit).min(capacity))
+        });
+    }
+}
+
+/// Runs the inner policy only when the limiter has a token to spare.
+pub struct RateLimitedPolicy {
+    inner: Box<dyn HealingPolicy>,
+    limiter: HealingRateLimiter,
+}
+
+impl RateLimitedPolicy {
+    pub fn new(inner: Box<dyn HealingPolicy>, limiter: HealingRateLimiter) -> Self {
+        RateLimitedPolicy { inner, limiter }
+    }
+}
+
+impl HealingPolicy for RateLimitedPolicy {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn heal(&mut self, stats: &NormalizedStats) -> bool {
+        match self.limiter.try_consume(1) {
+            Ok(()) => self.inner.heal(stats),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(max_burst: u32, refill_per_sec: f64) -> (HealingRateLimiter, Arc<MockClock>) {
+        let clock = Arc::new(MockClock::new());
+        let limiter = HealingRateLimiter::with_clock(max_burst, refill_per_sec, clock.clone());
+        (limiter, clock)
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let (limiter, clock) = limiter(4, 2.0);
+        assert_eq!(limiter.try_consume(4), Ok(()));
+        assert!(limiter.try_consume(1).is_err());
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(limiter.available(), 1.0);
+        assert_eq!(limiter.try_consume(1), Ok(()));
+        assert!(limiter.try_consume(1).is_err());
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(limiter.try_consume(2), Ok(()));
+    }
+
+    #[test]
+    fn refill_is_capped_at_burst() {
+        let (limiter, clock) = limiter(3, 10.0);
+        assert_eq!(limiter.try_consume(1), Ok(()));
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(limiter.available(), 3.0);
+        assert_eq!(limiter.try_consume(3), Ok(()));
+        assert!(limiter.try_consume(1).is_err());
+    }
+
+    #[test]
+    fn throttled_reports_retry_after() {
+        let (limiter, clock) = limiter(2, 4.0);
+        assert_eq!(limiter.try_consume(2), Ok(()));
+
+        assert_eq!(
+            limiter.try_consume(1),
+            Err(RateLimitError::Throttled {
+                retry_after: Duration::from_millis(250)
+            })
+        );
+
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(limiter.try_consume(1), Ok(()));
+    }
+
+    #[test]
+    fn zero_refill_never_recovers() {
+        let (limiter, clock) = limiter(1, 0.0);
+        assert_eq!(limiter.try_consume(1), Ok(()));
+
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(
+            limiter.try_consume(1),
+            Err(RateLimitError::Throttled { retry_after: Duration::MAX })
+        );
+    }
+
+    #[test]
+    fn request_above_burst_is_rejected() {
+        let (limiter, _clock) = limiter(5, 1.0);
+        assert_eq!(
+            limiter.try_consume(6),
+            Err(RateLimitError::ExceedsBurst {
+                requested: 6,
+                max_burst: 5
+            })
+        );
+        assert_eq!(limiter.available(), 5.0);
+    }
+}
@@ -0,0 +1,166 @@
+//! Pre-faulted memory set aside for code paths that must not fail to allocate.
+
+use std::io;
+use std::sync::Mutex;
+
+use super::MemoryError;
+
+// (address, length) of every live reservation
+static RESERVATIONS: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+
+/// Memory mapped and faulted in by `reserve`, unmapped on drop.
+pub struct MemoryReservation {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// The reservation owns its mapping exclusively, like a Box<[u8]>
+unsafe impl Send for MemoryReservation {}
+
+/// Map `bytes` of private anonymous memory and fault every page in.
+///
+/// Linux populates the pages with `MAP_POPULATE`; other Unix systems touch
+/// each page instead. The pages are backed by RAM or swap once this returns,
+/// so using them cannot fail later; combine with `lock_region` to keep them
+/// out of swap as well.
+pub fn reserve(bytes: usize) -> io::Result<MemoryReservation> {
+    if bytes == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot reserve zero bytes"));
+    }
+
+    #[cfg(unix)]
+    {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let flags = libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_POPULATE;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let flags = libc::MAP_PRIVATE | libc::MAP_ANONYMOUS;
+
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                bytes,
+                libc::PROT_READ | libc::PROT_WRITE,
+                flags,
+                -1,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let ptr = ptr as *mut u8;
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            // Writing one byte per page makes the kernel back it now
+            let page_size = super::util::page_size().max(1);
+            for offset in (0..bytes).step_by(page_size) {
+                unsafe {
+                    std::ptr::write_volatile(ptr.add(offset), 0);
+                }
+            }
+        }
+
+        registry().push((ptr as usize, bytes));
+        Ok(MemoryReservation { ptr, len: bytes })
+    }
+
+    #[cfg(not(unix))]
+    Err(io::Error::new(io::ErrorKind::Unsupported, "memory reservations need mmap"))
+}
+
+/// Bytes held by all live reservations.
+pub fn total_reserved_bytes() -> u64 {
+    registry().iter().map(|&(_, len)| len as u64).sum()
+}
+
+impl MemoryReservation {
+    /// The reserved memory, zero-filled when first reserved.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+
+        let addr = self.ptr as usize;
+        registry().retain(|&(start, _)| start != addr);
+    }
+}
+
+/// A reservation kept only to be given back when memory runs out.
+///
+/// Holding it keeps `bytes` of memory committed to this process; releasing
+/// it under pressure buys the rest of the process room to shed load or shut
+/// down cleanly. Attach it to a `MemoryMonitor` with `with_emergency_reserve`
+/// to release it automatically.
+pub struct EmergencyReserve {
+    bytes: usize,
+    reservation: Option<MemoryReservation>,
+}
+
+impl EmergencyReserve {
+    /// Reserve `bytes` of pre-faulted memory.
+    ///
+    /// If the memory cannot be reserved the reserve starts out released;
+    /// check `is_active()` or call `replenish()` to retry.
+    pub fn new(bytes: usize) -> EmergencyReserve {
+        let reservation = match reserve(bytes) {
+            Ok(reservation) => Some(reservation),
+            Err(err) => {
+                warn_event!("failed to reserve {} bytes of emergency memory: {}", bytes, err);
+                None
+            }
+        };
+
+        EmergencyReserve { bytes, reservation }
+    }
+
+    /// Free the reserved memory; does nothing if it is already released.
+    pub fn release(&mut self) {
+        if let Some(reservation) = self.reservation.take() {
+            let bytes = reservation.len();
+            drop(reservation);
+            error_event!("emergency memory reserve released: {} bytes returned to the system", bytes);
+        }
+    }
+
+    /// Reserve the memory again after `release`; does nothing while still active.
+    pub fn replenish(&mut self) -> Result<(), MemoryError> {
+        if self.reservation.is_none() {
+            self.reservation = Some(reserve(self.bytes)?);
+        }
+        Ok(())
+    }
+
+    /// Whether the memory is currently held.
+    pub fn is_active(&self) -> bool {
+        self.reservation.is_some()
+    }
+
+    /// Size the reserve holds while active.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+fn registry() -> std::sync::MutexGuard<'static, Vec<(usize, usize)>> {
+    RESERVATIONS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
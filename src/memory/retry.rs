@@ -0,0 +1,156 @@
+//! Retrying healing actions that fail transiently.
+//!
+//! Writes such as `/proc/sys/vm/drop_caches` can be refused for a moment, for
+//! example while a security policy reloads, so one failure is not final.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// Attempts used by the built-in healing operations.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// First backoff delay used by the built-in healing operations.
+pub const DEFAULT_BASE_DELAY_MS: u64 = 100;
+
+/// Run `action` until it succeeds, at most `max_attempts` times.
+///
+/// Waits `base_delay_ms * 2^n` after the n-th failure, capped at
+/// `base_delay_ms * 2^(max_attempts - 1)`, with random jitter taking off up
+/// to half of each delay so that processes healing together do not retry in
+/// lockstep. The action always runs at least once.
+pub fn retry_healing<F: Fn() -> bool>(action: F, max_attempts: u32, base_delay_ms: u64) -> bool {
+    let max_attempts = max_attempts.max(1);
+    let max_delay_ms = backoff_ms(base_delay_ms, max_attempts - 1);
+
+    for attempt in 0..max_attempts {
+        if action() {
+            return true;
+        }
+        if attempt + 1 == max_attempts {
+            break;
+        }
+
+        let delay_ms = backoff_ms(base_delay_ms, attempt).min(max_delay_ms);
+        thread::sleep(Duration::from_millis(with_jitter(delay_ms)));
+    }
+
+    false
+}
+
+/// Like `retry_healing`, for actions that report why they failed.
+///
+/// Only transient errors are retried. Anything else, such as
+/// `PermissionDenied` when not running as root, is returned at once since
+/// another attempt would fail the same way.
+pub fn retry_healing_io<F: Fn() -> io::Result<()>>(action: F, max_attempts: u32, base_delay_ms: u64) -> io::Result<()> {
+    let max_attempts = max_attempts.max(1);
+    let max_delay_ms = backoff_ms(base_delay_ms, max_attempts - 1);
+
+    let mut attempt = 0;
+    loop {
+        match action() {
+            Err(err) if is_transient(&err) && attempt + 1 < max_attempts => {
+                let delay_ms = backoff_ms(base_delay_ms, attempt).min(max_delay_ms);
+                thread::sleep(Duration::from_millis(with_jitter(delay_ms)));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether `err` may clear up if the operation is tried again.
+fn is_transient(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        if err.raw_os_error() == Some(libc::EBUSY) {
+            return true;
+        }
+    }
+
+    matches!(
+        err.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+/// `base_delay_ms * 2^attempt`, saturating instead of overflowing.
+fn backoff_ms(base_delay_ms: u64, attempt: u32) -> u64 {
+    match 1u64.checked_shl(attempt) {
+        Some(factor) => base_delay_ms.saturating_mul(factor),
+        None => u64::MAX,
+    }
+}
+
+/// A random delay between half of `delay_ms` and all of it.
+fn with_jitter(delay_ms: u64) -> u64 {
+    let half = delay_ms / 2;
+    if half == 0 {
+        return delay_ms;
+    }
+
+    // RandomState is seeded randomly per instance, which is plenty for jitter
+    let random = RandomState::new().build_hasher().finish();
+    delay_ms - random % (half + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn permission_denied_is_not_retried() {
+        let attempts = Cell::new(0);
+        let result = retry_healing_io(
+            || {
+                attempts.set(attempts.get() + 1);
+                Err(io::Error::from(io::ErrorKind::PermissionDenied))
+            },
+            5,
+            0,
+        );
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn transient_errors_are_retried_until_success() {
+        let attempts = Cell::new(0);
+        let result = retry_healing_io(
+            || {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() < 3 {
+                    Err(io::Error::from(io::ErrorKind::Interrupted))
+                } else {
+                    Ok(())
+                }
+            },
+            5,
+            0,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn transient_errors_stop_at_max_attempts() {
+        let attempts = Cell::new(0);
+        let result = retry_healing_io(
+            || {
+                attempts.set(attempts.get() + 1);
+                Err(io::Error::from(io::ErrorKind::WouldBlock))
+            },
+            3,
+            0,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 3);
+    }
+}
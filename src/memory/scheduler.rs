@@ -0,0 +1,205 @@
+//! Background healing driven by used-percentage thresholds.
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::healing::{DefragAction, DropCacheAction, HealingEngine, HealingEvent, HealingLog};
+use super::{format_timestamp, get_memory_stats};
+
+/// Thresholds and limits read by the scheduler thread on every check.
+#[derive(Debug, Clone)]
+struct SchedulerSettings {
+    warn_threshold: f64,
+    crit_threshold: f64,
+    max_attempts: u32,
+    cooldown: Duration,
+}
+
+/// Counters describing what a `HealingScheduler` has done so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SchedulerStatus {
+    pub healing_runs: u64, // Checks that triggered healing
+    pub failures: u64,     // Times healing gave up after max_attempts and backed off
+    pub backing_off: bool, // Whether the scheduler is currently in its cooldown
+}
+
+/// Everything the scheduler thread shares with its handle.
+struct SchedulerState {
+    settings: SchedulerSettings,
+    status: SchedulerStatus,
+    log: Option<Arc<Mutex<HealingLog>>>,
+}
+
+/// Polls `get_memory_stats()` and heals when usage crosses a threshold.
+///
+/// Above `warn_threshold` the memory cache is released; above
+/// `crit_threshold` allocator memory is defragmented as well. A check runs
+/// every `check_interval`, and healing repeats on each check while usage
+/// stays high. If usage is still above `warn_threshold` after
+/// `max_attempts` runs, the failure is logged as a warning, counted in
+/// `status()` and recorded as an unsuccessful `scheduler_backoff` event in the
+/// log if one is attached, and the scheduler does nothing for `cooldown`
+/// before trying again.
+pub struct HealingScheduler {
+    state: Arc<Mutex<SchedulerState>>,
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HealingScheduler {
+    /// Start checking every `check_interval`.
+    ///
+    /// Defaults: warn at 80%, critical at 90%, 3 attempts, 5 minute cooldown.
+    pub fn new(check_interval: Duration) -> Self {
+        let state = Arc::new(Mutex::new(SchedulerState {
+            settings: SchedulerSettings {
+                warn_threshold: 80.0,
+                crit_threshold: 90.0,
+                max_attempts: 3,
+                cooldown: Duration::from_secs(300),
+            },
+            status: SchedulerStatus::default(),
+            log: None,
+        }));
+        let (stop, stop_receiver) = mpsc::channel::<()>();
+
+        let shared = Arc::clone(&state);
+        let handle = thread::spawn(move || {
+            let mut warn_engine = HealingEngine::new();
+            warn_engine.register(Box::new(DropCacheAction));
+            let mut crit_engine = HealingEngine::new();
+            crit_engine.register(Box::new(DropCacheAction));
+            crit_engine.register(Box::new(DefragAction));
+
+            let mut attempts = 0;
+            let mut backoff_until: Option<Instant> = None;
+
+            while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(check_interval) {
+                if let Some(until) = backoff_until {
+                    if Instant::now() < until {
+                        continue;
+                    }
+                    backoff_until = None;
+                    lock(&shared).status.backing_off = false;
+                }
+
+                let (settings, log) = {
+                    let state = lock(&shared);
+                    (state.settings.clone(), state.log.clone())
+                };
+
+                let stats = get_memory_stats();
+                let used_percent = stats.used_percent;
+                if used_percent < settings.warn_threshold {
+                    attempts = 0;
+                    continue;
+                }
+
+                if attempts >= settings.max_attempts {
+                    // Healing is not helping; stop hammering the system for a while
+                    warn_event!(
+                        "healing did not bring usage below {}% after {} attempts; backing off for {:?}",
+                        settings.warn_threshold,
+                        settings.max_attempts,
+                        settings.cooldown
+                    );
+                    attempts = 0;
+                    backoff_until = Some(Instant::now() + settings.cooldown);
+                    if let Some(log) = &log {
+                        log.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).append(HealingEvent {
+                            timestamp: format_timestamp(),
+                            action: String::from("scheduler_backoff"),
+                            bytes_reclaimed: 0,
+                            success: false,
+                            dry_run: false,
+                            stats_before: stats.clone(),
+                            stats_after: stats,
+                        });
+                    }
+                    let mut state = lock(&shared);
+                    state.status.failures += 1;
+                    state.status.backing_off = true;
+                    continue;
+                }
+
+                let engine = if used_percent >= settings.crit_threshold {
+                    &crit_engine
+                } else {
+                    &warn_engine
+                };
+                match &log {
+                    Some(log) => {
+                        engine.heal_logged(log);
+                    }
+                    None => {
+                        engine.heal();
+                    }
+                }
+                attempts += 1;
+                lock(&shared).status.healing_runs += 1;
+            }
+        });
+
+        HealingScheduler {
+            state,
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    /// Set the used percentages at which healing starts and escalates.
+    pub fn with_thresholds(self, warn_threshold: f64, crit_threshold: f64) -> Self {
+        {
+            let mut state = lock(&self.state);
+            state.settings.warn_threshold = warn_threshold;
+            state.settings.crit_threshold = crit_threshold;
+        }
+        self
+    }
+
+    /// Set how many healing runs may fail to bring usage down before backing off.
+    pub fn with_max_attempts(self, max_attempts: u32) -> Self {
+        lock(&self.state).settings.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set how long to back off after healing gave up.
+    pub fn with_cooldown(self, cooldown: Duration) -> Self {
+        lock(&self.state).settings.cooldown = cooldown;
+        self
+    }
+
+    /// Record every healing action in `log`.
+    pub fn with_log(self, log: Arc<Mutex<HealingLog>>) -> Self {
+        lock(&self.state).log = Some(log);
+        self
+    }
+
+    /// Counters as of the last check.
+    pub fn status(&self) -> SchedulerStatus {
+        lock(&self.state).status
+    }
+
+    /// Signal the scheduler thread to exit and wait for it.
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for HealingScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn lock(state: &Mutex<SchedulerState>) -> std::sync::MutexGuard<'_, SchedulerState> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,66 @@
+//! Allocations that are wiped before they are freed.
+
+use alloc::alloc::{alloc_zeroed, dealloc, Layout};
+use core::sync::atomic::{compiler_fence, Ordering};
+
+/// Heap buffer for secrets such as keys or passwords.
+///
+/// Starts zeroed and is overwritten with zeros before it is handed back to
+/// the allocator, so its contents do not linger in freed memory.
+pub struct SecureAlloc {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+unsafe impl Send for SecureAlloc {}
+
+impl SecureAlloc {
+    /// Allocate a zeroed `size` byte buffer.
+    ///
+    /// Returns `None` if `size` is zero or the allocation fails.
+    pub fn new(size: usize) -> Option<Self> {
+        if size == 0 {
+            return None;
+        }
+
+        let layout = Layout::from_size_align(size, core::mem::align_of::<usize>()).ok()?;
+        let ptr = unsafe { alloc_zeroed(layout) };
+        if ptr.is_null() {
+            return None;
+        }
+
+        Some(SecureAlloc { ptr, layout })
+    }
+
+    pub fn len(&self) -> usize {
+        self.layout.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layout.size() == 0
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.ptr, self.layout.size()) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr, self.layout.size()) }
+    }
+}
+
+impl Drop for SecureAlloc {
+    fn drop(&mut self) {
+        unsafe {
+            core::ptr::write_bytes(self.ptr, 0, self.layout.size());
+        }
+        // Stores right before a free are dead to the optimiser; make the
+        // pointer escape so the wipe is kept
+        core::hint::black_box(self.ptr);
+        compiler_fence(Ordering::SeqCst);
+
+        unsafe {
+            dealloc(self.ptr, self.layout);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! `MemoryStats` published through POSIX shared memory.
+//!
+//! One process writes snapshots, any number of processes read them, with no
+//! sockets or locks in between.
+
+use std::ffi::CString;
+use std::io;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+use super::format::{from_bincode, to_bincode};
+use super::MemoryStats;
+
+/// Room for one encoded snapshot; bincode needs about 100 bytes.
+const DATA_WORDS: usize = 64;
+const DATA_CAPACITY: usize = DATA_WORDS * 8;
+
+/// Reads retried this often while a writer is active before giving up.
+const MAX_READ_RETRIES: usize = 64;
+
+const SHM_MODE: libc::mode_t = 0o600;
+
+/// Memory layout of the shared region.
+///
+/// `seq` is odd while a write is in progress. The payload is copied word by
+/// word through atomics so a torn read is detected rather than undefined.
+#[repr(C)]
+struct Region {
+    seq: AtomicU64,
+    len: AtomicU64,
+    data: [AtomicU64; DATA_WORDS],
+}
+
+/// A seqlock-protected `MemoryStats` slot in a named shared memory region.
+pub struct SharedStatsBuffer {
+    fd: libc::c_int,
+    region: *mut Region,
+}
+
+// The region is only accessed through atomics
+unsafe impl Send for SharedStatsBuffer {}
+unsafe impl Sync for SharedStatsBuffer {}
+
+impl SharedStatsBuffer {
+    /// Create the region `name`, or reuse it if it already exists.
+    ///
+    /// `name` is given a leading `/` if it lacks one.
+    pub fn create(name: &str) -> io::Result<SharedStatsBuffer> {
+        let fd = shm_open(name, libc::O_CREAT | libc::O_RDWR)?;
+
+        let size = std::mem::size_of::<Region>() as libc::off_t;
+        if unsafe { libc::ftruncate(fd, size) } != 0 {
+            let error = io::Error::last_os_error();
+            unsafe {
+                libc::close(fd);
+            }
+            return Err(error);
+        }
+
+        Self::map(fd)
+    }
+
+    /// Open the existing region `name`.
+    pub fn open(name: &str) -> io::Result<SharedStatsBuffer> {
+        let fd = shm_open(name, libc::O_RDWR)?;
+
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        if unsafe { libc::fstat(fd, &mut stat) } != 0 || (stat.st_size as usize) < std::mem::size_of::<Region>() {
+            unsafe {
+                libc::close(fd);
+            }
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "shared memory region is too small"));
+        }
+
+        Self::map(fd)
+    }
+
+    /// Remove the region `name`; processes that have it mapped keep their mapping.
+    pub fn unlink(name: &str) -> io::Result<()> {
+        let name = shm_name(name)?;
+        if unsafe { libc::shm_unlink(name.as_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn map(fd: libc::c_int) -> io::Result<SharedStatsBuffer> {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                std::mem::size_of::<Region>(),
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            let error = io::Error::last_os_error();
+            unsafe {
+                libc::close(fd);
+            }
+            return Err(error);
+        }
+
+        Ok(SharedStatsBuffer {
+            fd,
+            region: ptr as *mut Region,
+        })
+    }
+
+    fn region(&self) -> &Region {
+        unsafe { &*self.region }
+    }
+
+    /// Publish `stats`, replacing the previous snapshot.
+    ///
+    /// Concurrent writers take turns; snapshots that do not fit are rejected.
+    pub fn write(&self, stats: &MemoryStats) -> io::Result<()> {
+        let bytes = to_bincode(stats).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if bytes.len() > DATA_CAPACITY {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "snapshot does not fit the shared buffer"));
+        }
+
+        let region = self.region();
+
+        // Make seq odd to claim the slot
+        let mut seq = region.seq.load(Ordering::Relaxed);
+        loop {
+            if seq % 2 == 1 {
+                std::hint::spin_loop();
+                seq = region.seq.load(Ordering::Relaxed);
+                continue;
+            }
+            match region.seq.compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(current) => seq = current,
+            }
+        }
+        fence(Ordering::Release);
+
+        region.len.store(bytes.len() as u64, Ordering::Relaxed);
+        for (word, chunk) in region.data.iter().zip(bytes.chunks(8)) {
+            let mut buf = [0u8; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            word.store(u64::from_ne_bytes(buf), Ordering::Relaxed);
+        }
+
+        region.seq.store(seq + 2, Ordering::Release);
+        Ok(())
+    }
+
+    /// Latest snapshot, or `None` if nothing was written yet or a writer kept
+    /// the slot busy for every retry.
+    pub fn read(&self) -> Option<MemoryStats> {
+        let region = self.region();
+
+        for _ in 0..MAX_READ_RETRIES {
+            let before = region.seq.load(Ordering::Acquire);
+            if before == 0 {
+                return None;
+            }
+            if before % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+
+            let len = (region.len.load(Ordering::Relaxed) as usize).min(DATA_CAPACITY);
+            let mut bytes = Vec::with_capacity(DATA_CAPACITY);
+            for word in region.data.iter().take(len.div_ceil(8)) {
+                bytes.extend_from_slice(&word.load(Ordering::Relaxed).to_ne_bytes());
+            }
+            bytes.truncate(len);
+
+            fence(Ordering::Acquire);
+            if region.seq.load(Ordering::Relaxed) == before {
+                return from_bincode(&bytes).ok();
+            }
+        }
+
+        None
+    }
+}
+
+impl Drop for SharedStatsBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.region as *mut libc::c_void, std::mem::size_of::<Region>());
+            libc::close(self.fd);
+        }
+    }
+}
+
+fn shm_name(name: &str) -> io::Result<CString> {
+    let name = if name.starts_with('/') { name.to_string() } else { format!("/{}", name) };
+    CString::new(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
+
+fn shm_open(name: &str, flags: libc::c_int) -> io::Result<libc::c_int> {
+    let name = shm_name(name)?;
+
+    // Variadic on macOS, so the mode has to be passed promoted
+    #[cfg(target_os = "macos")]
+    let fd = unsafe { libc::shm_open(name.as_ptr(), flags, SHM_MODE as libc::c_uint) };
+    #[cfg(not(target_os = "macos"))]
+    let fd = unsafe { libc::shm_open(name.as_ptr(), flags, SHM_MODE) };
+
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(fd)
+}
@@ -0,0 +1,147 @@
+//! Compact binary files of `MemoryStats` snapshots for offline analysis.
+//!
+//! A file starts with the 8-byte magic `SHMSNAP\0`, a little-endian `u32`
+//! format version and a little-endian `u64` record count. Each record is a
+//! little-endian `u32` length followed by that many bytes of `to_bincode`
+//! output.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::format::{from_bincode, to_bincode};
+use super::MemoryStats;
+
+const MAGIC: &[u8; 8] = b"SHMSNAP\0";
+// Largest record `load` accepts; real records are a few hundred bytes
+const MAX_RECORD_LEN: usize = 64 * 1024;
+
+/// Format version written by `Snapshot::save`.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Reads and writes snapshot files.
+pub struct Snapshot;
+
+impl Snapshot {
+    /// Write `stats` to `path`, replacing any existing file.
+    pub fn save(stats: &[MemoryStats], path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        writer.write_all(&(stats.len() as u64).to_le_bytes())?;
+        for record in stats {
+            let bytes = to_bincode(record).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            if bytes.len() > MAX_RECORD_LEN {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "snapshot record larger than 64 KiB"));
+            }
+            writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+            writer.write_all(&bytes)?;
+        }
+
+        writer.flush()
+    }
+
+    /// Read every snapshot from a file written by `save`.
+    ///
+    /// Fails with `InvalidData` if the magic bytes are wrong, the version is
+    /// not `SNAPSHOT_VERSION`, or a record claims more than 64 KiB or cannot
+    /// be decoded, and with `UnexpectedEof` if the file holds fewer records
+    /// than its header says.
+    pub fn load(path: &Path) -> io::Result<Vec<MemoryStats>> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let version = read_header(&mut reader)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "snapshot format version {} is not supported; this build reads version {}",
+                    version, SNAPSHOT_VERSION
+                ),
+            ));
+        }
+
+        let count = read_u64(&mut reader)?;
+        // Cap the preallocation so a corrupt count cannot exhaust memory
+        let mut stats = Vec::with_capacity(count.min(4096) as usize);
+        for _ in 0..count {
+            let len = read_u32(&mut reader)? as usize;
+            if len > MAX_RECORD_LEN {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("snapshot record of {} bytes exceeds the 64 KiB limit", len),
+                ));
+            }
+            let mut bytes = vec![0u8; len];
+            reader.read_exact(&mut bytes)?;
+            stats.push(from_bincode(&bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?);
+        }
+
+        Ok(stats)
+    }
+
+    /// Upgrade a version 1 file at `path` to version 2 in place.
+    ///
+    /// Hook for the next format change: version 1 is still current, so files
+    /// at version 1 are left as they are. Files at any other version, or that
+    /// are not snapshots at all, fail with `InvalidData`.
+    pub fn migrate_v1_to_v2(path: &Path) -> io::Result<()> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        match read_header(&mut reader)? {
+            1 => Ok(()),
+            version => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected a version 1 snapshot, found version {}", version),
+            )),
+        }
+    }
+}
+
+/// Check the magic bytes and return the format version.
+fn read_header(reader: &mut impl Read) -> io::Result<u32> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a memory snapshot file"));
+    }
+
+    read_u32(reader)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("shm-snapshot-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn load_rejects_oversized_record_length() {
+        let path = temp_path("oversized");
+        let mut contents = Vec::new();
+        contents.extend_from_slice(MAGIC);
+        contents.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        contents.extend_from_slice(&1u64.to_le_bytes());
+        contents.extend_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(&path, &contents).unwrap();
+
+        let err = Snapshot::load(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
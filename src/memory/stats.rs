@@ -0,0 +1,72 @@
+//! Platform-independent views of `MemoryStats`.
+//!
+//! Each platform fills `MemoryStats` with slightly different semantics, so
+//! decisions such as "should we heal now?" should be made on the normalized
+//! numbers instead.
+
+use super::MemoryStats;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NormalizedStats {
+    pub effective_free: u64,      // Memory an application can claim without reclaim
+    pub effective_available: u64, // Memory an application can claim including reclaim
+    pub effective_used: u64,      // Memory that cannot be claimed back
+    pub used_percent: f64,        // effective_used as a percentage of total
+}
+
+/// Normalize stats collected on the current compile target.
+pub fn normalize_stats_for_platform(stats: MemoryStats) -> NormalizedStats {
+    #[cfg(target_os = "linux")]
+    return normalize_linux(&stats);
+
+    #[cfg(target_os = "macos")]
+    return normalize_macos(&stats);
+
+    #[cfg(target_os = "windows")]
+    return normalize_windows(&stats);
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    return normalized(&stats, stats.free, stats.available);
+}
+
+/// Linux reports buffers and page cache separately from free memory, but
+/// both are reclaimable so they count as free for applications.
+#[cfg(target_os = "linux")]
+fn normalize_linux(stats: &MemoryStats) -> NormalizedStats {
+    let reclaimable = stats.buffers.unwrap_or(0) + stats.cached.unwrap_or(0);
+    let effective_free = (stats.free + reclaimable).min(stats.total);
+
+    normalized(stats, effective_free, stats.available)
+}
+
+/// macOS counts speculative pages as free even though they hold cached data.
+#[cfg(target_os = "macos")]
+fn normalize_macos(stats: &MemoryStats) -> NormalizedStats {
+    let effective_free = stats.free.saturating_sub(stats.speculative.unwrap_or(0));
+
+    normalized(stats, effective_free, stats.available)
+}
+
+/// Windows only reports available memory, so free is the same number.
+#[cfg(target_os = "windows")]
+fn normalize_windows(stats: &MemoryStats) -> NormalizedStats {
+    normalized(stats, stats.available, stats.available)
+}
+
+fn normalized(stats: &MemoryStats, effective_free: u64, effective_available: u64) -> NormalizedStats {
+    let effective_used = stats.total.saturating_sub(effective_available.max(effective_free));
+
+    let used_percent = if stats.total > 0 {
+        (effective_used as f64 / stats.total as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    NormalizedStats {
+        effective_free,
+        effective_available,
+        effective_used,
+        used_percent,
+    }
+}
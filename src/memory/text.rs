@@ -0,0 +1,306 @@
+//! String encodings of `MemoryStats` that need only `core` and `alloc`.
+//!
+//! These are shared with the `no_std` build, where this module is the whole
+//! of `memory::format`; the std build re-exports them from `format`.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(feature = "influx")]
+use super::memory_stats::parse_timestamp_ms;
+use super::MemoryStats;
+
+/// StatsD dialect to emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsdFormat {
+    Plain,     // name:value|g
+    DogStatsd, // name:value|g|#tag:value,...
+}
+
+/// Render `stats` as plain StatsD gauge lines.
+pub fn to_statsd(stats: &MemoryStats, prefix: &str) -> String {
+    to_statsd_with_format(stats, prefix, StatsdFormat::Plain, &[])
+}
+
+/// Render `stats` as StatsD gauge lines in the given dialect.
+///
+/// `tags` are only emitted for `StatsdFormat::DogStatsd`.
+pub fn to_statsd_with_format(stats: &MemoryStats, prefix: &str, format: StatsdFormat, tags: &[(&str, &str)]) -> String {
+    render_statsd(stats, prefix, "", format, tags)
+}
+
+/// Shared by `to_statsd_with_format` and the one-shot senders; `byte_suffix`
+/// is appended to the names of the gauges measured in bytes.
+pub(crate) fn render_statsd(stats: &MemoryStats, prefix: &str, byte_suffix: &str, format: StatsdFormat, tags: &[(&str, &str)]) -> String {
+    let suffix = match format {
+        StatsdFormat::DogStatsd if !tags.is_empty() => {
+            let tags: Vec<String> = tags.iter().map(|(key, value)| format!("{}:{}", key, value)).collect();
+            format!("|#{}", tags.join(","))
+        }
+        _ => String::new(),
+    };
+
+    let mut gauges: Vec<(&str, &str, String)> = vec![
+        ("total", byte_suffix, stats.total.to_string()),
+        ("free", byte_suffix, stats.free.to_string()),
+        ("available", byte_suffix, stats.available.to_string()),
+        ("used", byte_suffix, stats.used.to_string()),
+        ("used_percent", "", stats.used_percent.to_string()),
+    ];
+    if let Some(buffers) = stats.buffers {
+        gauges.push(("buffers", byte_suffix, buffers.to_string()));
+    }
+    if let Some(cached) = stats.cached {
+        gauges.push(("cached", byte_suffix, cached.to_string()));
+    }
+    if let Some(speculative) = stats.speculative {
+        gauges.push(("speculative", byte_suffix, speculative.to_string()));
+    }
+    if let Some(score) = stats.fragmentation_score {
+        gauges.push(("fragmentation_score", "", score.to_string()));
+    }
+    if let Some(swap_total) = stats.swap_total {
+        gauges.push(("swap_total", byte_suffix, swap_total.to_string()));
+    }
+    if let Some(swap_free) = stats.swap_free {
+        gauges.push(("swap_free", byte_suffix, swap_free.to_string()));
+    }
+    if let Some(dirty) = stats.dirty {
+        gauges.push(("dirty", byte_suffix, dirty.to_string()));
+    }
+    if let Some(writeback) = stats.writeback {
+        gauges.push(("writeback", byte_suffix, writeback.to_string()));
+    }
+
+    let mut output = String::new();
+    for (name, unit, value) in gauges {
+        if !prefix.is_empty() {
+            output.push_str(prefix);
+            output.push('.');
+        }
+        output.push_str(&format!("memory.{}{}:{}|g{}\n", name, unit, value, suffix));
+    }
+
+    output
+}
+
+/// Render `stats` in the Prometheus text exposition format.
+#[cfg(feature = "prometheus")]
+pub fn format_prometheus(stats: &MemoryStats) -> String {
+    let gauges: [(&str, &str, String); 5] = [
+        ("memory_total_bytes", "Total physical memory in bytes.", stats.total.to_string()),
+        ("memory_free_bytes", "Free physical memory in bytes.", stats.free.to_string()),
+        ("memory_available_bytes", "Memory available for new allocations in bytes.", stats.available.to_string()),
+        ("memory_used_bytes", "Used physical memory in bytes.", stats.used.to_string()),
+        ("memory_used_ratio", "Used physical memory as a fraction of total.", (stats.used_percent / 100.0).to_string()),
+    ];
+
+    let mut output = String::new();
+    for (name, help, value) in gauges.iter() {
+        output.push_str(&format!("# HELP {} {}\n", name, help));
+        output.push_str(&format!("# TYPE {} gauge\n", name));
+        output.push_str(&format!("{}{{job=\"self_healing_memory\"}} {}\n", name, value));
+    }
+
+    output
+}
+
+/// Render `stats` as one InfluxDB line protocol row.
+///
+/// The timestamp is in nanoseconds, taken from `stats.timestamp`; it is left
+/// off when that cannot be parsed so the server assigns one on write.
+#[cfg(feature = "influx")]
+pub fn format_influx(stats: &MemoryStats, measurement: &str, tags: &[(&str, &str)]) -> String {
+    let mut line = escape_influx(measurement, &[',', ' ']);
+    for (key, value) in tags {
+        line.push(',');
+        line.push_str(&escape_influx(key, &[',', '=', ' ']));
+        line.push('=');
+        line.push_str(&escape_influx(value, &[',', '=', ' ']));
+    }
+
+    let mut fields = vec![
+        format!("total={}i", stats.total),
+        format!("free={}i", stats.free),
+        format!("available={}i", stats.available),
+        format!("used={}i", stats.used),
+        format!("used_percent={}", stats.used_percent),
+    ];
+    if let Some(buffers) = stats.buffers {
+        fields.push(format!("buffers={}i", buffers));
+    }
+    if let Some(cached) = stats.cached {
+        fields.push(format!("cached={}i", cached));
+    }
+    line.push(' ');
+    line.push_str(&fields.join(","));
+
+    if let Some(millis) = parse_timestamp_ms(&stats.timestamp) {
+        line.push_str(&format!(" {}", millis * 1_000_000));
+    }
+
+    line
+}
+
+/// Backslash-escape the characters line protocol treats as delimiters.
+#[cfg(feature = "influx")]
+fn escape_influx(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Column names matching `to_csv_row`.
+#[cfg(feature = "csv")]
+pub fn csv_header() -> &'static str {
+    "timestamp,total,free,available,used,used_percent,buffers,cached,speculative"
+}
+
+/// Render `stats` as one CSV row without a trailing newline.
+///
+/// Platform-specific fields a snapshot lacks are left empty.
+#[cfg(feature = "csv")]
+pub fn to_csv_row(stats: &MemoryStats) -> String {
+    let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+
+    format!(
+        "{},{},{},{},{},{},{},{},{}",
+        stats.timestamp,
+        stats.total,
+        stats.free,
+        stats.available,
+        stats.used,
+        stats.used_percent,
+        optional(stats.buffers),
+        optional(stats.cached),
+        optional(stats.speculative),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_stats() -> MemoryStats {
+        MemoryStats {
+            total: 1000,
+            free: 200,
+            available: 300,
+            used: 700,
+            used_percent: 70.0,
+            buffers: Some(10),
+            cached: None,
+            speculative: None,
+            fragmentation_score: None,
+            swap_total: None,
+            swap_free: None,
+            dirty: None,
+            writeback: None,
+            timestamp: String::from("1970-01-01T00:00:00Z"),
+        }
+    }
+
+    #[test]
+    fn statsd_plain() {
+        let output = to_statsd_with_format(&sample_stats(), "app", StatsdFormat::Plain, &[("env", "prod")]);
+        assert_eq!(
+            output,
+            "app.memory.total:1000|g\n\
+             app.memory.free:200|g\n\
+             app.memory.available:300|g\n\
+             app.memory.used:700|g\n\
+             app.memory.used_percent:70|g\n\
+             app.memory.buffers:10|g\n"
+        );
+    }
+
+    #[test]
+    fn statsd_plain_without_prefix() {
+        assert_eq!(to_statsd(&sample_stats(), ""), to_statsd_with_format(&sample_stats(), "", StatsdFormat::Plain, &[]));
+        assert!(to_statsd(&sample_stats(), "").starts_with("memory.total:1000|g\n"));
+    }
+
+    #[test]
+    fn dogstatsd_with_tags() {
+        let output = to_statsd_with_format(
+            &sample_stats(),
+            "app",
+            StatsdFormat::DogStatsd,
+            &[("env", "prod"), ("host", "a")],
+        );
+        assert_eq!(
+            output,
+            "app.memory.total:1000|g|#env:prod,host:a\n\
+             app.memory.free:200|g|#env:prod,host:a\n\
+             app.memory.available:300|g|#env:prod,host:a\n\
+             app.memory.used:700|g|#env:prod,host:a\n\
+             app.memory.used_percent:70|g|#env:prod,host:a\n\
+             app.memory.buffers:10|g|#env:prod,host:a\n"
+        );
+    }
+
+    #[test]
+    fn byte_suffix_skips_used_percent() {
+        let output = render_statsd(&sample_stats(), "", "_bytes", StatsdFormat::DogStatsd, &[("env", "prod")]);
+        assert_eq!(
+            output,
+            "memory.total_bytes:1000|g|#env:prod\n\
+             memory.free_bytes:200|g|#env:prod\n\
+             memory.available_bytes:300|g|#env:prod\n\
+             memory.used_bytes:700|g|#env:prod\n\
+             memory.used_percent:70|g|#env:prod\n\
+             memory.buffers_bytes:10|g|#env:prod\n"
+        );
+    }
+
+    #[test]
+    fn statsd_every_optional_field() {
+        let stats = MemoryStats {
+            cached: Some(20),
+            speculative: Some(30),
+            fragmentation_score: Some(0.25),
+            swap_total: Some(4096),
+            swap_free: Some(1024),
+            dirty: Some(5),
+            writeback: Some(6),
+            ..sample_stats()
+        };
+        assert_eq!(
+            render_statsd(&stats, "app", "_bytes", StatsdFormat::Plain, &[]),
+            "app.memory.total_bytes:1000|g\n\
+             app.memory.free_bytes:200|g\n\
+             app.memory.available_bytes:300|g\n\
+             app.memory.used_bytes:700|g\n\
+             app.memory.used_percent:70|g\n\
+             app.memory.buffers_bytes:10|g\n\
+             app.memory.cached_bytes:20|g\n\
+             app.memory.speculative_bytes:30|g\n\
+             app.memory.fragmentation_score:0.25|g\n\
+             app.memory.swap_total_bytes:4096|g\n\
+             app.memory.swap_free_bytes:1024|g\n\
+             app.memory.dirty_bytes:5|g\n\
+             app.memory.writeback_bytes:6|g\n"
+        );
+    }
+
+    #[test]
+    fn dogstatsd_without_tags() {
+        let output = to_statsd_with_format(&sample_stats(), "app", StatsdFormat::DogStatsd, &[]);
+        assert_eq!(
+            output,
+            "app.memory.total:1000|g\n\
+             app.memory.free:200|g\n\
+             app.memory.available:300|g\n\
+             app.memory.used:700|g\n\
+             app.memory.used_percent:70|g\n\
+             app.memory.buffers:10|g\n"
+        );
+    }
+}
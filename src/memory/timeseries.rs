@@ -0,0 +1,55 @@
+//! Newline-delimited JSON log of `MemoryStats` snapshots.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use super::MemoryStats;
+
+/// Append-only file holding one JSON-encoded snapshot per line.
+///
+/// A lightweight alternative to a time-series database; the format is easy
+/// to load with `jq`, pandas or any JSON lines reader.
+pub struct TimeSeriesLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl TimeSeriesLog {
+    /// Open `path` for appending, creating it if it does not exist.
+    pub fn open(path: &Path) -> io::Result<TimeSeriesLog> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(TimeSeriesLog {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    /// Append `stats` as one line.
+    pub fn record(&mut self, stats: &MemoryStats) -> io::Result<()> {
+        let mut line = serde_json::to_string(stats).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        line.push('\n');
+
+        // One write per line so concurrent appenders do not interleave records
+        self.file.write_all(line.as_bytes())
+    }
+
+    /// Read every recorded snapshot, oldest first.
+    ///
+    /// Blank lines are skipped; reading stops at the first I/O error. Yields
+    /// nothing if the file can no longer be opened.
+    pub fn replay(&self) -> impl Iterator<Item = Result<MemoryStats, serde_json::Error>> {
+        File::open(&self.path)
+            .map(|file| BufReader::new(file).lines())
+            .into_iter()
+            .flatten()
+            .map_while(Result::ok)
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(&line))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
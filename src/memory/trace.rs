@@ -0,0 +1,149 @@
+//! Compact allocation call-site recording.
+//!
+//! Storing a full backtrace per sample is expensive, so stacks are interned:
+//! every distinct symbol is stored once in a global table, every distinct
+//! stack once as a list of symbol ids, and samples refer to a `TraceId`.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// One frame of a captured call stack.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+    pub symbol: String,
+}
+
+impl Frame {
+    pub fn new(symbol: &str) -> Self {
+        Frame {
+            symbol: symbol.to_string(),
+        }
+    }
+}
+
+/// Identifier of an interned symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolId(pub u32);
+
+/// Identifier of an interned call stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TraceId(pub u32);
+
+/// Deduplicates frame symbols.
+///
+/// Interned strings are leaked on purpose: they live for the rest of the
+/// process, which lets `resolve` hand out `&'static str` without copying.
+#[derive(Default)]
+struct SymbolTable {
+    ids: HashMap<&'static str, SymbolId>,
+    symbols: Vec<&'static str>,
+}
+
+impl SymbolTable {
+    fn intern(&mut self, symbol: &str) -> SymbolId {
+        if let Some(id) = self.ids.get(symbol) {
+            return *id;
+        }
+
+        let symbol: &'static str = Box::leak(symbol.to_string().into_boxed_str());
+        let id = SymbolId(self.symbols.len() as u32);
+        self.symbols.push(symbol);
+        self.ids.insert(symbol, id);
+        id
+    }
+}
+
+#[derive(Default)]
+struct TraceTable {
+    symbols: SymbolTable,
+    ids: HashMap<Vec<SymbolId>, TraceId>,
+    stacks: Vec<Vec<SymbolId>>,
+    bytes: Vec<u64>, // Total bytes attributed to each trace
+}
+
+static TRACES: Mutex<Option<TraceTable>> = Mutex::new(None);
+
+fn lock_traces() -> MutexGuard<'static, Option<TraceTable>> {
+    // A panic while holding the lock cannot leave the tables inconsistent
+    // in a way that matters for profiling, so keep going
+    let mut guard = TRACES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if guard.is_none() {
+        *guard = Some(TraceTable::default());
+    }
+    guard
+}
+
+/// Entry point for recording and querying interned allocation stacks.
+pub struct AllocationTrace;
+
+impl AllocationTrace {
+    /// Intern a call stack and return its id.
+    pub fn record(frames: &[Frame]) -> TraceId {
+        let mut guard = lock_traces();
+        let table = guard.as_mut().unwrap();
+
+        let stack: Vec<SymbolId> = frames.iter().map(|frame| table.symbols.intern(&frame.symbol)).collect();
+
+        if let Some(id) = table.ids.get(&stack) {
+            return *id;
+        }
+
+        let id = TraceId(table.stacks.len() as u32);
+        table.stacks.push(stack.clone());
+        table.bytes.push(0);
+        table.ids.insert(stack, id);
+        id
+    }
+
+    /// Intern a call stack and attribute an allocation of `bytes` to it.
+    pub fn record_allocation(frames: &[Frame], bytes: u64) -> TraceId {
+        let id = Self::record(frames);
+        Self::add_bytes(id, bytes);
+        id
+    }
+
+    /// Attribute `bytes` to an already recorded trace.
+    pub fn add_bytes(id: TraceId, bytes: u64) {
+        let mut guard = lock_traces();
+        let table = guard.as_mut().unwrap();
+
+        if let Some(total) = table.bytes.get_mut(id.0 as usize) {
+            *total += bytes;
+        }
+    }
+
+    /// Look up the symbols of a recorded stack, innermost frame first.
+    ///
+    /// Returns an empty list for an unknown id.
+    pub fn resolve(id: TraceId) -> Vec<&'static str> {
+        let guard = lock_traces();
+        let table = guard.as_ref().unwrap();
+
+        match table.stacks.get(id.0 as usize) {
+            Some(stack) => stack.iter().map(|symbol| table.symbols.symbols[symbol.0 as usize]).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The `n` stacks with the most bytes attributed to them.
+    pub fn dump_top_n(n: usize) -> Vec<(Vec<&'static str>, u64)> {
+        let guard = lock_traces();
+        let table = guard.as_ref().unwrap();
+
+        let mut order: Vec<usize> = (0..table.stacks.len()).collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(table.bytes[index]));
+
+        order
+            .into_iter()
+            .take(n)
+            .map(|index| {
+                let symbols = table.stacks[index]
+                    .iter()
+                    .map(|symbol| table.symbols.symbols[symbol.0 as usize])
+                    .collect();
+                (symbols, table.bytes[index])
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,133 @@
+//! Page arithmetic shared by the `mprotect`/`madvise`/`mincore` style helpers,
+//! and byte counts formatted for people.
+
+use std::fmt;
+
+/// Size of a memory page in bytes.
+pub fn page_size() -> usize {
+    #[cfg(unix)]
+    {
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if size > 0 {
+            return size as usize;
+        }
+    }
+
+    4096
+}
+
+/// Expand `[ptr, ptr + len)` outward to page boundaries.
+///
+/// Returns `(base, aligned_len)` where `base` is `ptr` rounded down and
+/// `base + aligned_len` is `ptr + len` rounded up. A zero `len` yields a
+/// zero `aligned_len`.
+pub fn align_to_page(ptr: usize, len: usize) -> (usize, usize) {
+    align_to_page_size(ptr, len, page_size())
+}
+
+/// Same as `align_to_page` but for an explicit power-of-two `page_size`.
+pub fn align_to_page_size(ptr: usize, len: usize, page_size: usize) -> (usize, usize) {
+    debug_assert!(page_size.is_power_of_two());
+
+    let mask = page_size - 1;
+    let base = ptr & !mask;
+
+    if len == 0 {
+        return (base, 0);
+    }
+
+    // Saturate rather than wrap so a bogus range can never shrink
+    let end = ptr.saturating_add(len).saturating_add(mask) & !mask;
+
+    (base, end - base)
+}
+
+/// Whether `ptr` sits on a page boundary.
+pub fn is_page_aligned(ptr: usize) -> bool {
+    ptr & (page_size() - 1) == 0
+}
+
+/// Number of pages needed to hold `size` bytes.
+pub fn pages_required(size: usize) -> usize {
+    pages_required_for_page_size(size, page_size())
+}
+
+/// Same as `pages_required` but for an explicit `page_size`.
+pub fn pages_required_for_page_size(size: usize, page_size: usize) -> usize {
+    size.div_ceil(page_size)
+}
+
+/// Byte count shown in the largest binary unit it fills, e.g. `6.1 GiB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanBytes(pub u64);
+
+impl fmt::Display for HumanBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const UNITS: [(&str, u64); 3] = [("GiB", 1 << 30), ("MiB", 1 << 20), ("KiB", 1 << 10)];
+
+        for (unit, size) in UNITS {
+            if self.0 >= size {
+                return write!(f, "{:.1} {}", self.0 as f64 / size as f64, unit);
+            }
+        }
+        write!(f, "{} B", self.0)
+    }
+}
+
+/// `bytes` formatted like `HumanBytes`.
+pub fn format_bytes(bytes: u64) -> String {
+    HumanBytes(bytes).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_to_page_size_4k() {
+        assert_eq!(align_to_page_size(0x1000, 0x1000, 4096), (0x1000, 0x1000));
+        assert_eq!(align_to_page_size(0x1001, 1, 4096), (0x1000, 0x1000));
+        assert_eq!(align_to_page_size(0x1fff, 2, 4096), (0x1000, 0x2000));
+        assert_eq!(align_to_page_size(0x1234, 0, 4096), (0x1000, 0));
+    }
+
+    #[test]
+    fn align_to_page_size_16k() {
+        assert_eq!(align_to_page_size(0x4000, 0x4000, 16384), (0x4000, 0x4000));
+        assert_eq!(align_to_page_size(0x5000, 0x1000, 16384), (0x4000, 0x4000));
+        assert_eq!(align_to_page_size(0x7fff, 2, 16384), (0x4000, 0x8000));
+        assert_eq!(align_to_page_size(0x4001, 0, 16384), (0x4000, 0));
+    }
+
+    #[test]
+    fn pages_required_for_both_page_sizes() {
+        assert_eq!(pages_required_for_page_size(0, 4096), 0);
+        assert_eq!(pages_required_for_page_size(1, 4096), 1);
+        assert_eq!(pages_required_for_page_size(4096, 4096), 1);
+        assert_eq!(pages_required_for_page_size(4097, 4096), 2);
+        assert_eq!(pages_required_for_page_size(16384, 16384), 1);
+        assert_eq!(pages_required_for_page_size(16385, 16384), 2);
+        assert_eq!(pages_required_for_page_size(4096 * 4, 16384), 1);
+    }
+
+    #[test]
+    fn page_size_is_power_of_two() {
+        let size = page_size();
+        assert!(size.is_power_of_two());
+        assert!(is_page_aligned(0));
+        assert!(is_page_aligned(size));
+        assert!(!is_page_aligned(size + 1));
+    }
+
+    #[test]
+    fn human_bytes_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 << 20), "5.0 MiB");
+        assert_eq!(format_bytes(16 << 30), "16.0 GiB");
+        assert_eq!(format_bytes(4096 << 30), "4096.0 GiB");
+        assert_eq!(HumanBytes(u64::MAX).to_string(), "17179869184.0 GiB");
+    }
+}
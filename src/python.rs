@@ -0,0 +1,128 @@
+//! Native Python module built with PyO3, an alternative to loading the C API
+//! through ctypes.
+
+use pyo3::exceptions::PyKeyError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::memory::history::{MemoryHistory, MemoryTrend};
+use crate::memory::{self, MemoryStats};
+
+/// Current memory statistics as a dict keyed by `MemoryStats` field name.
+#[pyfunction]
+fn get_memory_stats(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    stats_to_dict(py, &memory::get_memory_stats())
+}
+
+/// Ask the OS to drop cached memory; True on success.
+#[pyfunction]
+fn release_memory_cache(py: Python<'_>) -> bool {
+    // Dropping caches can take a while; let other Python threads run meanwhile
+    py.allow_threads(memory::release_memory_cache)
+}
+
+/// Ring buffer of the most recent `capacity` snapshots.
+#[pyclass(name = "MemoryHistory")]
+struct PyMemoryHistory {
+    inner: MemoryHistory,
+}
+
+#[pymethods]
+impl PyMemoryHistory {
+    #[new]
+    fn new(capacity: usize) -> Self {
+        PyMemoryHistory {
+            inner: MemoryHistory::new(capacity),
+        }
+    }
+
+    /// Add a dict from `get_memory_stats()`, or take a new sample if none is given.
+    #[pyo3(signature = (stats = None))]
+    fn push(&mut self, stats: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+        let stats = match stats {
+            Some(dict) => dict_to_stats(dict)?,
+            None => memory::get_memory_stats(),
+        };
+        self.inner.push(stats);
+        Ok(())
+    }
+
+    /// Snapshots as dicts, oldest first.
+    fn samples<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner.iter().map(|stats| stats_to_dict(py, stats)).collect()
+    }
+
+    /// `("rising" | "stable" | "falling", slope)`, or None with too few samples.
+    #[pyo3(signature = (min_samples = 2))]
+    fn trend(&self, min_samples: usize) -> Option<(&'static str, f64)> {
+        self.inner.trend(min_samples).map(|trend| match trend {
+            MemoryTrend::Rising(slope) => ("rising", slope),
+            MemoryTrend::Stable => ("stable", 0.0),
+            MemoryTrend::Falling(slope) => ("falling", slope),
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[pymodule]
+fn self_healing_memory(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(get_memory_stats, module)?)?;
+    module.add_function(wrap_pyfunction!(release_memory_cache, module)?)?;
+    module.add_class::<PyMemoryHistory>()?;
+    Ok(())
+}
+
+fn stats_to_dict<'py>(py: Python<'py>, stats: &MemoryStats) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("total", stats.total)?;
+    dict.set_item("free", stats.free)?;
+    dict.set_item("available", stats.available)?;
+    dict.set_item("used", stats.used)?;
+    dict.set_item("used_percent", stats.used_percent)?;
+    dict.set_item("buffers", stats.buffers)?;
+    dict.set_item("cached", stats.cached)?;
+    dict.set_item("speculative", stats.speculative)?;
+    dict.set_item("fragmentation_score", stats.fragmentation_score)?;
+    dict.set_item("swap_total", stats.swap_total)?;
+    dict.set_item("swap_free", stats.swap_free)?;
+    dict.set_item("dirty", stats.dirty)?;
+    dict.set_item("writeback", stats.writeback)?;
+    dict.set_item("timestamp", &stats.timestamp)?;
+    Ok(dict)
+}
+
+/// Inverse of `stats_to_dict`; optional fields may be missing or None.
+fn dict_to_stats(dict: &Bound<'_, PyDict>) -> PyResult<MemoryStats> {
+    fn required<'py, T: FromPyObject<'py>>(dict: &Bound<'py, PyDict>, key: &str) -> PyResult<T> {
+        match dict.get_item(key)? {
+            Some(value) => value.extract(),
+            None => Err(PyKeyError::new_err(key.to_string())),
+        }
+    }
+    fn optional<'py, T: FromPyObject<'py>>(dict: &Bound<'py, PyDict>, key: &str) -> PyResult<Option<T>> {
+        match dict.get_item(key)? {
+            Some(value) => value.extract(),
+            None => Ok(None),
+        }
+    }
+
+    Ok(MemoryStats {
+        total: required(dict, "total")?,
+        free: required(dict, "free")?,
+        available: required(dict, "available")?,
+        used: required(dict, "used")?,
+        used_percent: required(dict, "used_percent")?,
+        buffers: optional(dict, "buffers")?,
+        cached: optional(dict, "cached")?,
+        speculative: optional(dict, "speculative")?,
+        fragmentation_score: optional(dict, "fragmentation_score")?,
+        swap_total: optional(dict, "swap_total")?,
+        swap_free: optional(dict, "swap_free")?,
+        dirty: optional(dict, "dirty")?,
+        writeback: optional(dict, "writeback")?,
+        timestamp: optional(dict, "timestamp")?.unwrap_or_default(),
+    })
+}
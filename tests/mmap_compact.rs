@@ -0,0 +1,62 @@
+//! Moves synthetic `mmap` allocations through `memory::mmap_compact`.
+#![cfg(unix)]
+
+use std::ptr;
+
+use memory_core::memory::mmap_compact;
+
+/// Map `len` bytes of anonymous memory filled with `byte`.
+fn map_filled(len: usize, byte: u8) -> *mut u8 {
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    unsafe { ptr::write_bytes(ptr as *mut u8, byte, len) };
+    ptr as *mut u8
+}
+
+#[test]
+fn objects_keep_their_contents() {
+    let lens = [100, 4096, 0, 33];
+    let mut regions: Vec<(*mut u8, usize)> = lens
+        .iter()
+        .enumerate()
+        .map(|(index, &len)| {
+            if len == 0 {
+                (ptr::null_mut(), 0)
+            } else {
+                (map_filled(len, index as u8 + 1), len)
+            }
+        })
+        .collect();
+
+    assert!(unsafe { mmap_compact(&mut regions) });
+
+    assert_eq!(regions[2], (ptr::null_mut(), 0));
+    let base = regions[0].0;
+    let mut end = base;
+    for (index, &(ptr, len)) in regions.iter().enumerate().filter(|(_, &(_, len))| len > 0) {
+        assert_eq!(len, lens[index]);
+        assert_eq!(ptr as usize % 16, 0);
+        assert!(ptr >= end);
+        let object = unsafe { std::slice::from_raw_parts(ptr, len) };
+        assert!(object.iter().all(|&byte| byte == index as u8 + 1));
+        end = unsafe { ptr.add(len) };
+    }
+
+    assert_eq!(unsafe { libc::munmap(base as *mut libc::c_void, end as usize - base as usize) }, 0);
+}
+
+#[test]
+fn empty_regions_are_left_alone() {
+    let mut regions = [(ptr::null_mut(), 0), (ptr::null_mut(), 0)];
+    assert!(unsafe { mmap_compact(&mut regions) });
+    assert_eq!(regions, [(ptr::null_mut(), 0), (ptr::null_mut(), 0)]);
+}
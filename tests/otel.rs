@@ -0,0 +1,76 @@
+//! Exports the memory gauges through the OpenTelemetry SDK's in-memory
+//! exporter and checks what a collector would receive.
+
+#![cfg(feature = "opentelemetry")]
+
+use std::collections::HashMap;
+
+use memory_core::memory::register_otel_instruments;
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry_sdk::metrics::data::{Gauge, Metric};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::runtime;
+use opentelemetry_sdk::testing::metrics::InMemoryMetricsExporter;
+
+/// Every exported metric, keyed by instrument name.
+fn exported_metrics(exporter: &InMemoryMetricsExporter) -> HashMap<String, Metric> {
+    exporter
+        .get_finished_metrics()
+        .unwrap()
+        .into_iter()
+        .flat_map(|resource| resource.scope_metrics)
+        .flat_map(|scope| scope.metrics)
+        .map(|metric| (metric.name.to_string(), metric))
+        .collect()
+}
+
+/// Gauge data points as (value of the first attribute, value).
+fn points<T: Copy + Send + Sync + std::fmt::Debug + 'static>(metric: &Metric) -> Vec<(Option<String>, T)> {
+    let gauge = metric
+        .data
+        .as_any()
+        .downcast_ref::<Gauge<T>>()
+        .unwrap_or_else(|| panic!("{} is not a gauge of the expected type", metric.name));
+    gauge
+        .data_points
+        .iter()
+        .map(|point| (point.attributes.iter().map(|(_, value)| value.to_string()).next(), point.value))
+        .collect()
+}
+
+#[test]
+fn exports_system_memory_gauges() {
+    // The periodic reader schedules its exports on a Tokio runtime
+    let tokio_runtime = tokio::runtime::Runtime::new().unwrap();
+    let _guard = tokio_runtime.enter();
+
+    let exporter = InMemoryMetricsExporter::default();
+    let provider = SdkMeterProvider::builder()
+        .with_reader(PeriodicReader::builder(exporter.clone(), runtime::Tokio).build())
+        .build();
+    let meter = provider.meter("memory_core-test");
+
+    let instruments = register_otel_instruments(&meter);
+    provider.force_flush().unwrap();
+
+    let metrics = exported_metrics(&exporter);
+    for name in ["system.memory.limit", "system.memory.usage", "system.memory.utilization"] {
+        assert!(metrics.contains_key(name), "{} missing from {:?}", name, metrics.keys());
+    }
+
+    let limit = points::<u64>(&metrics["system.memory.limit"])[0].1;
+    assert!(limit > 0);
+
+    let usage = points::<u64>(&metrics["system.memory.usage"]);
+    for state in ["used", "free"] {
+        let value = usage.iter().find(|(point_state, _)| point_state.as_deref() == Some(state));
+        assert!(value.map_or(false, |(_, value)| *value <= limit), "no {} usage in {:?}", state, usage);
+    }
+
+    let utilization = points::<f64>(&metrics["system.memory.utilization"]);
+    assert_eq!(utilization[0].0.as_deref(), Some("used"));
+    assert!((0.0..=1.0).contains(&utilization[0].1));
+
+    instruments.unregister().unwrap();
+    provider.shutdown().unwrap();
+}
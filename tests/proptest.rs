@@ -0,0 +1,131 @@
+//! Property tests for `MemoryStats` arithmetic and encodings.
+
+use memory_core::memory::{compute_delta, BuddyZone, MemoryStats};
+use proptest::prelude::*;
+
+// Keeps byte counts well inside i64 so deltas cannot wrap
+const MAX_BYTES: u64 = 1 << 50;
+
+prop_compose! {
+    /// A snapshot whose byte counts fit within `total`, with the matching
+    /// used_percent.
+    fn memory_stats()(total in 1..MAX_BYTES)(
+        total in Just(total),
+        free in 0..=total,
+        available in 0..=total,
+        used in 0..=total,
+        buffers in proptest::option::of(0..=total),
+        cached in proptest::option::of(0..=total),
+        speculative in proptest::option::of(0..=total),
+        fragmentation_score in proptest::option::of(0.0..=1.0f64),
+        swap_total in proptest::option::of(0..MAX_BYTES),
+        swap_free in proptest::option::of(0..MAX_BYTES),
+        dirty in proptest::option::of(0..=total),
+        writeback in proptest::option::of(0..=total),
+        timestamp in "[0-9a-zA-Z:.+-]{0,32}",
+    ) -> MemoryStats {
+        MemoryStats {
+            total,
+            free,
+            available,
+            used,
+            used_percent: used as f64 / total as f64 * 100.0,
+            buffers,
+            cached,
+            speculative,
+            fragmentation_score,
+            swap_total,
+            swap_free,
+            dirty,
+            writeback,
+            timestamp,
+        }
+    }
+}
+
+/// Parse a `to_csv_row` row back into a snapshot, by `csv_header` column
+/// name; fields without a column are `None`.
+#[cfg(feature = "csv")]
+fn from_csv_row(row: &str) -> MemoryStats {
+    use std::collections::HashMap;
+
+    let columns: HashMap<&str, &str> = memory_core::memory::csv_header().split(',').zip(row.split(',')).collect();
+    let bytes = |name: &str| columns[name].parse::<u64>().unwrap();
+    let optional = |name: &str| match columns[name] {
+        "" => None,
+        value => Some(value.parse::<u64>().unwrap()),
+    };
+
+    MemoryStats {
+        total: bytes("total"),
+        free: bytes("free"),
+        available: bytes("available"),
+        used: bytes("used"),
+        used_percent: columns["used_percent"].parse().unwrap(),
+        buffers: optional("buffers"),
+        cached: optional("cached"),
+        speculative: optional("speculative"),
+        fragmentation_score: None,
+        swap_total: None,
+        swap_free: None,
+        dirty: None,
+        writeback: None,
+        timestamp: columns["timestamp"].to_string(),
+    }
+}
+
+proptest! {
+    #[test]
+    fn delta_is_antisymmetric(before in memory_stats(), after in memory_stats()) {
+        let forward = compute_delta(&before, &after);
+        let backward = compute_delta(&after, &before);
+
+        prop_assert_eq!(forward.used_delta, -backward.used_delta);
+        prop_assert_eq!(forward.free_delta, -backward.free_delta);
+        prop_assert_eq!(forward.available_delta, -backward.available_delta);
+        prop_assert_eq!(forward.used_percent_delta, -backward.used_percent_delta);
+        prop_assert_eq!(
+            forward.fragmentation_score_changed,
+            backward.fragmentation_score_changed.map(|change| -change)
+        );
+    }
+
+    #[test]
+    fn fragmentation_score_is_a_fraction(free_blocks in any::<[u64; 11]>()) {
+        let zone = BuddyZone {
+            node: 0,
+            zone: String::from("Normal"),
+            free_blocks,
+        };
+
+        let score = zone.fragmentation_score();
+        prop_assert!((0.0..=1.0).contains(&score), "score {} out of range", score);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn csv_row_round_trips(stats in memory_stats()) {
+        let row = memory_core::memory::to_csv_row(&stats);
+        prop_assert_eq!(row.split(',').count(), memory_core::memory::csv_header().split(',').count());
+
+        // Only the CSV columns survive the trip
+        let expected = MemoryStats {
+            fragmentation_score: None,
+            swap_total: None,
+            swap_free: None,
+            dirty: None,
+            writeback: None,
+            ..stats
+        };
+        prop_assert_eq!(format!("{:?}", from_csv_row(&row)), format!("{:?}", expected));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn bincode_round_trips(stats in memory_stats()) {
+        let encoded = memory_core::memory::to_bincode(&stats).unwrap();
+        let decoded = memory_core::memory::from_bincode(&encoded).unwrap();
+
+        prop_assert_eq!(format!("{:?}", decoded), format!("{:?}", stats));
+    }
+}
@@ -0,0 +1,59 @@
+//! Checks that `SecureAlloc` wipes its buffer before freeing it.
+//!
+//! The test binary installs an allocator that inspects the bytes of one
+//! watched block on dealloc. Runs under Miri: `cargo miri test --test secure_alloc`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use memory_core::memory::secure::SecureAlloc;
+
+const NOT_SEEN: usize = 0;
+const FREED_ZEROED: usize = 1;
+const FREED_DIRTY: usize = 2;
+
+// Address of the block to inspect, 0 when nothing is watched
+static WATCHED: AtomicUsize = AtomicUsize::new(0);
+static RESULT: AtomicUsize = AtomicUsize::new(NOT_SEEN);
+
+struct InspectingAllocator;
+
+unsafe impl GlobalAlloc for InspectingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ptr as usize == WATCHED.load(Ordering::SeqCst) {
+            let bytes = std::slice::from_raw_parts(ptr, layout.size());
+            let result = if bytes.iter().all(|&byte| byte == 0) { FREED_ZEROED } else { FREED_DIRTY };
+            RESULT.store(result, Ordering::SeqCst);
+            WATCHED.store(0, Ordering::SeqCst);
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: InspectingAllocator = InspectingAllocator;
+
+#[test]
+fn buffer_is_zeroed_before_dealloc() {
+    let mut secret = SecureAlloc::new(64).unwrap();
+    assert!(secret.as_slice().iter().all(|&byte| byte == 0));
+
+    secret.as_mut_slice().fill(0x5A);
+    WATCHED.store(secret.as_slice().as_ptr() as usize, Ordering::SeqCst);
+    drop(secret);
+
+    assert_eq!(RESULT.load(Ordering::SeqCst), FREED_ZEROED);
+}
+
+#[test]
+fn zero_size_is_rejected() {
+    assert!(SecureAlloc::new(0).is_none());
+}
@@ -0,0 +1,78 @@
+//! Checks that instrumented operations reach a `tracing` subscriber.
+
+#![cfg(feature = "tracing")]
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use memory_core::memory::get_memory_stats;
+use memory_core::memory::healing::{DropCacheAction, DryRun, HealingAction};
+use memory_core::memory::reserve::EmergencyReserve;
+use tracing::Level;
+
+/// Writer collecting everything the fmt subscriber prints.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Captured {
+    fn output(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+/// Run `f` with a debug-level fmt subscriber and return what it printed.
+fn capture<F: FnOnce()>(f: F) -> String {
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    tracing::subscriber::with_default(subscriber, f);
+    captured.output()
+}
+
+#[test]
+fn get_memory_stats_records_its_return_value() {
+    let output = capture(|| {
+        get_memory_stats();
+    });
+
+    assert!(output.contains("get_memory_stats"), "no span in {:?}", output);
+    assert!(output.contains("return="), "no return value in {:?}", output);
+}
+
+#[test]
+fn dry_run_emits_an_info_event() {
+    let output = capture(|| {
+        let result = DryRun::new(DropCacheAction).execute();
+        assert!(result.dry_run);
+    });
+
+    assert!(output.contains("INFO"), "no info event in {:?}", output);
+    assert!(output.contains("dry run: would run drop_cache (priority 100)"), "{:?}", output);
+}
+
+#[test]
+fn releasing_the_emergency_reserve_emits_an_error_event() {
+    let output = capture(|| {
+        let mut reserve = EmergencyReserve::new(4096);
+        reserve.release();
+    });
+
+    assert!(output.contains("ERROR"), "no error event in {:?}", output);
+    assert!(output.contains("emergency memory reserve released: 4096 bytes"), "{:?}", output);
+}
@@ -0,0 +1,42 @@
+//! Runs the wasm32 `get_memory_stats` path in a wasm-bindgen test runner.
+//!
+//! `wasm-pack test --node -- --features wasm-bindgen`
+
+#![cfg(target_arch = "wasm32")]
+
+use memory_core::memory::get_memory_stats;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+#[wasm_bindgen_test]
+fn reports_the_wasm32_address_space() {
+    let stats = get_memory_stats();
+
+    assert_eq!(stats.total, 65536 * WASM_PAGE_SIZE);
+    assert_eq!(stats.free + stats.used, stats.total);
+    assert_eq!(stats.available, stats.free);
+    assert!(stats.used > 0);
+    assert_eq!(stats.used % WASM_PAGE_SIZE, 0);
+    assert!(stats.used_percent > 0.0 && stats.used_percent <= 100.0);
+    assert!(stats.buffers.is_none());
+    assert!(stats.cached.is_none());
+}
+
+#[wasm_bindgen_test]
+fn used_grows_with_linear_memory() {
+    let before = get_memory_stats().used;
+
+    // Force the allocator to grow linear memory by at least one page
+    let buffer = vec![1u8; 4 * WASM_PAGE_SIZE as usize];
+    let after = get_memory_stats().used;
+
+    assert!(after >= before);
+    assert!(after >= buffer.len() as u64);
+}
+
+#[cfg(feature = "wasm-bindgen")]
+#[wasm_bindgen_test]
+fn timestamp_comes_from_the_js_host() {
+    assert!(!get_memory_stats().timestamp.is_empty());
+}